@@ -0,0 +1,222 @@
+#!/usr/bin/env python3
+"""Generates src/jagged/unicode_tables.rs from the Unicode Character Database.
+
+The general categories are taken from Python's `unicodedata` module, the
+properties it does not expose are listed below for the same Unicode version.
+
+Usage: python3 scripts/unicode_tables.py > src/jagged/unicode_tables.rs
+"""
+import sys
+import unicodedata
+
+UNICODE_VERSION = "14.0.0"
+
+# PropList.txt: Other_Grapheme_Extend
+OTHER_GRAPHEME_EXTEND = [
+    (0x09BE, 0x09BE), (0x09D7, 0x09D7), (0x0B3E, 0x0B3E), (0x0B57, 0x0B57),
+    (0x0BBE, 0x0BBE), (0x0BD7, 0x0BD7), (0x0CC2, 0x0CC2), (0x0CD5, 0x0CD6),
+    (0x0D3E, 0x0D3E), (0x0D57, 0x0D57), (0x0DCF, 0x0DCF), (0x0DDF, 0x0DDF),
+    (0x1B35, 0x1B35), (0x200C, 0x200C), (0x302E, 0x302F), (0xFF9E, 0xFF9F),
+    (0x1133E, 0x1133E), (0x11357, 0x11357), (0x114B0, 0x114B0),
+    (0x114BD, 0x114BD), (0x115AF, 0x115AF), (0x11930, 0x11930),
+    (0x1D165, 0x1D165), (0x1D16E, 0x1D172), (0xE0020, 0xE007F),
+]
+
+# emoji-data.txt: Emoji_Modifier
+EMOJI_MODIFIER = [(0x1F3FB, 0x1F3FF)]
+
+# GraphemeBreakProperty.txt: Prepend
+PREPEND = [
+    (0x0600, 0x0605), (0x06DD, 0x06DD), (0x070F, 0x070F), (0x0890, 0x0891),
+    (0x08E2, 0x08E2), (0x0D4E, 0x0D4E), (0x110BD, 0x110BD), (0x110CD, 0x110CD),
+    (0x111C2, 0x111C3), (0x1193F, 0x1193F), (0x11941, 0x11941),
+    (0x11A3A, 0x11A3A), (0x11A84, 0x11A89), (0x11D46, 0x11D46),
+]
+
+# Spacing marks that do not extend a grapheme cluster, see UAX #29, table 2.
+SPACING_MARK_EXCEPTIONS = [
+    (0x102B, 0x102C), (0x1038, 0x1038), (0x1062, 0x1064), (0x1067, 0x106D),
+    (0x1083, 0x1083), (0x1087, 0x108C), (0x108F, 0x108F), (0x109A, 0x109C),
+    (0x1A61, 0x1A61), (0x1A63, 0x1A64), (0xAA7B, 0xAA7B), (0xAA7D, 0xAA7D),
+    (0x11720, 0x11721),
+]
+
+# Unassigned code points that are Default_Ignorable_Code_Point.
+UNASSIGNED_IGNORABLE = [
+    (0x2065, 0x2065), (0xFFF0, 0xFFF8), (0xE0000, 0xE0000),
+    (0xE0002, 0xE001F), (0xE0080, 0xE00FF), (0xE01F0, 0xE0FFF),
+]
+
+# emoji-data.txt: Extended_Pictographic
+EXTENDED_PICTOGRAPHIC = [
+    (0x00A9, 0x00A9), (0x00AE, 0x00AE), (0x203C, 0x203C), (0x2049, 0x2049),
+    (0x2122, 0x2122), (0x2139, 0x2139), (0x2194, 0x2199), (0x21A9, 0x21AA),
+    (0x231A, 0x231B), (0x2328, 0x2328), (0x2388, 0x2388), (0x23CF, 0x23CF),
+    (0x23E9, 0x23F3), (0x23F8, 0x23FA), (0x24C2, 0x24C2), (0x25AA, 0x25AB),
+    (0x25B6, 0x25B6), (0x25C0, 0x25C0), (0x25FB, 0x25FE), (0x2600, 0x2605),
+    (0x2607, 0x2612), (0x2614, 0x2685), (0x2690, 0x2705), (0x2708, 0x2712),
+    (0x2714, 0x2714), (0x2716, 0x2716), (0x271D, 0x271D), (0x2721, 0x2721),
+    (0x2728, 0x2728), (0x2733, 0x2734), (0x2744, 0x2744), (0x2747, 0x2747),
+    (0x274C, 0x274C), (0x274E, 0x274E), (0x2753, 0x2755), (0x2757, 0x2757),
+    (0x2763, 0x2767), (0x2795, 0x2797), (0x27A1, 0x27A1), (0x27B0, 0x27B0),
+    (0x27BF, 0x27BF), (0x2934, 0x2935), (0x2B05, 0x2B07), (0x2B1B, 0x2B1C),
+    (0x2B50, 0x2B50), (0x2B55, 0x2B55), (0x3030, 0x3030), (0x303D, 0x303D),
+    (0x3297, 0x3297), (0x3299, 0x3299), (0x1F000, 0x1F0FF),
+    (0x1F10D, 0x1F10F), (0x1F12F, 0x1F12F), (0x1F16C, 0x1F171),
+    (0x1F17E, 0x1F17F), (0x1F18E, 0x1F18E), (0x1F191, 0x1F19A),
+    (0x1F1AD, 0x1F1E5), (0x1F201, 0x1F20F), (0x1F21A, 0x1F21A),
+    (0x1F22F, 0x1F22F), (0x1F232, 0x1F23A), (0x1F23C, 0x1F23F),
+    (0x1F249, 0x1F3FA), (0x1F400, 0x1F53D), (0x1F546, 0x1F64F),
+    (0x1F680, 0x1F6FF), (0x1F774, 0x1F77F), (0x1F7D5, 0x1F7FF),
+    (0x1F80C, 0x1F80F), (0x1F848, 0x1F84F), (0x1F85A, 0x1F85F),
+    (0x1F888, 0x1F88F), (0x1F8AE, 0x1F8FF), (0x1F90C, 0x1F93A),
+    (0x1F93C, 0x1F945), (0x1F947, 0x1FAFF), (0x1FC00, 0x1FFFD),
+]
+
+HANGUL_L = [(0x1100, 0x115F), (0xA960, 0xA97C)]
+HANGUL_V = [(0x1160, 0x11A7), (0xD7B0, 0xD7C6)]
+HANGUL_T = [(0x11A8, 0x11FF), (0xD7CB, 0xD7FB)]
+HANGUL_SYLLABLES = (0xAC00, 0xD7A3)
+REGIONAL_INDICATOR = [(0x1F1E6, 0x1F1FF)]
+
+
+def contains(ranges, cp):
+    return any(start <= cp <= end for start, end in ranges)
+
+
+def grapheme_category(cp):
+    """Returns the Grapheme_Cluster_Break value of a code point, see UAX #29."""
+    if cp == 0x0D:
+        return "Cr"
+    if cp == 0x0A:
+        return "Lf"
+    if cp == 0x200D:
+        return "Zwj"
+    category = unicodedata.category(chr(cp))
+    if (
+        category in ("Mn", "Me")
+        or contains(OTHER_GRAPHEME_EXTEND, cp)
+        or contains(EMOJI_MODIFIER, cp)
+    ):
+        return "Extend"
+    if contains(PREPEND, cp):
+        return "Prepend"
+    if (
+        category in ("Cc", "Zl", "Zp", "Cf")
+        or category == "Cn" and contains(UNASSIGNED_IGNORABLE, cp)
+    ):
+        return "Control"
+    if contains(REGIONAL_INDICATOR, cp):
+        return "RegionalIndicator"
+    if cp in (0x0E33, 0x0EB3) or (
+        category == "Mc" and not contains(SPACING_MARK_EXCEPTIONS, cp)
+    ):
+        return "SpacingMark"
+    if contains(HANGUL_L, cp):
+        return "L"
+    if contains(HANGUL_V, cp):
+        return "V"
+    if contains(HANGUL_T, cp):
+        return "T"
+    if HANGUL_SYLLABLES[0] <= cp <= HANGUL_SYLLABLES[1]:
+        # Alternate every char, the category is computed instead.
+        return None
+    if contains(EXTENDED_PICTOGRAPHIC, cp):
+        return "ExtendedPictographic"
+    return None
+
+
+# Unassigned code points that default to East_Asian_Width=W, see EastAsianWidth.txt.
+DEFAULT_WIDE = [
+    (0x3400, 0x4DBF), (0x4E00, 0x9FFF), (0xF900, 0xFAFF), (0x20000, 0x2FFFD),
+    (0x30000, 0x3FFFD),
+]
+
+# Hangul vowel and trailing jamo, which are rendered within the preceding syllable.
+HANGUL_MEDIAL_FINAL = [(0x1160, 0x11FF), (0xD7B0, 0xD7FF)]
+
+
+def is_wide(cp):
+    """Whether a code point occupies two cells, see UAX #11."""
+    if unicodedata.category(chr(cp)) == "Cn":
+        return contains(DEFAULT_WIDE, cp) or None
+    return unicodedata.east_asian_width(chr(cp)) in ("W", "F") or None
+
+
+def is_zero_width(cp):
+    """Whether a code point occupies no cell of its own."""
+    category = unicodedata.category(chr(cp))
+    if category == "Cf":
+        # The soft hyphen and prepended concatenation marks are visible.
+        return (cp != 0x00AD and not contains(PREPEND, cp)) or None
+    return category in ("Mn", "Me") or contains(HANGUL_MEDIAL_FINAL, cp) or None
+
+
+def ranges(property_of):
+    """Returns the maximal ranges of code points with the same property value."""
+    result = []
+    for cp in range(0x110000):
+        if 0xD800 <= cp <= 0xDFFF:
+            continue
+        value = property_of(cp)
+        if value is None:
+            continue
+        if result and result[-1][1] + 1 == cp and result[-1][2] == value:
+            result[-1][1] = cp
+        else:
+            result.append([cp, cp, value])
+    return result
+
+
+def char(cp):
+    return "'\\u{%x}'" % cp
+
+
+def main():
+    assert unicodedata.unidata_version == UNICODE_VERSION, unicodedata.unidata_version
+    out = sys.stdout
+    out.write(
+        "//! Unicode property tables, generated by `scripts/unicode_tables.py` from\n"
+        "//! the Unicode Character Database %s. Do not edit by hand.\n\n" % UNICODE_VERSION
+    )
+    out.write(
+        (
+            "/// The value of the Grapheme_Cluster_Break property of a char, see Unicode\n"
+            "/// Standard Annex #29. Chars that are not listed in [`GRAPHEME_CATEGORIES`]\n"
+            "/// are `Other`, except for the Hangul syllables `U+%04X..=U+%04X`, which\n"
+            "/// are `Lv` or `Lvt`.\n" % HANGUL_SYLLABLES
+        )
+        + "#[derive(Debug, Clone, Copy, PartialEq, Eq)]\n"
+        + "pub(crate) enum GraphemeCategory {\n"
+    )
+    for name in (
+        "Cr", "Lf", "Control", "Extend", "Zwj", "RegionalIndicator", "Prepend",
+        "SpacingMark", "L", "V", "T", "Lv", "Lvt", "ExtendedPictographic", "Other",
+    ):
+        out.write("    %s,\n" % name)
+    out.write("}\n\n")
+    out.write(
+        "/// The ranges of chars with a Grapheme_Cluster_Break other than `Other`,\n"
+        "/// sorted by their first char.\n"
+        "#[rustfmt::skip]\n"
+        "pub(crate) const GRAPHEME_CATEGORIES: &[(char, char, GraphemeCategory)] = &[\n"
+    )
+    for start, end, value in ranges(grapheme_category):
+        out.write("    (%s, %s, GraphemeCategory::%s),\n" % (char(start), char(end), value))
+    out.write("];\n")
+    for name, doc, property_of in (
+        ("WIDE", "with an East_Asian_Width of Wide or Fullwidth,\n///", is_wide),
+        ("ZERO_WIDTH", "that occupy no cell, e.g. combining marks and format\n/// chars,", is_zero_width),
+    ):
+        out.write(
+            "\n/// The ranges of chars %s sorted by their first char.\n"
+            "#[rustfmt::skip]\n"
+            "pub(crate) const %s: &[(char, char)] = &[\n" % (doc, name)
+        )
+        for start, end, _ in ranges(property_of):
+            out.write("    (%s, %s),\n" % (char(start), char(end)))
+        out.write("];\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,63 @@
+//! # Error Module
+//!
+//! The `error` module defines the error type returned by the fallible `try_*`
+//! methods of [`Jagged`](crate::Jagged).
+use crate::Index2;
+
+/// An error that occurs when an operation on a jagged array receives invalid input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JaggedError {
+    /// The row index is out of bounds.
+    RowOutOfBounds {
+        /// The row that was accessed.
+        row: usize,
+        /// The number of rows.
+        len: usize,
+    },
+    /// The column index is out of bounds of its row.
+    ColOutOfBounds {
+        /// The position that was accessed.
+        index: Index2,
+        /// The number of columns of the row.
+        len: usize,
+    },
+    /// The start of a range lies behind its end.
+    InvertedRange {
+        /// The start of the range.
+        start: Index2,
+        /// The end of the range.
+        end: Index2,
+    },
+    /// The operation requires a non-empty array.
+    EmptyBuffer,
+    /// Two ranges of a batch of edits overlap.
+    OverlappingRanges {
+        /// The start of the range that overlaps its predecessor.
+        start: Index2,
+    },
+}
+
+impl std::fmt::Display for JaggedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RowOutOfBounds { row, len } => {
+                write!(f, "row {row} is out of bounds of {len} rows")
+            }
+            Self::ColOutOfBounds { index, len } => {
+                write!(
+                    f,
+                    "index {index} is out of bounds of a row with {len} columns"
+                )
+            }
+            Self::InvertedRange { start, end } => {
+                write!(f, "range start {start} is greater than range end {end}")
+            }
+            Self::EmptyBuffer => write!(f, "the array is empty"),
+            Self::OverlappingRanges { start } => {
+                write!(f, "range starting at {start} overlaps another range")
+            }
+        }
+    }
+}
+
+impl std::error::Error for JaggedError {}
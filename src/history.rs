@@ -0,0 +1,573 @@
+//! # History Module
+//!
+//! The `history` module provides [`UndoableJagged`], a wrapper around [`Jagged`]
+//! that records every mutation so that it can be undone and redone.
+use std::ops::{Deref, RangeBounds};
+
+use crate::{
+    traits::{JaggedRemove, JaggedSlice},
+    Index2, Jagged, JaggedIndex, JaggedStorage,
+};
+
+/// A recorded change: the elements from `start` to `old_end` were replaced by the
+/// elements from `start` to `new_end`, see [`ChangeEvent`](crate::ChangeEvent).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Change<T> {
+    start: Index2,
+    old_end: Index2,
+    new_end: Index2,
+    /// The removed elements, a row per line break.
+    removed: Vec<Vec<T>>,
+    /// The inserted elements, a row per line break.
+    inserted: Vec<Vec<T>>,
+    /// Whether the array had no rows before the change.
+    empty_before: bool,
+    /// Whether the array had no rows after the change.
+    empty_after: bool,
+}
+
+/// A [`Jagged`] array with undo and redo.
+///
+/// Every mutating method records the span it edited together with the removed
+/// and the inserted elements, so the memory cost of the history grows with the
+/// edited content, not with the size of the buffer. Undo and redo replace the
+/// span again, so marks and change events follow the edit.
+///
+/// Edits between [`UndoableJagged::begin_group`] and [`UndoableJagged::end_group`]
+/// are undone and redone as a single step.
+///
+/// The wrapper dereferences to [`Jagged`] for read access.
+///
+/// # Examples
+///
+/// ```
+/// use edtui_jagged::{history::UndoableJagged, Index2, Jagged};
+///
+/// let mut data = UndoableJagged::new(Jagged::from("hello\nworld"));
+///
+/// data.begin_group();
+/// let row = data.extract(Index2::new(0, 0)..Index2::new(1, 0));
+/// data.merge(&mut row.clone());
+/// data.end_group();
+/// assert_eq!(data.to_string(), "worldhello");
+///
+/// data.undo();
+/// assert_eq!(data.to_string(), "hello\nworld");
+///
+/// data.redo();
+/// assert_eq!(data.to_string(), "worldhello");
+/// ```
+#[derive(Debug, Clone)]
+pub struct UndoableJagged<T, S = Vec<Vec<T>>> {
+    data: Jagged<T, S>,
+    undo_stack: Vec<Vec<Change<T>>>,
+    redo_stack: Vec<Vec<Change<T>>>,
+    group: Vec<Change<T>>,
+    group_depth: usize,
+}
+
+impl<T: Clone, S: JaggedStorage<T>> UndoableJagged<T, S> {
+    /// Instantiates a new [`UndoableJagged`] with an empty history.
+    #[must_use]
+    pub fn new(data: Jagged<T, S>) -> Self {
+        Self {
+            data,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            group: Vec::new(),
+            group_depth: 0,
+        }
+    }
+
+    /// Consumes the wrapper and returns the underlying array.
+    pub fn into_inner(self) -> Jagged<T, S> {
+        self.data
+    }
+
+    /// Starts a group of edits that is undone as a single step.
+    ///
+    /// Groups can be nested, only the outermost group forms an undo step.
+    pub fn begin_group(&mut self) {
+        self.group_depth += 1;
+    }
+
+    /// Ends a group of edits started with [`UndoableJagged::begin_group`].
+    pub fn end_group(&mut self) {
+        self.group_depth = self.group_depth.saturating_sub(1);
+        if self.group_depth == 0 {
+            self.flush_group();
+        }
+    }
+
+    /// Returns `true` if there is an edit that can be undone.
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty() || !self.group.is_empty()
+    }
+
+    /// Returns `true` if there is an edit that can be redone.
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Undoes the last edit or group of edits.
+    /// Returns `false` if there was nothing to undo.
+    ///
+    /// An open group is closed before undoing.
+    pub fn undo(&mut self) -> bool {
+        self.group_depth = 0;
+        self.flush_group();
+        let Some(changes) = self.undo_stack.pop() else {
+            return false;
+        };
+        for change in changes.iter().rev() {
+            replace(
+                &mut self.data,
+                (change.start, change.new_end),
+                &change.removed,
+                change.empty_before,
+            );
+        }
+        self.redo_stack.push(changes);
+        true
+    }
+
+    /// Redoes the last undone edit or group of edits.
+    /// Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(changes) = self.redo_stack.pop() else {
+            return false;
+        };
+        for change in &changes {
+            replace(
+                &mut self.data,
+                (change.start, change.old_end),
+                &change.inserted,
+                change.empty_after,
+            );
+        }
+        self.undo_stack.push(changes);
+        true
+    }
+
+    /// Clears the undo and redo history.
+    pub fn clear_history(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.group.clear();
+    }
+
+    /// Clears the jagged array, removing all values.
+    pub fn clear(&mut self) {
+        let end = self.data.end_index();
+        self.record((Index2::default(), end), Jagged::clear);
+    }
+
+    /// Appends an element to the back of the array.
+    pub fn push<U>(&mut self, slice: U)
+    where
+        U: JaggedSlice<T, S>,
+    {
+        self.record_insertion(|data| data.push(slice));
+    }
+
+    /// Inserts an element at `position` within the rows, shifting all
+    /// elements after it.
+    pub fn insert<I, U>(&mut self, index: I, slice: U)
+    where
+        I: JaggedIndex<T, S>,
+        U: JaggedSlice<T, S, Index = I>,
+    {
+        self.record_insertion(|data| data.insert(index, slice));
+    }
+
+    /// Removes and returns the element at position index within the jagged array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove<I>(&mut self, index: I) -> I::Output
+    where
+        I: JaggedRemove<T, S>,
+    {
+        let range = index.removed_range(&self.data);
+        self.record(range, |data| data.remove(index))
+    }
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Jagged<T, S>) {
+        self.record_insertion(|data| data.append(other));
+    }
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// Similar to [`Self::append`] but fuses the last vector of `self` with the
+    /// first vector of `other`.
+    pub fn merge(&mut self, other: &mut Jagged<T, S>) {
+        self.record_insertion(|data| data.merge(other));
+    }
+
+    /// Joins two consecutive rows together. Merge `row_index` with `row_index` + 1.
+    pub fn join_lines(&mut self, row_index: usize) {
+        let line_break = Index2::new(row_index, self.data.len_col(row_index).unwrap_or(0));
+        let range = (line_break, Index2::new(row_index + 1, 0));
+        self.record(range, |data| data.join_lines(row_index));
+    }
+
+    /// Truncate lines up to the specified position.
+    pub fn truncate<I>(&mut self, index: I)
+    where
+        I: Into<Index2>,
+    {
+        let index = index.into();
+        let range = (self.clamp(index), self.data.end_index());
+        self.record(range, |data| data.truncate(index));
+    }
+
+    /// Splits the array into two at the given index.
+    ///
+    /// See [`Jagged::split_off`].
+    #[must_use]
+    pub fn split_off<I>(&mut self, at: I) -> Jagged<T, S>
+    where
+        I: Into<Index2>,
+    {
+        let at = at.into();
+        // Splitting off whole rows removes the line break in front of them.
+        let start = match at {
+            Index2 { row, col: 0 } if row > 0 => self.clamp(Index2::new(row - 1, usize::MAX)),
+            _ => self.clamp(at),
+        };
+        let range = (start, self.data.end_index());
+        self.record(range, |data| data.split_off(at))
+    }
+
+    /// Extracts a range of [Index2]..[Index2] and returns a newly allocated `Jagged<T>`.
+    ///
+    /// See [`Jagged::extract`].
+    #[must_use]
+    pub fn extract<R>(&mut self, range: R) -> Jagged<T, S>
+    where
+        R: RangeBounds<Index2>,
+    {
+        let Some((start, end)) = self
+            .data
+            .range_bounds((range.start_bound(), range.end_bound()))
+        else {
+            return Jagged::from_storage(S::default());
+        };
+        // An end at the end of its row may join the next row, and extracting the
+        // last rows removes the line break in front of them.
+        let end = if end.row + 1 < self.data.len() {
+            Index2::new(end.row + 1, 0)
+        } else {
+            self.data.end_index()
+        };
+        let start = match start.row.checked_sub(1) {
+            Some(row) => self.clamp(Index2::new(row, usize::MAX)),
+            None => self.clamp(start),
+        };
+        let span = (start, end);
+        self.record(span, |data| data.extract(range))
+    }
+
+    /// Clamps the column of `index` to the length of its row.
+    fn clamp(&self, index: Index2) -> Index2 {
+        let len = self.data.len_col(index.row).unwrap_or(0);
+        Index2::new(index.row, index.col.min(len))
+    }
+
+    /// Runs `edit`, which inserts elements without removing any, and records the
+    /// change.
+    fn record_insertion<F, R>(&mut self, edit: F) -> R
+    where
+        F: FnOnce(&mut Jagged<T, S>) -> R,
+    {
+        self.record((Index2::default(), Index2::default()), edit)
+    }
+
+    /// Runs `edit` and records the change it made.
+    ///
+    /// The elements from `range.0` to `range.1` (exclusive) are copied before the
+    /// edit, they must include every element that `edit` removes.
+    fn record<F, R>(&mut self, range: (Index2, Index2), edit: F) -> R
+    where
+        F: FnOnce(&mut Jagged<T, S>) -> R,
+    {
+        let (first, last) = range;
+        let copied = copy_span(&self.data, first, last.max(first));
+        let empty_before = self.data.is_empty();
+
+        let (output, events) = self.data.capture_changes(edit);
+
+        debug_assert!(events.len() <= 1, "an edit is recorded as a single change");
+        for event in events {
+            let (start, old_end) = event.range_removed;
+            let removed = if start == old_end {
+                vec![Vec::new()]
+            } else {
+                debug_assert!(
+                    first <= start && old_end <= last,
+                    "edit outside of {range:?}"
+                );
+                sub_span(&copied, first, start, old_end)
+            };
+            self.redo_stack.clear();
+            self.group.push(Change {
+                start,
+                old_end,
+                new_end: event.new_end,
+                removed,
+                inserted: copy_span(&self.data, start, event.new_end),
+                empty_before,
+                empty_after: self.data.is_empty(),
+            });
+            if self.group_depth == 0 {
+                self.flush_group();
+            }
+        }
+        output
+    }
+
+    fn flush_group(&mut self) {
+        if !self.group.is_empty() {
+            self.undo_stack.push(std::mem::take(&mut self.group));
+        }
+    }
+}
+
+impl<T, S> Deref for UndoableJagged<T, S> {
+    type Target = Jagged<T, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<T: Clone, S: JaggedStorage<T>> From<Jagged<T, S>> for UndoableJagged<T, S> {
+    fn from(value: Jagged<T, S>) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Copies the elements from `start` to `end` (exclusive), a row per line break.
+fn copy_span<T: Clone, S: JaggedStorage<T>>(
+    data: &Jagged<T, S>,
+    start: Index2,
+    end: Index2,
+) -> Vec<Vec<T>> {
+    (start.row..=end.row)
+        .map(|row| {
+            let first = if row == start.row { start.col } else { 0 };
+            let last = if row == end.row {
+                end.col
+            } else {
+                data.len_col(row).unwrap_or(0)
+            };
+            (first..last)
+                .map_while(|col| data.get(Index2::new(row, col)).cloned())
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the elements from `start` to `end` (exclusive) of `rows`, which hold
+/// the elements from `origin` on.
+fn sub_span<T: Clone>(rows: &[Vec<T>], origin: Index2, start: Index2, end: Index2) -> Vec<Vec<T>> {
+    let relative = |index: Index2| {
+        if index.row == origin.row {
+            Index2::new(0, index.col - origin.col)
+        } else {
+            Index2::new(index.row - origin.row, index.col)
+        }
+    };
+    let (start, end) = (relative(start), relative(end));
+    (start.row..=end.row)
+        .map(|row| {
+            let elements = rows.get(row).map_or(&[][..], Vec::as_slice);
+            let first = if row == start.row { start.col } else { 0 };
+            let last = if row == end.row {
+                end.col
+            } else {
+                elements.len()
+            };
+            elements[first.min(last)..last.min(elements.len())].to_vec()
+        })
+        .collect()
+}
+
+/// Replaces the elements from `range.0` to `range.1` (exclusive) with `rows`.
+/// Removes the remaining empty row if the array is to be `empty`.
+fn replace<T: Clone, S: JaggedStorage<T>>(
+    data: &mut Jagged<T, S>,
+    range: (Index2, Index2),
+    rows: &[Vec<T>],
+    empty: bool,
+) {
+    let replacement = Jagged::from_storage(S::from_rows(rows.to_vec()));
+    let _ = data.splice(range.0, range.1, replacement);
+    if empty {
+        data.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{index::RowIndex, ChangeEvent, Gravity};
+
+    #[test]
+    fn test_undo_redo() {
+        let original = Jagged::from("hello\n\nworld");
+        let mut data = UndoableJagged::new(original.clone());
+
+        data.push('!');
+        data.insert(Index2::new(0, 0), 'H');
+        data.remove(Index2::new(0, 1));
+        data.insert(RowIndex::new(1), vec!['x']);
+        data.remove(RowIndex::new(0));
+        data.join_lines(0);
+        data.truncate(Index2::new(1, 2));
+        let _ = data.split_off(Index2::new(0, 1));
+        assert_eq!(*data, Jagged::from("x"));
+
+        while data.undo() {}
+        assert_eq!(*data, original);
+        assert!(!data.can_undo());
+
+        while data.redo() {}
+        assert_eq!(*data, Jagged::from("x"));
+        assert!(!data.can_redo());
+    }
+
+    #[test]
+    fn test_undo_extract() {
+        let original = Jagged::from("first\n\nsecond\nthird");
+        for start_row in 0..5 {
+            for start_col in 0..8 {
+                for end_row in 0..5 {
+                    for end_col in 0..8 {
+                        let start = Index2::new(start_row, start_col);
+                        let end = Index2::new(end_row, end_col);
+                        let mut data = UndoableJagged::new(original.clone());
+                        let _ = data.extract(start..end);
+                        let extracted = data.data.clone();
+                        data.undo();
+                        assert_eq!(*data, original, "undo {start}..{end}");
+                        data.redo();
+                        assert_eq!(data.data, extracted, "redo {start}..{end}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_undo_group() {
+        let mut data = UndoableJagged::new(Jagged::from("one\ntwo\nthree"));
+
+        data.begin_group();
+        let mut row = data.extract(Index2::new(0, 0)..Index2::new(1, 0));
+        data.begin_group();
+        data.merge(&mut row);
+        data.end_group();
+        data.end_group();
+        assert_eq!(data.to_string(), "two\nthreeone");
+
+        assert!(data.undo());
+        assert_eq!(data.to_string(), "one\ntwo\nthree");
+        assert!(!data.undo());
+    }
+
+    #[test]
+    fn test_undo_empty_buffer() {
+        let mut data = UndoableJagged::new(Jagged::default());
+        data.push(vec![1, 2]);
+        data.clear();
+        data.append(&mut Jagged::new(vec![vec![3], vec![4]]));
+        assert_eq!(*data, Jagged::new(vec![vec![3], vec![4]]));
+
+        data.undo();
+        assert!(data.is_empty());
+        data.undo();
+        assert_eq!(*data, Jagged::new(vec![vec![1, 2]]));
+        data.undo();
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn test_redo_cleared_by_edit() {
+        let mut data = UndoableJagged::new(Jagged::new(vec![vec![1]]));
+        data.push(2);
+        data.undo();
+        assert!(data.can_redo());
+
+        data.push(3);
+        assert!(!data.can_redo());
+        assert_eq!(*data, Jagged::new(vec![vec![1, 3]]));
+    }
+
+    #[test]
+    fn test_undo_keeps_marks() {
+        let mut original = Jagged::from("hello world\nfoo");
+        let mark = original.add_mark(Index2::new(0, 8), Gravity::Left);
+        let mut data = UndoableJagged::new(original);
+
+        data.insert(Index2::new(0, 0), 'X');
+        assert_eq!(data.mark(mark), Some(Index2::new(0, 9)));
+        data.undo();
+        assert_eq!(data.mark(mark), Some(Index2::new(0, 8)));
+        data.redo();
+        assert_eq!(data.mark(mark), Some(Index2::new(0, 9)));
+
+        data.join_lines(0);
+        data.undo();
+        assert_eq!(data.mark(mark), Some(Index2::new(0, 9)));
+    }
+
+    #[test]
+    fn test_undo_change_events() {
+        let mut original = Jagged::from("ab\ncd");
+        original.track_changes(true);
+        let mut data = UndoableJagged::new(original);
+
+        data.insert(Index2::new(1, 1), 'x');
+        data.undo();
+        data.redo();
+
+        let (start, end) = (Index2::new(1, 1), Index2::new(1, 2));
+        let changes: Vec<ChangeEvent> = data.into_inner().drain_changes().collect();
+        let event = |removed, new_end, inserted_len| ChangeEvent {
+            range_removed: removed,
+            new_end,
+            inserted_len,
+        };
+        assert_eq!(
+            changes,
+            vec![
+                event((start, start), end, 1),
+                event((start, end), start, 0),
+                event((start, start), end, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undo_split_off_and_truncate() {
+        let original = Jagged::from("first\n\nsecond");
+        for (row, line) in original.iter_row().enumerate() {
+            for col in 0..=line.len() {
+                let index = Index2::new(row, col);
+                let mut data = UndoableJagged::new(original.clone());
+                let _ = data.split_off(index);
+                data.truncate(Index2::new(row.saturating_sub(1), col));
+                let edited = data.data.clone();
+                while data.undo() {}
+                assert_eq!(*data, original, "undo {index}");
+                while data.redo() {}
+                assert_eq!(data.data, edited, "redo {index}");
+            }
+        }
+    }
+}
@@ -3,7 +3,7 @@
 //! The `index` module provides types related to indexing elements within the jagged array.
 use std::cmp::Ordering;
 
-use crate::Jagged;
+use crate::{Jagged, JaggedStorage};
 
 /// An index representing a specific position in a 2d jagged array.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -38,7 +38,7 @@ impl Index2 {
 
     /// Whether the index is out of bounds
     #[must_use]
-    pub fn out_of_bounds<T>(&self, jagged: &Jagged<T>) -> bool {
+    pub fn out_of_bounds<T, S: JaggedStorage<T>>(&self, jagged: &Jagged<T, S>) -> bool {
         let Some(max_col) = jagged.len_col(self.row) else {
             return true;
         };
@@ -54,15 +54,21 @@ impl std::fmt::Display for Index2 {
 
 impl PartialOrd for Index2 {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Index2 {
+    fn cmp(&self, other: &Self) -> Ordering {
         match self.row.cmp(&other.row) {
-            Ordering::Less => Some(Ordering::Less),
-            Ordering::Greater => Some(Ordering::Greater),
-            Ordering::Equal => self.col.partial_cmp(&other.col),
+            Ordering::Equal => self.col.cmp(&other.col),
+            ordering => ordering,
         }
     }
 }
 
 /// An index representing a specific row in a jagged array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RowIndex(pub(crate) usize);
 
 impl RowIndex {
@@ -76,3 +82,9 @@ impl RowIndex {
         RowIndex(index)
     }
 }
+
+impl From<RowIndex> for Index2 {
+    fn from(value: RowIndex) -> Self {
+        Index2::new(value.0, 0)
+    }
+}
@@ -4,23 +4,52 @@
 //! the [`Jagged`] struct.
 //! This struct represents a generic container for working with an object where each
 //! element is organized into lines (rows).
+mod block;
+mod byte_offset;
+pub mod changes;
+pub mod cursors;
+mod display;
+mod fallible;
+mod grapheme;
 mod helper;
 mod iter;
+pub mod line_ending;
 pub mod lines;
+pub mod lsp;
+pub mod marks;
 mod match_indices;
+pub mod motions;
+mod pairs;
+#[cfg(feature = "regex")]
+mod regex_search;
+mod replace;
+pub mod row_find;
+pub mod search;
+pub mod selection;
+pub mod text_objects;
+mod unicode_tables;
+use byte_offset::ByteOffsets;
+use changes::ChangeEvent;
+use line_ending::LineEndings;
+use marks::Marks;
 use match_indices::MatchIndicesEq;
+#[cfg(feature = "regex")]
+pub use regex_search::RegexMatches;
+use search::{Pattern, SearchOptions};
 
 use crate::{
     index::RowIndex,
+    storage::row_range,
     traits::{JaggedRemove, JaggedSlice},
-    Index2, JaggedIndex,
+    Index2, JaggedIndex, JaggedStorage,
 };
 use std::{
     fmt::Debug,
+    marker::PhantomData,
     ops::{Bound, RangeBounds},
 };
 
-use self::match_indices::MatchIndices;
+use self::match_indices::{MatchIndices, MatchIndicesRev, MultiLineMatches};
 
 /// A generic container for working with an object, where each element is organized
 /// into lines (rows).
@@ -31,6 +60,8 @@ use self::match_indices::MatchIndices;
 /// # Generic Parameters
 ///
 /// - `T`: The data type of elements stored within the jagged array.
+/// - `S`: The storage backend of the rows, see [`JaggedStorage`]. Defaults to
+///   `Vec<Vec<T>>`.
 ///
 /// # Examples
 ///
@@ -46,11 +77,26 @@ use self::match_indices::MatchIndices;
 ///
 /// let lines = Jagged::new(data);
 /// ```
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Jagged<T> {
-    pub(crate) data: Vec<Vec<T>>,
+#[derive(Debug, Clone)]
+pub struct Jagged<T, S = Vec<Vec<T>>> {
+    pub(crate) data: S,
+    marks: Marks,
+    changes: Option<Vec<ChangeEvent>>,
+    byte_offsets: ByteOffsets<T>,
+    line_endings: LineEndings,
+    _marker: PhantomData<T>,
 }
 
+/// Two arrays are equal if their elements are equal, marks, recorded changes, line
+/// endings and caches are not compared.
+impl<T: PartialEq, S: PartialEq> PartialEq for Jagged<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<T: Eq, S: Eq> Eq for Jagged<T, S> {}
+
 impl<T> Default for Jagged<T> {
     fn default() -> Self {
         Self::new(Vec::new())
@@ -83,18 +129,67 @@ impl<T> Jagged<T> {
     where
         U: Into<Vec<Vec<T>>>,
     {
-        Jagged { data: data.into() }
+        Self::from_storage(data.into())
+    }
+}
+
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Instantiates a new [`Jagged`] object from a storage backend.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use edtui_jagged::Jagged;
+    ///
+    /// let lines = Jagged::from_storage(vec![vec![1, 2, 3], vec![4]]);
+    /// assert_eq!(lines.len(), 2);
+    /// ```
+    #[must_use]
+    pub fn from_storage(data: S) -> Self {
+        Jagged {
+            data,
+            marks: Marks::default(),
+            changes: None,
+            byte_offsets: ByteOffsets::default(),
+            line_endings: LineEndings::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Instantiates an empty [`Jagged`] object.
+    pub(crate) fn empty() -> Self {
+        Self::from_storage(S::default())
+    }
+
+    /// Consumes the array and returns the underlying storage.
+    pub fn into_storage(self) -> S {
+        self.data
+    }
+
+    /// Moves the rows into another storage, keeping the marks, the recorded
+    /// changes and the line endings.
+    pub(crate) fn convert_storage<U: JaggedStorage<T>>(self) -> Jagged<T, U> {
+        Jagged {
+            data: U::from_rows(self.data.into_rows()),
+            marks: self.marks,
+            changes: self.changes,
+            byte_offsets: self.byte_offsets,
+            line_endings: self.line_endings,
+            _marker: PhantomData,
+        }
     }
 
     /// Clears the jagged array, removing all values.
     pub fn clear(&mut self) {
+        let end = self.end_index();
         self.data.clear();
+        self.edited(Index2::default(), end, Index2::default());
     }
 
     /// Appends an element to the back of the array.
     pub fn push<U>(&mut self, slice: U)
     where
-        U: JaggedSlice<T>,
+        U: JaggedSlice<T, S>,
     {
         slice.push_into(self);
     }
@@ -103,20 +198,22 @@ impl<T> Jagged<T> {
     /// elements after it.
     pub fn insert<I, U>(&mut self, index: I, slice: U)
     where
-        I: JaggedIndex<T>,
-        U: JaggedSlice<T, Index = I>,
+        I: JaggedIndex<T, S>,
+        U: JaggedSlice<T, S, Index = I>,
     {
         slice.insert_into(index, self);
     }
 
     /// Removes and returns the element at position index within the jagged array.
     ///
+    /// See [`Jagged::try_remove`] for a version that does not panic.
+    ///
     /// # Panics
     ///
     /// Panics if `index` is out of bounds.
     pub fn remove<I>(&mut self, index: I) -> I::Output
     where
-        I: JaggedRemove<T>,
+        I: JaggedRemove<T, S>,
     {
         index.remove(self)
     }
@@ -125,7 +222,13 @@ impl<T> Jagged<T> {
     ///
     /// Use [`Self::merge`] if the arrays should be fused at tail and head instead.
     pub fn append(&mut self, other: &mut Self) {
-        self.data.append(&mut other.data);
+        let row = self.len();
+        let line_endings = other.line_endings.take();
+        let rows = std::mem::take(&mut other.data);
+        let count = rows.len().saturating_sub(1);
+        let _ = self.splice_rows(row, 0, rows);
+        self.line_endings.replace(row, count, &line_endings);
+        self.update_line_ending_bytes(row..row + count);
     }
 
     /// Moves all the elements of `other` into `self`, leaving `other` empty.
@@ -136,16 +239,24 @@ impl<T> Jagged<T> {
         if other.data.is_empty() {
             return;
         }
-        let last_row = self.last_row_index();
-        if !self.data.is_empty() {
-            self.data[last_row].append(&mut other.data.remove(0));
+        if self.data.is_empty() {
+            self.append(other);
+            return;
         }
-        self.data.append(&mut other.data);
+        let start = self.end_index();
+        let line_endings = other.line_endings.take();
+        let count = other.len() - 1;
+        let mut first = other.data.remove_row(0);
+        self.row_mut_unchecked(start.row).append(&mut first);
+        self.data.append_rows(&mut other.data);
+        self.edited(start, start, self.end_index());
+        self.line_endings.replace(start.row, count, &line_endings);
+        self.update_line_ending_bytes(start.row..start.row + count);
     }
 
     /// Consumes the Lines and returns the underlying nested vectors.
     pub fn into_vecs(self) -> Vec<Vec<T>> {
-        self.data
+        self.data.into_rows()
     }
 
     /// Joins two consecutive rows together. Merge `row_index` with `row_index` + 1.
@@ -157,11 +268,22 @@ impl<T> Jagged<T> {
     /// assert_eq!(data, Jagged::from("helloworld"));
     /// ````
     pub fn join_lines(&mut self, row_index: usize) {
+        if let Some(line_break) = self.join_rows(row_index) {
+            self.edited(line_break, Index2::new(row_index + 1, 0), line_break);
+        }
+    }
+
+    /// Joins two consecutive rows without recording the edit.
+    /// Returns the position of the removed line break.
+    fn join_rows(&mut self, row_index: usize) -> Option<Index2> {
         if row_index + 1 >= self.len() {
-            return;
+            return None;
         }
-        let mut row = self.data.remove(row_index + 1);
-        self.data[row_index].append(&mut row);
+        let mut row = self.data.remove_row(row_index + 1);
+        let current_row = self.row_mut_unchecked(row_index);
+        let line_break = Index2::new(row_index, current_row.len());
+        current_row.append(&mut row);
+        Some(line_break)
     }
 
     /// Truncate lines up to the specified position.
@@ -170,10 +292,13 @@ impl<T> Jagged<T> {
         I: Into<Index2>,
     {
         let index = index.into();
+        let end = self.end_index();
         if let Some(current_row) = self.get_mut(RowIndex::new(index.row)) {
+            let start = Index2::new(index.row, index.col.min(current_row.len()));
             current_row.truncate(index.col);
+            self.data.truncate_rows(index.row + 1);
+            self.edited(start, end, start);
         }
-        self.data.truncate(index.row + 1);
     }
 
     /// Splits a `Jagged` array into two at the given index.
@@ -187,17 +312,24 @@ impl<T> Jagged<T> {
         I: Into<Index2>,
     {
         let at = at.into();
-        if at.col == 0 {
-            Self::new(self.data.split_off(at.row))
+        let line_endings = self.line_endings.slice(at.row..self.len());
+        let mut split = if at.col == 0 {
+            let count = self.len().saturating_sub(at.row);
+            Self::from_storage(self.splice_rows(at.row, count, S::default()))
         } else {
-            let mut lines = self.data.remove(at.row);
+            let end = self.end_index();
+            let mut lines = self.data.remove_row(at.row);
             let rest = lines.split_off(at.col);
 
-            self.data.insert(at.row, lines);
-            self.data.insert(at.row + 1, rest);
+            self.data.insert_row(at.row, lines);
+            self.data.insert_row(at.row + 1, rest);
 
-            Self::new(self.data.split_off(at.row + 1))
-        }
+            let split = Self::from_storage(self.data.split_off_rows(at.row + 1));
+            self.edited(at, end, at);
+            split
+        };
+        split.line_endings = line_endings;
+        split
     }
 
     /// Returns `true` if the object contains no elements.
@@ -226,13 +358,13 @@ impl<T> Jagged<T> {
     /// Panics if `row` is out of bounds.
     #[must_use]
     pub fn len_col_unchecked(&self, row: usize) -> usize {
-        self.data[row].len()
+        self.len_col(row).expect("row index out of bounds")
     }
 
     /// Get the number of columns of a given row.
     /// Returns None if the row is out of bounds.
     pub fn len_col(&self, row: usize) -> Option<usize> {
-        self.data.get(row).map(std::vec::Vec::len)
+        self.data.row_len(row)
     }
 
     // /// Find the first index.
@@ -250,18 +382,22 @@ impl<T> Jagged<T> {
     #[must_use]
     pub fn get<I>(&self, index: I) -> Option<&I::Output>
     where
-        I: JaggedIndex<T>,
+        I: JaggedIndex<T, S>,
     {
         index.get(self)
     }
 
     /// Get a mutable reference to the element at a specific position.
     /// Returns `Some(&mut T)` if the position is valid, otherwise `None`.
+    ///
+    /// The caches that depend on the borrowed elements are invalidated, see
+    /// [`JaggedIndex::rows`].
     #[must_use]
     pub fn get_mut<I>(&mut self, index: I) -> Option<&mut I::Output>
     where
-        I: JaggedIndex<T>,
+        I: JaggedIndex<T, S>,
     {
+        self.invalidate_rows(index.rows());
         index.get_mut(self)
     }
 
@@ -430,7 +566,7 @@ impl<T> Jagged<T> {
         None
     }
 
-    fn range_bounds<R>(&self, range: R) -> Option<(Index2, Index2)>
+    pub(crate) fn range_bounds<R>(&self, range: R) -> Option<(Index2, Index2)>
     where
         R: RangeBounds<Index2>,
     {
@@ -474,33 +610,83 @@ impl<T> Jagged<T> {
     /// assert_eq!(data, Jagged::from(" world!"));
     /// ```
     #[must_use]
-    #[allow(clippy::too_many_lines)]
     pub fn extract<R>(&mut self, range: R) -> Self
     where
         R: RangeBounds<Index2>,
     {
+        let Some((start, end)) = self.range_bounds(range) else {
+            return Self::empty();
+        };
+        if start.row >= self.len() {
+            return Self::empty();
+        }
+        // The extraction removes a contiguous range of elements that starts at
+        // `start` and ends in the row after `end` at the latest. The elements
+        // behind the range are not touched, so its end can be derived from the
+        // length of the rows before and after the extraction.
+        let last_row = end.row.saturating_add(1).min(self.last_row_index());
+        let len_rows: Vec<usize> = (start.row..=last_row.max(start.row))
+            .map(|row| self.len_col_unchecked(row))
+            .collect();
+        let len_before = self.len();
+
+        let drained = self.extract_inclusive(start, end);
+
+        let old_len_col = |row: usize| len_rows[row - start.row];
+        let mut start = Index2::new(start.row, start.col.min(old_len_col(start.row)));
+        let old_end = if self.is_empty() {
+            start = Index2::default();
+            Index2::new(len_before - 1, old_len_col(len_before - 1))
+        } else if start.row >= self.len() {
+            let row = self.last_row_index();
+            start = Index2::new(row, self.len_col_unchecked(row));
+            Index2::new(len_before - 1, old_len_col(len_before - 1))
+        } else {
+            let row = start.row + len_before - self.len();
+            let tail = self.len_col_unchecked(start.row) - start.col;
+            Index2::new(row, old_len_col(row) - tail)
+        };
+        if old_end != start {
+            self.edited(start, old_end, start);
+        }
+        drained
+    }
+
+    /// Extracts the elements from `start` to `end` (inclusive), see [`Jagged::extract`].
+    ///
+    /// The extracted rows keep their line endings.
+    fn extract_inclusive(&mut self, start: Index2, end: Index2) -> Self {
+        let line_endings = self
+            .line_endings
+            .slice(start.row..end.row.saturating_add(1));
+        let mut drained = self.drain_inclusive(start, end);
+        drained.line_endings = line_endings;
+        drained
+    }
+
+    /// Removes the elements from `start` to `end` (inclusive) without recording
+    /// the edit, see [`Jagged::extract_inclusive`].
+    #[allow(clippy::too_many_lines)]
+    fn drain_inclusive(&mut self, start: Index2, end: Index2) -> Self {
         // This function is a bit of a mess. Turned out it is not that easy
         // to extract slices while trying to handle out of bounds gracefully
         // Maybe it would have been better to panic on out of bounds input.
 
         #[inline]
-        fn drain_into_jagged<U>(drain: std::vec::Drain<U>) -> Jagged<U> {
-            Jagged::new(vec![drain.collect::<Vec<U>>()])
+        fn drain_into_jagged<U, V: JaggedStorage<U>>(drain: std::vec::Drain<U>) -> Jagged<U, V> {
+            Jagged::from_storage(V::from_rows(vec![drain.collect::<Vec<U>>()]))
         }
         if self.is_empty() {
-            return Jagged::default();
+            return Self::empty();
         }
-
-        let Some((mut start, mut end)) = self.range_bounds(range) else {
-            return Jagged::default();
-        };
+        let (mut start, mut end) = (start, end);
 
         // Handle start row out of bounds, return empty
         if start.row > self.last_row_index() {
-            return Jagged::default();
+            return Self::empty();
         }
 
-        let mut drained = Jagged::<T>::default();
+        let mut drained = Self::empty();
 
         // Handle start col out of bounds
         let mut start_column_out_of_bounds = false;
@@ -558,23 +744,23 @@ impl<T> Jagged<T> {
         };
 
         if start > end || (start == end && start_column_out_of_bounds) {
-            return Jagged::default();
+            return Self::empty();
         }
 
         // Handle case where entire row is extracted (no splitting)
         if start.row == end.row && split_start.is_none() && split_end.is_none() {
-            drained.append(&mut self.extract_rows(start.row..=start.row));
+            drained.data.push_row(self.data.remove_row(start.row));
             return drained;
         }
 
         // Handle case where entire extraction happens on a single line (splitting)
         if start.row == end.row {
-            let row = &mut self.data[start.row];
+            let row = self.row_mut_unchecked(start.row);
             drained.append(&mut drain_into_jagged(row.drain(start.col..=end.col)));
             if start_column_out_of_bounds {
-                self.join_lines(start.row.saturating_sub(1));
+                self.join_rows(start.row.saturating_sub(1));
             } else if end_column_out_of_bounds {
-                self.join_lines(start.row);
+                self.join_rows(start.row);
             }
             return drained;
         }
@@ -584,22 +770,23 @@ impl<T> Jagged<T> {
         // the last line, if needed.
 
         if let Some(split_start) = split_start {
-            let row = &mut self.data[start.row];
+            let row = self.row_mut_unchecked(start.row);
             drained.append(&mut drain_into_jagged(row.drain(split_start..)));
         }
 
-        let mut drained_rows = self.extract_rows(extract_from..=extract_until);
+        let mut drained_rows =
+            Self::from_storage(self.data.drain_rows(extract_from..=extract_until));
         let num_drained_rows = drained_rows.len();
         drained.append(&mut drained_rows);
 
         if let Some(split_end) = split_end {
-            let row = &mut self.data[end.row.saturating_sub(num_drained_rows)];
+            let row = self.row_mut_unchecked(end.row.saturating_sub(num_drained_rows));
             let mut drained_row = drain_into_jagged(row.drain(..=split_end));
             drained.append(&mut drained_row);
         }
 
         if split_start.is_some() || start_column_out_of_bounds {
-            self.join_lines(start.row);
+            self.join_rows(start.row);
         }
 
         drained
@@ -621,11 +808,133 @@ impl<T> Jagged<T> {
     where
         R: RangeBounds<usize>,
     {
-        Jagged::new(self.data.drain(range).collect::<Vec<Vec<T>>>())
+        let (start, end) = row_range(&range, self.len());
+        let line_endings = self.line_endings.slice(start..end);
+        let mut rows = Self::from_storage(self.splice_rows(start, end - start, S::default()));
+        rows.line_endings = line_endings;
+        rows
+    }
+
+    /// Replaces `count` rows starting at `row` with `rows` and returns the removed rows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows are out of bounds.
+    pub(crate) fn splice_rows(&mut self, row: usize, count: usize, mut rows: S) -> S {
+        let inserted = rows.len();
+        let (start, old_end) = self.rows_range(row, count);
+
+        let mut removed = self.data.split_off_rows(row);
+        let mut tail = removed.split_off_rows(count);
+        self.data.append_rows(&mut rows);
+        self.data.append_rows(&mut tail);
+
+        let (_, new_end) = self.rows_range(row, inserted);
+        self.edited(start, old_end, new_end);
+        removed
+    }
+
+    /// Returns the range of elements that is covered by `count` rows starting at
+    /// `row`, including the line break that separates them from the other rows.
+    pub(crate) fn rows_range(&self, row: usize, count: usize) -> (Index2, Index2) {
+        let len_col = |row: usize| self.len_col(row).unwrap_or(0);
+        let end_row = row + count;
+        if end_row < self.len() || (count == 0 && row < self.len()) {
+            (Index2::new(row, 0), Index2::new(end_row, 0))
+        } else if row > 0 {
+            let start = Index2::new(row - 1, len_col(row - 1));
+            let end = if count == 0 {
+                start
+            } else {
+                Index2::new(end_row - 1, len_col(end_row - 1))
+            };
+            (start, end)
+        } else if count == 0 {
+            (Index2::default(), Index2::default())
+        } else {
+            (
+                Index2::default(),
+                Index2::new(end_row - 1, len_col(end_row - 1)),
+            )
+        }
+    }
+
+    /// Returns the position behind the last element.
+    pub(crate) fn end_index(&self) -> Index2 {
+        let row = self.last_row_index();
+        Index2::new(row, self.len_col(row).unwrap_or(0))
+    }
+
+    /// Records that the elements from `start` to `old_end` (exclusive) were replaced
+    /// by elements that end at `new_end`. Must be called by every method that moves
+    /// elements.
+    pub(crate) fn edited(&mut self, start: Index2, old_end: Index2, new_end: Index2) {
+        if start == old_end && start == new_end {
+            return;
+        }
+        self.marks.adjust(start, old_end, new_end);
+        self.line_endings.adjust(start, old_end, new_end);
+        self.update_byte_offsets(start, old_end, new_end);
+        self.record_change(start, old_end, new_end);
+    }
+
+    fn row_mut_unchecked(&mut self, row: usize) -> &mut Vec<T> {
+        self.data.row_mut(row).expect("row index out of bounds")
+    }
+
+    /// Replaces the elements from `start` to `end` (exclusive) with `replacement`.
+    ///
+    /// Positions at the end of a row refer to its line break, so `replacement`
+    /// and the removed elements may span multiple rows. An empty array is treated
+    /// as a single empty row.
+    ///
+    /// Returns the removed elements and the end of the inserted elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start > end` or if a position is out of bounds.
+    pub(crate) fn splice(
+        &mut self,
+        start: Index2,
+        end: Index2,
+        replacement: Self,
+    ) -> (Self, Index2) {
+        assert!(start <= end, "splice called with end < start");
+        if self.is_empty() {
+            self.data.push_row(Vec::new());
+        }
+        let mut tail = self.row_mut_unchecked(end.row).split_off(end.col);
+        let mut removed_rows = self.data.drain_rows(start.row + 1..=end.row);
+        let first = self.row_mut_unchecked(start.row).split_off(start.col);
+
+        let mut removed = Self::from_storage(S::from_rows(vec![first]));
+        removed.data.append_rows(&mut removed_rows);
+
+        let mut rows = replacement.data;
+        if rows.is_empty() {
+            rows.push_row(Vec::new());
+        }
+        let inserted_rows = rows.len() - 1;
+        let mut first = rows.remove_row(0);
+        let row = self.row_mut_unchecked(start.row);
+        let first_col = if inserted_rows == 0 { row.len() } else { 0 };
+        row.append(&mut first);
+
+        let mut after = self.data.split_off_rows(start.row + 1);
+        self.data.append_rows(&mut rows);
+        self.data.append_rows(&mut after);
+
+        let end_row = start.row + inserted_rows;
+        let last = self.row_mut_unchecked(end_row);
+        let new_end = Index2::new(end_row, first_col.max(last.len()));
+        last.append(&mut tail);
+
+        self.edited(start, end, new_end);
+        (removed, new_end)
     }
 }
 
-impl<T: Clone> Jagged<T> {
+impl<T: Clone, S: JaggedStorage<T>> Jagged<T, S> {
     // Flattens the jagged array into a single vector with optional line breaks.
     ///
     /// Returns a flattened `Vec<T>` where each row from the original structure is
@@ -634,8 +943,8 @@ impl<T: Clone> Jagged<T> {
     pub fn flatten(&self, line_break: &Option<T>) -> Vec<T> {
         let mut flattened = Vec::new();
 
-        for (i, row) in self.data.iter().enumerate() {
-            flattened.extend_from_slice(row);
+        for (i, row) in self.data.iter_rows().enumerate() {
+            flattened.extend_from_slice(&row);
             if i < self.data.len() - 1 {
                 if let Some(item) = line_break.clone() {
                     flattened.push(item);
@@ -661,9 +970,9 @@ impl<T: Clone> Jagged<T> {
     where
         R: RangeBounds<Index2>,
     {
-        let mut copied_lines = Self::default();
+        let mut copied_lines = Self::empty();
         let Some((mut start, mut end)) = self.range_bounds(range) else {
-            return Jagged::default();
+            return Self::empty();
         };
 
         // Handle start out of bounds
@@ -689,7 +998,7 @@ impl<T: Clone> Jagged<T> {
     }
 }
 
-impl<T: MatchIndicesEq> Jagged<T> {
+impl<T: MatchIndicesEq, S: JaggedStorage<T>> Jagged<T, S> {
     /// Returns an iterator that searches for disjoint matches of a pattern within the array.
     ///
     /// # Example
@@ -707,16 +1016,171 @@ impl<T: MatchIndicesEq> Jagged<T> {
     ///
     /// The iterator returned by this method yields tuples, where the first element
     /// is the matched slice and the second element is the corresponding index.
+    ///
+    /// Shorthand for [`Jagged::search`] with the default [`SearchOptions`].
+    #[must_use]
+    pub fn match_indices<'b>(&self, pattern: &'b [T]) -> MatchIndices<'_, 'b, T, S> {
+        self.search(pattern, SearchOptions::default())
+    }
+
+    /// Returns an iterator that searches for disjoint matches of a pattern within the
+    /// array, configured by [`SearchOptions`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Case, Index2, Jagged, SearchOptions};
+    ///
+    /// let jagged = Jagged::from("abc Abc");
+    /// let pattern: Vec<char> = vec!['A', 'b', 'c'];
+    ///
+    /// let options = SearchOptions::default().case(Case::Smart);
+    /// let mut matches = jagged.search(&pattern, options);
+    /// assert_eq!(matches.next().map(|(_, index)| index), Some(Index2::new(0, 4)));
+    /// assert_eq!(matches.next(), None);
+    /// ```
+    #[must_use]
+    pub fn search<'b>(
+        &self,
+        pattern: &'b [T],
+        options: SearchOptions,
+    ) -> MatchIndices<'_, 'b, T, S> {
+        MatchIndices::new(self, pattern, Index2::default(), options)
+    }
+
+    /// Returns an iterator over the disjoint matches of a pattern that start at
+    /// or after `index`.
+    ///
+    /// If [`SearchOptions::wrap_around`] is set, the search continues at the
+    /// start of the array and ends before `index`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged, SearchOptions};
+    ///
+    /// let jagged = Jagged::from("ab\nab\nab");
+    /// let pattern: Vec<char> = vec!['a', 'b'];
+    ///
+    /// let options = SearchOptions::default().wrap_around(true);
+    /// let rows: Vec<usize> = jagged
+    ///     .search_from(&pattern, Index2::new(1, 1), options)
+    ///     .map(|(_, index)| index.row)
+    ///     .collect();
+    /// assert_eq!(rows, vec![2, 0, 1]);
+    /// ```
     #[must_use]
-    pub fn match_indices<'b>(&self, pattern: &'b [T]) -> MatchIndices<'_, 'b, T> {
-        MatchIndices::new(self, pattern)
+    pub fn search_from<'b>(
+        &self,
+        pattern: &'b [T],
+        index: Index2,
+        options: SearchOptions,
+    ) -> MatchIndices<'_, 'b, T, S> {
+        MatchIndices::new(self, pattern, index, options)
+    }
+
+    /// Returns an iterator over the disjoint matches of a pattern that start before
+    /// `index`, in reverse order.
+    ///
+    /// If [`SearchOptions::wrap_around`] is set, the search continues at the
+    /// end of the array and ends at `index`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged, SearchOptions};
+    ///
+    /// let jagged = Jagged::from("ab\nab\nab");
+    /// let pattern: Vec<char> = vec!['a', 'b'];
+    ///
+    /// let options = SearchOptions::default().wrap_around(true);
+    /// let rows: Vec<usize> = jagged
+    ///     .search_backward(&pattern, Index2::new(1, 1), options)
+    ///     .map(|(_, index)| index.row)
+    ///     .collect();
+    /// assert_eq!(rows, vec![1, 0, 2]);
+    /// ```
+    #[must_use]
+    pub fn search_backward<'b>(
+        &self,
+        pattern: &'b [T],
+        index: Index2,
+        options: SearchOptions,
+    ) -> MatchIndicesRev<'_, 'b, T, S> {
+        MatchIndicesRev::new(self, pattern, index, options)
+    }
+
+    /// Returns an iterator over the disjoint matches of a pattern that may span
+    /// multiple rows.
+    ///
+    /// Each item is the start and the (exclusive) end of a match. Unlike
+    /// [`Jagged::search`], the pattern consists of [`Pattern`] items to express
+    /// line breaks, and the end of a match can not be derived from the length of
+    /// the pattern. [`Jagged::search`] keeps its signature for patterns within a
+    /// row.
+    ///
+    /// The matches are found in a single pass in `O(n + m)`, where `n` is the
+    /// number of elements and `m` the length of the pattern.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged, Pattern, SearchOptions};
+    ///
+    /// let jagged = Jagged::from("fn a() {\n}\nfn b() {\n}");
+    /// let pattern = Pattern::from_text("{\n}");
+    ///
+    /// let starts: Vec<Index2> = jagged
+    ///     .search_multiline(&pattern, SearchOptions::default())
+    ///     .map(|(start, _)| start)
+    ///     .collect();
+    /// assert_eq!(starts, vec![Index2::new(0, 7), Index2::new(2, 7)]);
+    /// ```
+    #[must_use]
+    pub fn search_multiline<'b>(
+        &self,
+        pattern: &'b [Pattern<T>],
+        options: SearchOptions,
+    ) -> MultiLineMatches<'_, 'b, T, S> {
+        MultiLineMatches::new(self, pattern, Index2::default(), options)
+    }
+
+    /// Returns an iterator over the disjoint matches of a pattern that may span
+    /// multiple rows and start at or after `index`.
+    ///
+    /// If [`SearchOptions::wrap_around`] is set, the search continues at the
+    /// start of the array and ends before `index`. See [`Jagged::search_multiline`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged, Pattern, SearchOptions};
+    ///
+    /// let jagged = Jagged::from("a {\n}\nb {\n}");
+    /// let pattern = Pattern::from_text("{\n}");
+    ///
+    /// let options = SearchOptions::default().wrap_around(true);
+    /// let starts: Vec<Index2> = jagged
+    ///     .search_multiline_from(&pattern, Index2::new(1, 0), options)
+    ///     .map(|(start, _)| start)
+    ///     .collect();
+    /// assert_eq!(starts, vec![Index2::new(2, 2), Index2::new(0, 2)]);
+    /// ```
+    #[must_use]
+    pub fn search_multiline_from<'b>(
+        &self,
+        pattern: &'b [Pattern<T>],
+        index: Index2,
+        options: SearchOptions,
+    ) -> MultiLineMatches<'_, 'b, T, S> {
+        MultiLineMatches::new(self, pattern, index, options)
     }
 }
 
-impl<T: Clone> Jagged<T> {
+impl<T: Clone, S: JaggedStorage<T>> Jagged<T, S> {
     /// Returns a clone of the underlying nested vectors.
     pub fn to_vecs(&self) -> Vec<Vec<T>> {
-        self.data.clone()
+        self.data.iter_rows().map(|row| row.to_vec()).collect()
     }
 }
 
@@ -1063,3 +1527,375 @@ mod tests {
         assert_eq!(data.copy_range(start..=end), Jagged::from("\nWo"));
     }
 }
+
+/// The tests of the default storage, run against the other storage backends.
+#[cfg(test)]
+mod storage_tests {
+    use crate::storage::{GapRows, Rope};
+    use crate::traits::RowSlice;
+
+    use super::*;
+
+    /// Runs the tests against the storage backend `$storage`.
+    macro_rules! storage_tests {
+        ($backend:ident, $storage:ident) => {
+            mod $backend {
+                use super::*;
+
+                type Storage<T> = $storage<T>;
+
+                fn new<T>(rows: Vec<Vec<T>>) -> Jagged<T, Storage<T>>
+                where
+                    Storage<T>: JaggedStorage<T>,
+                {
+                    Jagged::from_storage(Storage::from_rows(rows))
+                }
+
+                fn from(text: &str) -> Jagged<char, Storage<char>> {
+                    text.parse().unwrap()
+                }
+
+                #[test]
+                fn test_push() {
+                    let mut a = new(vec![vec![1, 2, 3]]);
+                    a.push(4);
+
+                    assert_eq!(a, new(vec![vec![1, 2, 3, 4]]));
+                }
+
+                #[test]
+                fn test_push_row() {
+                    let mut a = new(vec![vec![1, 2, 3]]);
+                    a.push(RowSlice::from(vec![4]));
+
+                    assert_eq!(a, new(vec![vec![1, 2, 3], vec![4]]));
+                }
+
+                #[test]
+                fn test_push_vec() {
+                    let mut a = new(vec![vec![1, 2, 3]]);
+                    a.push(vec![4]);
+
+                    assert_eq!(a, new(vec![vec![1, 2, 3], vec![4]]));
+                }
+
+                #[test]
+                fn test_append() {
+                    let mut a = new(vec![vec![1, 2, 3]]);
+                    let mut b = new(vec![vec![4, 5, 6]]);
+                    a.append(&mut b);
+
+                    assert_eq!(a, new(vec![vec![1, 2, 3], vec![4, 5, 6]]));
+                }
+
+                #[test]
+                fn test_merge() {
+                    let mut a = new(vec![vec![1, 2]]);
+                    let mut b = new(vec![vec![3], vec![4, 5, 6]]);
+
+                    a.merge(&mut b.clone());
+                    assert_eq!(a, new(vec![vec![1, 2, 3], vec![4, 5, 6]]));
+
+                    let mut a_empty = new(Vec::<Vec<i32>>::new());
+                    a_empty.merge(&mut b);
+                    assert_eq!(a_empty, new(vec![vec![3], vec![4, 5, 6]]));
+                }
+
+                #[test]
+                fn test_flatten() {
+                    // given
+                    let a = new(vec![vec![1], vec![], vec![2]]);
+                    // when
+                    let flattened = a.flatten(&Some(0));
+                    // then
+                    assert_eq!(flattened, vec![1, 0, 0, 2]);
+                }
+
+                #[test]
+                fn test_iter() {
+                    let lines = from(
+                        "Hello\n\
+                    World",
+                    );
+                    let mut iter = lines.iter_row();
+
+                    assert_eq!(iter.next().as_deref(), Some(&"Hello".chars().collect()));
+                    assert_eq!(iter.next().as_deref(), Some(&"World".chars().collect()));
+                    assert!(iter.next().is_none());
+                }
+
+                #[test]
+                fn test_split_off() {
+                    let data: Vec<Vec<i32>> = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+                    let mut a = new(data);
+
+                    let b = a.split_off(Index2::new(1, 1));
+                    assert_eq!(a, new(vec![vec![1, 2, 3], vec![4]]));
+                    assert_eq!(b, new(vec![vec![5, 6], vec![7, 8, 9]]));
+                }
+
+                #[test]
+                fn test_next() {
+                    let data: Vec<Vec<i32>> = vec![vec![1, 2, 3], vec![], vec![7, 8, 9]];
+                    let lines = new(data);
+
+                    assert_eq!(
+                        lines.next(Index2::new(0, 0)),
+                        Some((Some(&2), Index2::new(0, 1)))
+                    );
+                    assert_eq!(
+                        lines.next(Index2::new(0, 2)),
+                        Some((None, Index2::new(1, 0)))
+                    );
+                    assert_eq!(lines.next(Index2::new(2, 2)), None,);
+                }
+
+                #[test]
+                fn test_prev() {
+                    let data: Vec<Vec<i32>> = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+                    let lines = new(data);
+
+                    assert_eq!(
+                        lines.prev(Index2::new(1, 1)),
+                        Some((Some(&4), Index2::new(1, 0)))
+                    );
+                    assert_eq!(
+                        lines.prev(Index2::new(1, 0)),
+                        Some((Some(&3), Index2::new(0, 2)))
+                    );
+                    assert_eq!(lines.prev(Index2::new(0, 0)), None,);
+                }
+
+                #[test]
+                fn test_next_predicate() {
+                    let data: Vec<Vec<i32>> =
+                        vec![vec![1, 2, 3], vec![], vec![4, 5, 6], vec![7, 8, 9]];
+                    let lines = new(data);
+
+                    assert_eq!(
+                        lines.next_predicate(Index2::new(0, 2), |val| val == Some(&5)),
+                        Some((Some(&5), Index2::new(2, 1)))
+                    );
+                    assert_eq!(
+                        lines.next_predicate(Index2::new(0, 0), |val| val == Some(&99)),
+                        None,
+                    );
+                }
+
+                #[test]
+                fn test_prev_predicate() {
+                    let data: Vec<Vec<i32>> = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+                    let lines = new(data);
+
+                    assert_eq!(
+                        lines.prev_predicate(Index2::new(2, 2), |val| val == Some(&5)),
+                        Some((Some(&5), Index2::new(1, 1)))
+                    );
+                    assert_eq!(
+                        lines.prev_predicate(Index2::new(2, 2), |val| val == Some(&99)),
+                        None,
+                    );
+                }
+
+                #[test]
+                fn test_from_str() {
+                    let lines = from("H\n");
+
+                    assert_eq!(lines, new(vec![vec!['H'], vec![]]));
+                }
+
+                #[test]
+                fn test_extract() {
+                    // given
+                    let original = from("first\n\nsecond\nthird");
+
+                    // when
+                    let mut data = original.clone();
+                    let drained = data.extract(Index2::new(0, 0)..Index2::new(0, 2));
+
+                    //then
+                    let expected_drained = from("fi");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("rst\n\nsecond\nthird");
+                    assert_eq!(data, expected_remaining);
+
+                    // when
+                    let mut data = original.clone();
+                    let drained = data.extract(Index2::new(0, 0)..=Index2::new(1, 0));
+
+                    //then
+                    let expected_drained = from("first\n");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("second\nthird");
+                    assert_eq!(data, expected_remaining);
+
+                    // when
+                    let mut data = original.clone();
+                    let drained = data.extract(Index2::new(0, 2)..Index2::new(2, 2));
+
+                    //then
+                    let expected_drained = from("rst\n\nse");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("ficond\nthird");
+                    assert_eq!(data, expected_remaining);
+
+                    // when
+                    let mut data = original.clone();
+                    let drained = data.extract(Index2::new(2, 2)..Index2::new(3, 2));
+
+                    //then
+                    let expected_drained = from("cond\nth");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("first\n\nseird");
+                    assert_eq!(data, expected_remaining);
+
+                    // when
+                    let mut data = original.clone();
+                    let drained = data.extract(Index2::new(2, 0)..=Index2::new(3, 0));
+
+                    //then
+                    let expected_drained = from("second\nt");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("first\n\nhird");
+                    assert_eq!(data, expected_remaining);
+
+                    // when
+                    let mut data = original.clone();
+
+                    let drained = data.extract(Index2::new(1, 0)..=Index2::new(2, 1));
+
+                    //then
+                    let expected_drained = from("\nse");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("first\ncond\nthird");
+                    assert_eq!(data, expected_remaining);
+
+                    // when
+                    let mut data = original.clone();
+                    let drained = data.extract(Index2::new(1, 3)..=Index2::new(2, 1));
+
+                    //then
+                    let expected_drained = from("\nse");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("first\ncond\nthird");
+                    assert_eq!(data, expected_remaining);
+                }
+
+                #[test]
+                fn test_extract_out_of_bounds() {
+                    // given
+                    let original = from("first\nsecond");
+
+                    // when
+                    let mut data = original.clone();
+                    let drained = data.extract(Index2::new(0, 0)..Index2::new(0, 99));
+
+                    //then
+                    let expected_drained = from("first");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("second");
+                    assert_eq!(data, expected_remaining);
+
+                    // when
+                    let mut data = original.clone();
+                    let drained = data.extract(Index2::new(0, 99)..Index2::new(1, 99));
+
+                    //then
+                    let expected_drained = from("\nsecond");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("first");
+                    assert_eq!(data, expected_remaining);
+
+                    // when
+                    let mut data = original.clone();
+                    let drained = data.extract(Index2::new(0, 10)..Index2::new(11, 0));
+
+                    //then
+                    let expected_drained = from("\nsecond");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("first");
+                    assert_eq!(data, expected_remaining);
+
+                    // when
+                    let mut data = original.clone();
+                    let drained = data.extract(Index2::new(0, 1)..Index2::new(0, 99));
+
+                    //then
+                    let expected_drained = from("irst");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("fsecond");
+                    assert_eq!(data, expected_remaining);
+                }
+
+                #[test]
+                fn test_extract_empty_buffer() {
+                    // given
+                    let original = from("");
+
+                    // when
+                    let mut data = original.clone();
+                    let drained = data.extract(Index2::new(0, 0)..=Index2::new(0, 0));
+
+                    // then
+                    let expected_drained = from("");
+                    assert_eq!(drained, expected_drained);
+                    let expected_remaining = from("");
+                    assert_eq!(data, expected_remaining);
+                }
+
+                #[test]
+                fn test_extract_end_larger_than_start() {
+                    // given
+                    let original = from("first\nsecond");
+
+                    // when
+                    let mut data = original.clone();
+                    let _ = data.extract(Index2::new(0, 99)..Index2::new(0, 90));
+                }
+
+                #[test]
+                fn test_extract_end_equals_start() {
+                    // given
+                    let original = from("first\nsecond");
+
+                    // when
+                    let mut data = original.clone();
+                    let _ = data.extract(Index2::new(0, 1)..Index2::new(0, 1));
+                }
+
+                #[test]
+                fn test_copy_range() {
+                    let data = from("Hello\nWorld");
+
+                    let start = Index2::new(0, 3);
+                    let end = Index2::new(1, 1);
+                    assert_eq!(data.copy_range(start..=end), from("lo\nWo"));
+
+                    let start = Index2::new(0, 0);
+                    let end = Index2::new(0, 2);
+                    assert_eq!(data.copy_range(start..end), from("He"));
+
+                    let start = Index2::new(0, 0);
+                    let end = Index2::new(0, 1);
+                    assert_eq!(data.copy_range(start..end), from("H"));
+
+                    let start = Index2::new(0, 0);
+                    let end = Index2::new(0, 0);
+                    assert_eq!(data.copy_range(start..end), from(""));
+                }
+
+                #[test]
+                fn test_copy_range_out_of_bounds() {
+                    let data = from("Hello\nWorld");
+                    let start = Index2::new(0, 5);
+                    let end = Index2::new(1, 1);
+
+                    assert_eq!(data.copy_range(start..=end), from("\nWo"));
+                }
+            }
+        };
+    }
+
+    storage_tests!(rope, Rope);
+    storage_tests!(gap_buffer, GapRows);
+}
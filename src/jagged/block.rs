@@ -0,0 +1,206 @@
+use std::ops::{Bound, RangeBounds};
+
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// Converts a range into `(start, end)` with an exclusive end, clamped to `len`.
+fn clamped_range<R>(range: &R, len: usize) -> (usize, usize)
+where
+    R: RangeBounds<usize>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.saturating_add(1),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    let end = end.min(len);
+    (start.min(end), end)
+}
+
+/// Returns the width of a block with the columns `range`, None if it is unbounded.
+fn block_width<R>(range: &R) -> Option<usize>
+where
+    R: RangeBounds<usize>,
+{
+    let (start, end) = clamped_range(range, usize::MAX);
+    (end < usize::MAX).then_some(end - start)
+}
+
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Removes the columns `cols` of the rows `rows` and returns them, one row for
+    /// every row of the block. Rows that are shorter than the block contribute
+    /// the columns they have.
+    pub(crate) fn extract_block_unpadded<R, C>(&mut self, rows: R, cols: C) -> Self
+    where
+        R: RangeBounds<usize>,
+        C: RangeBounds<usize>,
+    {
+        let (first_row, end_row) = clamped_range(&rows, self.len());
+        let mut block = Self::empty();
+        for row in first_row..end_row {
+            let (start, end) = clamped_range(&cols, self.len_col_unchecked(row));
+            let (mut removed, _) = self.splice(
+                Index2::new(row, start),
+                Index2::new(row, end),
+                Self::empty(),
+            );
+            block.data.append_rows(&mut removed.data);
+        }
+        block
+    }
+}
+
+impl<T: Clone, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Removes the columns `cols` of the rows `rows` and returns them, one row for
+    /// every row of the block.
+    ///
+    /// Rows that are shorter than the block contribute the columns they have. If
+    /// `fill` is given and `cols` is bounded, the rows of the returned block are
+    /// padded with `fill` to the width of the block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::Jagged;
+    ///
+    /// let mut data = Jagged::from("abcd\ne\nfghi");
+    /// let block = data.extract_block(.., 1..3, None);
+    ///
+    /// assert_eq!(block, Jagged::from("bc\n\ngh"));
+    /// assert_eq!(data, Jagged::from("ad\ne\nfi"));
+    ///
+    /// let block = data.extract_block(.., 1..3, Some('.'));
+    /// assert_eq!(block, Jagged::from("d.\n..\ni."));
+    /// ```
+    pub fn extract_block<R, C>(&mut self, rows: R, cols: C, fill: Option<T>) -> Self
+    where
+        R: RangeBounds<usize>,
+        C: RangeBounds<usize>,
+    {
+        let width = block_width(&cols);
+        let mut block = self.extract_block_unpadded(rows, cols);
+        if let (Some(fill), Some(width)) = (fill, width) {
+            for row in 0..block.len() {
+                let row = block.data.row_mut(row).expect("row index out of bounds");
+                row.resize(row.len().max(width), fill.clone());
+            }
+        }
+        block
+    }
+
+    /// Returns a copy of the columns `cols` of the rows `rows`.
+    ///
+    /// See [`Jagged::extract_block`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::Jagged;
+    ///
+    /// let data = Jagged::from("abcd\ne\nfghi");
+    /// assert_eq!(data.copy_block(1.., ..=1, None), Jagged::from("e\nfg"));
+    /// assert_eq!(data.copy_block(1.., ..=1, Some(' ')), Jagged::from("e \nfg"));
+    /// ```
+    #[must_use]
+    pub fn copy_block<R, C>(&self, rows: R, cols: C, fill: Option<T>) -> Self
+    where
+        R: RangeBounds<usize>,
+        C: RangeBounds<usize>,
+    {
+        let width = block_width(&cols);
+        let (first_row, end_row) = clamped_range(&rows, self.len());
+        let rows = (first_row..end_row)
+            .filter_map(|row| self.data.row(row))
+            .map(|row| {
+                let (start, end) = clamped_range(&cols, row.len());
+                let mut elements = row[start..end].to_vec();
+                if let (Some(fill), Some(width)) = (&fill, width) {
+                    elements.resize(elements.len().max(width), fill.clone());
+                }
+                elements
+            })
+            .collect();
+        Self::from_storage(S::from_rows(rows))
+    }
+
+    /// Inserts every row of `block` at column `at.col` of the rows starting at `at.row`.
+    ///
+    /// Rows that are shorter than `at.col` are padded with `fill` up to the column,
+    /// or receive the block row at their end if `fill` is None. Missing rows are
+    /// appended.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("abc\nd");
+    /// data.insert_block(Index2::new(0, 2), Jagged::from("1\n2\n3"), Some(' '));
+    /// assert_eq!(data, Jagged::from("ab1c\nd 2\n  3"));
+    /// ```
+    pub fn insert_block(&mut self, at: Index2, block: Self, fill: Option<T>) {
+        for (i, elements) in block.data.into_rows().into_iter().enumerate() {
+            let row = at.row + i;
+            while self.len() <= row {
+                self.push(Vec::new());
+            }
+            let len = self.len_col_unchecked(row);
+            let mut inserted = Vec::new();
+            if let Some(fill) = &fill {
+                inserted.resize(at.col.saturating_sub(len), fill.clone());
+            }
+            inserted.extend(elements);
+            let index = Index2::new(row, at.col.min(len));
+            let inserted = Self::from_storage(S::from_rows(vec![inserted]));
+            let _ = self.splice(index, index, inserted);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_block_clamps() {
+        let mut data = Jagged::from("abc\nde");
+        let block = data.extract_block(1..5, 1..=9, None);
+
+        assert_eq!(block, Jagged::from("e"));
+        assert_eq!(data, Jagged::from("abc\nd"));
+    }
+
+    #[test]
+    fn test_insert_block_without_fill() {
+        let mut data = Jagged::from("abc\n\nxyz");
+        data.insert_block(Index2::new(0, 1), Jagged::from("-\n-\n-"), None);
+
+        assert_eq!(data, Jagged::from("a-bc\n-\nx-yz"));
+    }
+
+    #[test]
+    fn test_insert_block_behind_last_row() {
+        let mut data = Jagged::from("ab\ncd");
+        data.insert_block(Index2::new(5, 0), Jagged::from("x\ny"), Some(' '));
+
+        assert_eq!(data, Jagged::from("ab\ncd\n\n\n\nx\ny"));
+    }
+
+    #[test]
+    fn test_block_fill() {
+        let mut data = Jagged::from("abc\n\nde");
+        assert_eq!(
+            data.copy_block(.., 1..=3, Some('-')),
+            Jagged::from("bc-\n---\ne--")
+        );
+        assert_eq!(data.copy_block(.., 1.., Some('-')), Jagged::from("bc\n\ne"));
+
+        let block = data.extract_block(1.., 1..3, Some('-'));
+        assert_eq!(block, Jagged::from("--\ne-"));
+        assert_eq!(data, Jagged::from("abc\n\nd"));
+    }
+}
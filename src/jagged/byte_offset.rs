@@ -0,0 +1,449 @@
+use std::{fmt::Debug, ops::Range};
+
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// The byte lengths of the rows of an array, see [`Jagged::track_byte_offsets`].
+///
+/// The length of a row includes its line ending. The lengths are kept in a Fenwick
+/// tree, so the byte offset of a row is a prefix sum that takes O(log n). Edits
+/// within rows update the tree in O(log n), edits that insert or remove rows
+/// rebuild it in O(n) without measuring the other rows again.
+#[derive(Clone)]
+pub(crate) struct ByteOffsets<T>(Option<Tracked<T>>);
+
+#[derive(Clone)]
+struct Tracked<T> {
+    /// Returns the number of bytes of an element.
+    measure: fn(&T) -> usize,
+    lens: RowLens,
+    /// The rows that were mutably borrowed since they were measured, see
+    /// [`Jagged::get_mut`].
+    stale: Range<usize>,
+}
+
+impl<T> Default for ByteOffsets<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T> Debug for ByteOffsets<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ByteOffsets").finish_non_exhaustive()
+    }
+}
+
+/// A Fenwick tree of row lengths.
+#[derive(Clone, Default)]
+struct RowLens {
+    lens: Vec<usize>,
+    /// The node `i` holds the sum of the `i & i.wrapping_neg()` lengths up to
+    /// and including the row `i - 1`.
+    tree: Vec<usize>,
+}
+
+impl RowLens {
+    fn new(lens: Vec<usize>) -> Self {
+        let mut tree = lens.clone();
+        for i in 1..=tree.len() {
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= tree.len() {
+                tree[parent - 1] += tree[i - 1];
+            }
+        }
+        Self { lens, tree }
+    }
+
+    fn len(&self) -> usize {
+        self.lens.len()
+    }
+
+    /// Sets the length of `row`.
+    fn set(&mut self, row: usize, len: usize) {
+        // The tree holds the sums modulo 2^n, which are exact as they never overflow.
+        let delta = len.wrapping_sub(self.lens[row]);
+        self.lens[row] = len;
+        let mut i = row + 1;
+        while i <= self.tree.len() {
+            self.tree[i - 1] = self.tree[i - 1].wrapping_add(delta);
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Returns the sum of the lengths of the rows before `row`.
+    fn prefix(&self, row: usize) -> usize {
+        let mut sum = 0;
+        let mut i = row.min(self.tree.len());
+        while i > 0 {
+            sum += self.tree[i - 1];
+            i &= i - 1;
+        }
+        sum
+    }
+
+    /// Returns the number of leading rows whose lengths sum up to at most `offset`.
+    fn search(&self, mut offset: usize) -> usize {
+        let mut row = 0;
+        let mut step = self.tree.len().checked_next_power_of_two().unwrap_or(0);
+        while step > 0 {
+            if row + step <= self.tree.len() && self.tree[row + step - 1] <= offset {
+                row += step;
+                offset -= self.tree[row - 1];
+            }
+            step /= 2;
+        }
+        row
+    }
+}
+
+/// Returns the number of UTF-8 bytes of a row.
+fn byte_len(row: &[char]) -> usize {
+    row.iter().map(|&ch| char_len(&ch)).sum()
+}
+
+/// Returns the number of UTF-8 bytes of a char.
+fn char_len(ch: &char) -> usize {
+    ch.len_utf8()
+}
+
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Updates the byte lengths after the elements from `start` to `old_end` were
+    /// replaced by elements that end at `new_end`, see [`Jagged::edited`].
+    pub(crate) fn update_byte_offsets(&mut self, start: Index2, old_end: Index2, new_end: Index2) {
+        let Some(mut tracked) = self.byte_offsets.0.take() else {
+            return;
+        };
+        let old_len = tracked.lens.len();
+        let old_rows = start.row..(old_end.row + 1).min(old_len);
+        let new_rows = start.row..(new_end.row + 1).min(self.len());
+        if start.row > old_len || old_len - old_rows.len() + new_rows.len() != self.len() {
+            self.byte_offsets.0 = Some(self.measure_rows(tracked.measure));
+            return;
+        }
+        // The stale rows behind the edit are shifted, the stale rows within the
+        // edit are measured again anyway.
+        let shift = |row: usize| row - old_rows.len() + new_rows.len();
+        let stale = tracked.stale.start..tracked.stale.end.min(start.row);
+        let stale_behind = shift(tracked.stale.start.max(old_rows.end))
+            ..shift(tracked.stale.end.max(old_rows.end));
+        tracked.stale = 0..0;
+
+        let measured: Vec<usize> = new_rows
+            .clone()
+            .map(|row| self.measure_row(tracked.measure, row))
+            .collect();
+        if old_rows.len() == new_rows.len() {
+            for (row, len) in new_rows.zip(measured) {
+                tracked.lens.set(row, len);
+            }
+        } else {
+            let mut lens = std::mem::take(&mut tracked.lens.lens);
+            lens.splice(old_rows, measured);
+            tracked.lens = RowLens::new(lens);
+        }
+        for row in stale.chain(stale_behind) {
+            tracked
+                .lens
+                .set(row, self.measure_row(tracked.measure, row));
+        }
+        self.byte_offsets.0 = Some(tracked);
+    }
+
+    /// Measures `rows` again after their line endings changed.
+    pub(crate) fn update_line_ending_bytes(&mut self, rows: Range<usize>) {
+        let Some(mut tracked) = self.byte_offsets.0.take() else {
+            return;
+        };
+        for row in rows.start..rows.end.min(tracked.lens.len()) {
+            tracked
+                .lens
+                .set(row, self.measure_row(tracked.measure, row));
+        }
+        self.byte_offsets.0 = Some(tracked);
+    }
+
+    /// Marks `rows` as stale before they are mutably borrowed, see
+    /// [`Jagged::get_mut`].
+    pub(crate) fn invalidate_rows(&mut self, rows: Range<usize>) {
+        let Some(mut tracked) = self.byte_offsets.0.take() else {
+            return;
+        };
+        for row in tracked.stale.clone() {
+            tracked
+                .lens
+                .set(row, self.measure_row(tracked.measure, row));
+        }
+        let end = rows.end.min(tracked.lens.len());
+        tracked.stale = rows.start.min(end)..end;
+        self.byte_offsets.0 = Some(tracked);
+    }
+
+    /// Measures every row.
+    fn measure_rows(&self, measure: fn(&T) -> usize) -> Tracked<T> {
+        let lens = (0..self.len())
+            .map(|row| self.measure_row(measure, row))
+            .collect();
+        Tracked {
+            measure,
+            lens: RowLens::new(lens),
+            stale: 0..0,
+        }
+    }
+
+    /// Returns the number of bytes of `row`, including its line ending.
+    fn measure_row(&self, measure: fn(&T) -> usize, row: usize) -> usize {
+        let len = self
+            .data
+            .fold_row(row, 0, |len, element| len + measure(element))
+            .unwrap_or(0);
+        if row + 1 < self.len() {
+            len + self.line_endings.get(row).as_str().len()
+        } else {
+            len
+        }
+    }
+}
+
+impl<S: JaggedStorage<char>> Jagged<char, S> {
+    /// Enables or disables the tracking of the byte length of every row, which
+    /// makes [`Jagged::byte_offset`] and [`Jagged::index_from_byte_offset`] take
+    /// O(log n) instead of O(n).
+    ///
+    /// Arrays that are created from a string track the byte lengths from the start.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let mut data = Jagged::new(vec![vec!['ä', 'b'], vec!['c']]);
+    /// data.track_byte_offsets(true);
+    /// assert_eq!(data.byte_offset(Index2::new(1, 0)), Some(4));
+    /// ```
+    pub fn track_byte_offsets(&mut self, enabled: bool) {
+        self.byte_offsets.0 = match (enabled, self.byte_offsets.0.take()) {
+            (true, None) => Some(self.measure_rows(char_len)),
+            (true, tracked) => tracked,
+            (false, _) => None,
+        };
+    }
+
+    /// Returns the UTF-8 byte offset of `index`, where rows are separated by their
+    /// line endings, see [`Jagged::line_ending_at`].
+    ///
+    /// The end of a row, i.e. its line break, is a valid index.
+    /// Returns None if the index is out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("äb\ncd");
+    /// assert_eq!(data.byte_offset(Index2::new(0, 1)), Some(2));
+    /// assert_eq!(data.byte_offset(Index2::new(1, 1)), Some(5));
+    /// assert_eq!(data.byte_offset(Index2::new(1, 3)), None);
+    ///
+    /// let data = Jagged::from("a\r\nb");
+    /// assert_eq!(data.byte_offset(Index2::new(1, 0)), Some(3));
+    /// ```
+    #[must_use]
+    pub fn byte_offset(&self, index: Index2) -> Option<usize> {
+        let row = self.data.row(index.row)?;
+        let col_offset = byte_len(row.get(..index.col)?);
+        Some(self.row_byte_offset(index.row) + col_offset)
+    }
+
+    /// Returns the [`Index2`] of a UTF-8 byte offset, where rows are separated by
+    /// their line endings.
+    ///
+    /// Returns None if the offset is out of bounds, does not lie on a char boundary
+    /// or lies within a `\r\n` line ending.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("äb\ncd");
+    /// assert_eq!(data.index_from_byte_offset(3), Some(Index2::new(0, 2)));
+    /// assert_eq!(data.index_from_byte_offset(4), Some(Index2::new(1, 0)));
+    /// assert_eq!(data.index_from_byte_offset(1), None);
+    /// ```
+    #[must_use]
+    pub fn index_from_byte_offset(&self, offset: usize) -> Option<Index2> {
+        if self.is_empty() {
+            return None;
+        }
+        let row = self.row_at_byte_offset(offset).min(self.last_row_index());
+        let mut remaining = offset - self.row_byte_offset(row);
+        let line = self.data.row(row)?;
+        for (col, ch) in line.iter().enumerate() {
+            if remaining == 0 {
+                return Some(Index2::new(row, col));
+            }
+            remaining = remaining.checked_sub(ch.len_utf8())?;
+        }
+        (remaining == 0).then_some(Index2::new(row, line.len()))
+    }
+
+    /// Returns the byte offset at which `row` starts.
+    fn row_byte_offset(&self, row: usize) -> usize {
+        let Some(tracked) = &self.byte_offsets.0 else {
+            return (0..row).map(|row| self.measure_row(char_len, row)).sum();
+        };
+        let stale = tracked.stale.start..tracked.stale.end.min(row);
+        stale.fold(tracked.lens.prefix(row), |offset, row| {
+            offset + self.measure_row(char_len, row) - tracked.lens.lens[row]
+        })
+    }
+
+    /// Returns the row that contains `offset`, or the number of rows if the offset
+    /// lies behind them.
+    fn row_at_byte_offset(&self, offset: usize) -> usize {
+        let Some(tracked) = &self.byte_offsets.0 else {
+            return self
+                .walk_rows(0..self.len(), 0, offset)
+                .unwrap_or(self.len());
+        };
+        let stale = tracked.stale.clone();
+        let start = tracked.lens.prefix(stale.start);
+        if stale.is_empty() || offset < start {
+            return tracked.lens.search(offset);
+        }
+        let end = stale.end;
+        self.walk_rows(stale, start, offset).unwrap_or_else(|| {
+            // The rows behind the stale rows are shifted by the change of their length.
+            tracked
+                .lens
+                .search(offset - self.row_byte_offset(end) + tracked.lens.prefix(end))
+        })
+    }
+
+    /// Returns the row of `rows`, starting at `start`, that contains `offset`.
+    fn walk_rows(&self, rows: Range<usize>, mut start: usize, offset: usize) -> Option<usize> {
+        for row in rows {
+            start += self.measure_row(char_len, row);
+            if offset < start {
+                return Some(row);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{index::RowIndex, LineEnding};
+
+    #[test]
+    fn test_byte_offset_roundtrip() {
+        let data = Jagged::from("aä\n\n€x\n");
+        let text = data.to_string();
+        for (offset, _) in text.char_indices().chain([(text.len(), ' ')]) {
+            let index = data.index_from_byte_offset(offset).unwrap();
+            assert_eq!(data.byte_offset(index), Some(offset));
+        }
+        assert_eq!(data.index_from_byte_offset(text.len() + 1), None);
+    }
+
+    #[test]
+    fn test_byte_offset_after_edits() {
+        let mut data = Jagged::from("ab\ncd\nef");
+        assert_eq!(data.byte_offset(Index2::new(2, 0)), Some(6));
+
+        data.insert(Index2::new(1, 0), 'ö');
+        assert_eq!(data.byte_offset(Index2::new(2, 0)), Some(8));
+
+        if let Some(ch) = data.get_mut(Index2::new(0, 0)) {
+            *ch = '€';
+        }
+        assert_eq!(data.byte_offset(Index2::new(2, 0)), Some(10));
+        assert_eq!(data.index_from_byte_offset(10), Some(Index2::new(2, 0)));
+
+        data.join_lines(0);
+        assert_eq!(data.index_from_byte_offset(9), Some(Index2::new(1, 0)));
+    }
+
+    #[test]
+    fn test_byte_offset_line_endings() {
+        let mut data = Jagged::from("a\r\nb\rc");
+        let text = data.to_string();
+        for (offset, _) in text.char_indices().chain([(text.len(), ' ')]) {
+            let index = data.index_from_byte_offset(offset);
+            if text[..offset].ends_with('\r') && text[offset..].starts_with('\n') {
+                assert_eq!(index, None);
+                continue;
+            }
+            assert_eq!(data.byte_offset(index.unwrap()), Some(offset));
+        }
+        assert_eq!(data.byte_offset(Index2::new(1, 0)), Some(3));
+        assert_eq!(data.byte_offset(Index2::new(2, 0)), Some(5));
+
+        data.set_line_ending(LineEnding::Lf);
+        assert_eq!(data.byte_offset(Index2::new(2, 0)), Some(4));
+    }
+
+    #[test]
+    fn test_byte_offsets_follow_edits() {
+        let mut data = Jagged::from("aä\r\n\n€x\rb");
+        let mut untracked = data.clone();
+        untracked.track_byte_offsets(false);
+        let untracked_index = |data: &Jagged<char>, offset| {
+            let mut data = data.clone();
+            data.track_byte_offsets(false);
+            data.index_from_byte_offset(offset)
+        };
+        let check = |data: &Jagged<char>| {
+            let text = data.to_string();
+            for (offset, _) in text.char_indices().chain([(text.len(), ' ')]) {
+                let index = data.index_from_byte_offset(offset);
+                assert_eq!(index, untracked_index(data, offset), "{text:?} at {offset}");
+                if let Some(index) = index {
+                    assert_eq!(data.byte_offset(index), Some(offset));
+                }
+            }
+        };
+
+        for data in [&mut data, &mut untracked] {
+            if let Some(row) = data.get_mut(RowIndex::new(2)) {
+                row.push('😀');
+            }
+            check(data);
+            data.insert(Index2::new(0, 0), 'x');
+            check(data);
+            if let Some(ch) = data.get_mut(Index2::new(3, 0)) {
+                *ch = 'ö';
+            }
+            data.insert(RowIndex::new(1), vec!['y', 'ü']);
+            check(data);
+            data.remove(RowIndex::new(0));
+            data.merge(&mut Jagged::from("\r\nz"));
+            check(data);
+            let _ = data.split_off(Index2::new(1, 0));
+            check(data);
+            data.clear();
+            check(data);
+        }
+        assert_eq!(data, untracked);
+    }
+
+    #[test]
+    fn test_row_lens() {
+        let lens = RowLens::new(vec![3, 0, 2, 5, 1]);
+        assert_eq!(
+            (0..=5).map(|row| lens.prefix(row)).collect::<Vec<_>>(),
+            vec![0, 3, 3, 5, 10, 11]
+        );
+        assert_eq!(lens.search(2), 0);
+        assert_eq!(lens.search(3), 2);
+        assert_eq!(lens.search(10), 4);
+        assert_eq!(lens.search(99), 5);
+
+        let mut lens = lens;
+        lens.set(1, 4);
+        assert_eq!(lens.prefix(5), 15);
+        assert_eq!(lens.search(6), 1);
+    }
+}
@@ -0,0 +1,217 @@
+//! # Changes Module
+//!
+//! The `changes` module contains [`ChangeEvent`], which describes a single
+//! mutation of a [`Jagged`] array. Once enabled with [`Jagged::track_changes`],
+//! every mutation appends an event to a queue that is emptied by
+//! [`Jagged::drain_changes`].
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// A mutation of a [`Jagged`] array.
+///
+/// The elements in `range_removed` were replaced by `inserted_len` elements
+/// that start at `range_removed.0` and end at `new_end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChangeEvent {
+    /// The start and the (exclusive) end of the removed elements, given in
+    /// coordinates before the change.
+    pub range_removed: (Index2, Index2),
+    /// The (exclusive) end of the inserted elements, given in coordinates after
+    /// the change.
+    pub new_end: Index2,
+    /// The number of inserted elements. A line break counts as one element.
+    pub inserted_len: usize,
+}
+
+impl ChangeEvent {
+    /// Returns `true` if the change only inserted elements.
+    #[must_use]
+    pub fn is_insertion(&self) -> bool {
+        self.range_removed.0 == self.range_removed.1
+    }
+
+    /// Returns `true` if the change only removed elements.
+    #[must_use]
+    pub fn is_removal(&self) -> bool {
+        self.range_removed.0 == self.new_end
+    }
+}
+
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Enables or disables the recording of [`ChangeEvent`]s.
+    ///
+    /// Disabling discards all events that were not drained yet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{ChangeEvent, Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("hello");
+    /// data.track_changes(true);
+    /// data.insert(Index2::new(0, 5), '!');
+    ///
+    /// let changes: Vec<ChangeEvent> = data.drain_changes().collect();
+    /// assert_eq!(
+    ///     changes,
+    ///     vec![ChangeEvent {
+    ///         range_removed: (Index2::new(0, 5), Index2::new(0, 5)),
+    ///         new_end: Index2::new(0, 6),
+    ///         inserted_len: 1,
+    ///     }]
+    /// );
+    /// ```
+    pub fn track_changes(&mut self, enabled: bool) {
+        match (enabled, &self.changes) {
+            (true, None) => self.changes = Some(Vec::new()),
+            (false, _) => self.changes = None,
+            (true, Some(_)) => {}
+        }
+    }
+
+    /// Returns `true` if [`ChangeEvent`]s are recorded.
+    #[must_use]
+    pub fn is_tracking_changes(&self) -> bool {
+        self.changes.is_some()
+    }
+
+    /// Returns the recorded [`ChangeEvent`]s that were not drained yet.
+    #[must_use]
+    pub fn pending_changes(&self) -> &[ChangeEvent] {
+        self.changes.as_deref().unwrap_or_default()
+    }
+
+    /// Removes the recorded [`ChangeEvent`]s and returns them in the order in which
+    /// they happened.
+    ///
+    /// Returns an empty iterator if changes are not tracked, tracking is not
+    /// enabled by draining.
+    pub fn drain_changes(&mut self) -> impl Iterator<Item = ChangeEvent> + '_ {
+        self.changes
+            .as_mut()
+            .into_iter()
+            .flat_map(|changes| changes.drain(..))
+    }
+
+    /// Runs `edit` and returns the [`ChangeEvent`]s of its mutations, whether or not
+    /// changes are tracked. Tracked events stay queued.
+    pub(crate) fn capture_changes<F, R>(&mut self, edit: F) -> (R, Vec<ChangeEvent>)
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let queued = self.changes.as_ref().map(Vec::len);
+        if queued.is_none() {
+            self.changes = Some(Vec::new());
+        }
+        let output = edit(self);
+        let changes = match queued {
+            None => self.changes.take().unwrap_or_default(),
+            Some(len) => self
+                .pending_changes()
+                .get(len..)
+                .unwrap_or_default()
+                .to_vec(),
+        };
+        (output, changes)
+    }
+
+    /// Records a [`ChangeEvent`] if changes are tracked.
+    pub(crate) fn record_change(&mut self, start: Index2, old_end: Index2, new_end: Index2) {
+        if self.changes.is_none() {
+            return;
+        }
+        let inserted_len = self.len_between(start, new_end);
+        if let Some(changes) = &mut self.changes {
+            changes.push(ChangeEvent {
+                range_removed: (start, old_end),
+                new_end,
+                inserted_len,
+            });
+        }
+    }
+
+    /// Returns the number of elements from `start` to `end` (exclusive), where a line
+    /// break counts as one element.
+    fn len_between(&self, start: Index2, end: Index2) -> usize {
+        if start.row == end.row {
+            return end.col.saturating_sub(start.col);
+        }
+        let first = self
+            .len_col(start.row)
+            .unwrap_or(0)
+            .saturating_sub(start.col);
+        let middle: usize = (start.row + 1..end.row)
+            .map(|row| self.len_col(row).unwrap_or(0))
+            .sum();
+        first + middle + end.col + (end.row - start.row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::RowIndex;
+
+    fn change(removed: (Index2, Index2), new_end: Index2, inserted_len: usize) -> ChangeEvent {
+        ChangeEvent {
+            range_removed: removed,
+            new_end,
+            inserted_len,
+        }
+    }
+
+    #[test]
+    fn test_change_events() {
+        let mut data = Jagged::from("ab\ncd");
+        data.push(vec!['e']);
+        assert!(data.pending_changes().is_empty());
+
+        data.track_changes(true);
+        data.push(vec!['f', 'g']);
+        data.join_lines(0);
+        data.remove(RowIndex::new(2));
+        let _ = data.extract(Index2::new(0, 1)..Index2::new(0, 3));
+        let _ = data.split_off(Index2::new(0, 1));
+
+        let (a, b) = (Index2::new(0, 1), Index2::new(0, 2));
+        let changes: Vec<ChangeEvent> = data.drain_changes().collect();
+        assert_eq!(
+            changes,
+            vec![
+                change((Index2::new(2, 1), Index2::new(2, 1)), Index2::new(3, 2), 3),
+                change((b, Index2::new(1, 0)), b, 0),
+                change((Index2::new(1, 1), Index2::new(2, 2)), Index2::new(1, 1), 0),
+                change((a, Index2::new(0, 3)), a, 0),
+                change((a, Index2::new(1, 1)), a, 0),
+            ]
+        );
+        assert!(data.pending_changes().is_empty());
+        assert_eq!(data, Jagged::from("a"));
+    }
+
+    #[test]
+    fn test_inserted_len() {
+        let mut data = Jagged::from("ab");
+        data.track_changes(true);
+        data.merge(&mut Jagged::from("x\n\nyz"));
+        let _ = data.replace_all(&['x'], &['1', '2'], .., crate::SearchOptions::default());
+
+        let changes: Vec<ChangeEvent> = data.drain_changes().collect();
+        assert_eq!(changes.len(), 2);
+        assert!(changes[0].is_insertion());
+        assert_eq!(changes[0].inserted_len, 5);
+        assert_eq!(changes[0].new_end, Index2::new(2, 2));
+        assert!(!changes[1].is_removal());
+        assert_eq!(changes[1].inserted_len, 2);
+        assert_eq!(changes[1].new_end, Index2::new(0, 4));
+    }
+
+    #[test]
+    fn test_drain_changes_untracked() {
+        let mut data = Jagged::from("ab");
+        assert_eq!(data.drain_changes().count(), 0);
+        assert!(!data.is_tracking_changes());
+
+        data.push('c');
+        assert!(data.pending_changes().is_empty());
+    }
+}
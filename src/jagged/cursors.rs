@@ -0,0 +1,305 @@
+//! # Cursors Module
+//!
+//! The `cursors` module contains [`Cursors`], a set of positions within a
+//! [`Jagged`] array, and the edits that are applied at every cursor at once.
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// A set of cursor positions, kept sorted and without duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use edtui_jagged::{Cursors, Index2, Jagged};
+///
+/// let mut data = Jagged::from("ab\ncd");
+/// let mut cursors: Cursors = [Index2::new(0, 1), Index2::new(1, 1)].into_iter().collect();
+///
+/// data.insert_at_all(&mut cursors, &Jagged::from("-"));
+/// assert_eq!(data, Jagged::from("a-b\nc-d"));
+/// assert_eq!(cursors.as_slice(), &[Index2::new(0, 2), Index2::new(1, 2)]);
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Cursors {
+    positions: Vec<Index2>,
+}
+
+impl Cursors {
+    /// Instantiates a new [`Cursors`] set with a single cursor.
+    #[must_use]
+    pub fn new(index: Index2) -> Self {
+        Self {
+            positions: vec![index],
+        }
+    }
+
+    /// Adds a cursor. Returns false if there already is a cursor at `index`.
+    pub fn add(&mut self, index: Index2) -> bool {
+        match self.positions.binary_search(&index) {
+            Ok(_) => false,
+            Err(i) => {
+                self.positions.insert(i, index);
+                true
+            }
+        }
+    }
+
+    /// Removes the cursor at `index`. Returns false if there is none.
+    pub fn remove(&mut self, index: Index2) -> bool {
+        match self.positions.binary_search(&index) {
+            Ok(i) => {
+                self.positions.remove(i);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the number of cursors.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns `true` if there are no cursors.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns the cursor positions in ascending order.
+    #[must_use]
+    pub fn as_slice(&self) -> &[Index2] {
+        &self.positions
+    }
+
+    /// Returns an iterator over the cursor positions in ascending order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Index2> {
+        self.positions.iter()
+    }
+
+    fn set(&mut self, mut positions: Vec<Index2>) {
+        positions.sort();
+        positions.dedup();
+        self.positions = positions;
+    }
+}
+
+impl FromIterator<Index2> for Cursors {
+    fn from_iter<I: IntoIterator<Item = Index2>>(iter: I) -> Self {
+        let mut cursors = Self::default();
+        cursors.set(iter.into_iter().collect());
+        cursors
+    }
+}
+
+impl<'a> IntoIterator for &'a Cursors {
+    type Item = &'a Index2;
+    type IntoIter = std::slice::Iter<'a, Index2>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Removes up to `count` elements before every cursor.
+    ///
+    /// A line break counts as one element. Ranges of different cursors that overlap
+    /// are merged, so cursors that meet are combined into one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Cursors, Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("ab\ncd");
+    /// let mut cursors: Cursors = [Index2::new(0, 1), Index2::new(1, 0)].into_iter().collect();
+    ///
+    /// data.remove_before_all(&mut cursors, 1);
+    /// assert_eq!(data, Jagged::from("bcd"));
+    /// assert_eq!(cursors.as_slice(), &[Index2::new(0, 0), Index2::new(0, 1)]);
+    /// ```
+    pub fn remove_before_all(&mut self, cursors: &mut Cursors, count: usize) {
+        if self.is_empty() {
+            return;
+        }
+        let ranges = cursors
+            .iter()
+            .map(|&index| {
+                let end = self.clamp_index(index);
+                (self.step_back(end, count), end)
+            })
+            .collect();
+        self.remove_ranges_at(cursors, ranges);
+    }
+
+    /// Removes up to `count` elements behind every cursor.
+    ///
+    /// A line break counts as one element. Ranges of different cursors that overlap
+    /// are merged, so cursors that meet are combined into one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Cursors, Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("ab\ncd");
+    /// let mut cursors: Cursors = [Index2::new(0, 1), Index2::new(1, 0)].into_iter().collect();
+    ///
+    /// data.remove_after_all(&mut cursors, 2);
+    /// assert_eq!(data, Jagged::from("a"));
+    /// assert_eq!(cursors.as_slice(), &[Index2::new(0, 1)]);
+    /// ```
+    pub fn remove_after_all(&mut self, cursors: &mut Cursors, count: usize) {
+        if self.is_empty() {
+            return;
+        }
+        let ranges = cursors
+            .iter()
+            .map(|&index| {
+                let start = self.clamp_index(index);
+                (start, self.step_forward(start, count))
+            })
+            .collect();
+        self.remove_ranges_at(cursors, ranges);
+    }
+
+    /// Removes the ranges of the cursors and moves every cursor to the start
+    /// of its range.
+    fn remove_ranges_at(&mut self, cursors: &mut Cursors, mut ranges: Vec<(Index2, Index2)>) {
+        ranges.sort();
+        let mut merged: Vec<(Index2, Index2)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        let changed = self.replace_ranges(merged, |_| Self::empty());
+        cursors.set(changed.into_iter().map(|(start, _)| start).collect());
+    }
+
+    /// Returns the position that lies `count` elements before `index`, where
+    /// a line break counts as one element.
+    fn step_back(&self, mut index: Index2, mut count: usize) -> Index2 {
+        while count > 0 {
+            if index.col >= count {
+                index.col -= count;
+                break;
+            }
+            if index.row == 0 {
+                index.col = 0;
+                break;
+            }
+            count -= index.col + 1;
+            index.row -= 1;
+            index.col = self.len_col_unchecked(index.row);
+        }
+        index
+    }
+
+    /// Returns the position that lies `count` elements behind `index`, where
+    /// a line break counts as one element.
+    fn step_forward(&self, mut index: Index2, mut count: usize) -> Index2 {
+        while count > 0 {
+            let len = self.len_col_unchecked(index.row);
+            if len - index.col >= count {
+                index.col += count;
+                break;
+            }
+            if index.row + 1 >= self.len() {
+                index.col = len;
+                break;
+            }
+            count -= len - index.col + 1;
+            index = Index2::new(index.row + 1, 0);
+        }
+        index
+    }
+
+    /// Clamps `index` to the positions of the array, including the end of each row.
+    fn clamp_index(&self, index: Index2) -> Index2 {
+        if self.is_empty() {
+            return Index2::default();
+        }
+        let row = index.row.min(self.last_row_index());
+        Index2::new(row, index.col.min(self.len_col_unchecked(row)))
+    }
+}
+
+impl<T: Clone, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Inserts `slice` at every cursor and moves the cursors behind the
+    /// inserted elements.
+    ///
+    /// `slice` may span multiple rows, the cursors that follow are shifted
+    /// accordingly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Cursors, Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("ab");
+    /// let mut cursors: Cursors = [Index2::new(0, 1), Index2::new(0, 2)].into_iter().collect();
+    ///
+    /// data.insert_at_all(&mut cursors, &Jagged::from("\n"));
+    /// assert_eq!(data, Jagged::from("a\nb\n"));
+    /// assert_eq!(cursors.as_slice(), &[Index2::new(1, 0), Index2::new(2, 0)]);
+    /// ```
+    pub fn insert_at_all(&mut self, cursors: &mut Cursors, slice: &Self) {
+        let mut positions: Vec<Index2> = cursors.iter().map(|&i| self.clamp_index(i)).collect();
+        positions.dedup();
+        let ranges = positions.into_iter().map(|index| (index, index)).collect();
+        let changed = self.replace_ranges(ranges, |_| {
+            Self::from_storage(S::from_rows(slice.to_vecs()))
+        });
+        cursors.set(changed.into_iter().map(|(_, end)| end).collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_at_all_multi_row() {
+        let mut data = Jagged::from("abc\nd");
+        let mut cursors: Cursors = [Index2::new(0, 0), Index2::new(0, 2), Index2::new(1, 1)]
+            .into_iter()
+            .collect();
+
+        data.insert_at_all(&mut cursors, &Jagged::from("x\ny"));
+        assert_eq!(data, Jagged::from("x\nyabx\nyc\ndx\ny"));
+        assert_eq!(
+            cursors.as_slice(),
+            &[Index2::new(1, 1), Index2::new(2, 1), Index2::new(4, 1)]
+        );
+    }
+
+    #[test]
+    fn test_remove_before_all_merges_cursors() {
+        let mut data = Jagged::from("abcd\nef");
+        let mut cursors: Cursors = [Index2::new(0, 2), Index2::new(0, 3), Index2::new(1, 1)]
+            .into_iter()
+            .collect();
+
+        data.remove_before_all(&mut cursors, 2);
+        assert_eq!(data, Jagged::from("df"));
+        assert_eq!(cursors.as_slice(), &[Index2::new(0, 0), Index2::new(0, 1)]);
+    }
+
+    #[test]
+    fn test_remove_after_all_clamps_at_end() {
+        let mut data = Jagged::from("ab\nc");
+        let mut cursors: Cursors = [Index2::new(0, 2), Index2::new(1, 0)].into_iter().collect();
+
+        data.remove_after_all(&mut cursors, 5);
+        assert_eq!(data, Jagged::from("ab"));
+        assert_eq!(cursors.as_slice(), &[Index2::new(0, 2)]);
+
+        let mut cursors: Cursors = [Index2::new(0, 0), Index2::new(0, 1)].into_iter().collect();
+        data.remove_before_all(&mut cursors, 1);
+        assert_eq!(data, Jagged::from("b"));
+        assert_eq!(cursors.as_slice(), &[Index2::new(0, 0)]);
+    }
+}
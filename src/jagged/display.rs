@@ -0,0 +1,170 @@
+use super::{
+    grapheme::{category, is_boundary},
+    unicode_tables::{GraphemeCategory, WIDE, ZERO_WIDTH},
+};
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// Selects the emoji presentation of the preceding char, which occupies two cells.
+const EMOJI_PRESENTATION_SELECTOR: char = '\u{FE0F}';
+
+/// Returns true if `ch` lies in one of the sorted `ranges`.
+fn in_ranges(ranges: &[(char, char)], ch: char) -> bool {
+    let i = ranges.partition_point(|&(_, end)| end < ch);
+    ranges.get(i).is_some_and(|&(start, _)| start <= ch)
+}
+
+/// Returns the number of cells `ch` occupies on its own, following the wide and
+/// fullwidth chars of Unicode Standard Annex #11.
+fn char_width(ch: char) -> usize {
+    if ch.is_control() || in_ranges(ZERO_WIDTH, ch) {
+        0
+    } else if in_ranges(WIDE, ch) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Returns the number of cells a grapheme cluster occupies when it starts at cell
+/// `col`. A cluster is rendered as one glyph, so an emoji zwj sequence occupies
+/// the cells of a single pictograph.
+fn cluster_width(cluster: &[char], col: usize, tab_width: usize) -> usize {
+    match cluster {
+        ['\t'] if tab_width == 0 => 0,
+        ['\t'] => tab_width - col % tab_width,
+        [first, ..] if first.is_control() => 0,
+        [first, _] if category(*first) == GraphemeCategory::RegionalIndicator => 2,
+        [_, rest @ ..] if rest.contains(&EMOJI_PRESENTATION_SELECTOR) => 2,
+        _ => cluster.iter().map(|&ch| char_width(ch)).max().unwrap_or(0),
+    }
+}
+
+/// Returns the number of cells of every char of `row`. The first char of a
+/// grapheme cluster occupies the cells of the whole cluster, the others none.
+fn char_widths(row: &[char], tab_width: usize) -> impl Iterator<Item = usize> + '_ {
+    let mut col = 0;
+    let mut cluster_end = 0;
+    (0..row.len()).map(move |start| {
+        if start < cluster_end {
+            return 0;
+        }
+        cluster_end = (start + 1..=row.len())
+            .find(|&end| is_boundary(row, end))
+            .unwrap_or(row.len());
+        let width = cluster_width(&row[start..cluster_end], col, tab_width);
+        col += width;
+        width
+    })
+}
+
+impl<S: JaggedStorage<char>> Jagged<char, S> {
+    /// Returns the cell at which the element at `index` is rendered.
+    ///
+    /// Wide chars, such as CJK ideographs, occupy two cells, combining marks none
+    /// and a tab extends to the next multiple of `tab_width`. A column behind the
+    /// end of the row is clamped to the end of the row, a row out of bounds has
+    /// a display column of zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("日本\tx");
+    /// assert_eq!(data.display_col(Index2::new(0, 1), 4), 2);
+    /// assert_eq!(data.display_col(Index2::new(0, 3), 4), 8);
+    /// ```
+    #[must_use]
+    pub fn display_col(&self, index: Index2, tab_width: usize) -> usize {
+        let Some(row) = self.data.row(index.row) else {
+            return 0;
+        };
+        char_widths(row, tab_width).take(index.col).sum()
+    }
+
+    /// Returns the index of the element that is rendered at cell `display_col`.
+    ///
+    /// A cell in the middle of a wide char or a tab resolves to that char. A cell
+    /// behind the end of the row resolves to the end of the row. This is the
+    /// inverse of [`Jagged::display_col`] and keeps the visual column when the
+    /// cursor moves to another row.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("abcd\n日本");
+    /// let display_col = data.display_col(Index2::new(0, 3), 4);
+    /// assert_eq!(data.index_at_display_col(1, display_col, 4), Index2::new(1, 1));
+    /// assert_eq!(data.index_at_display_col(1, 9, 4), Index2::new(1, 2));
+    /// ```
+    #[must_use]
+    pub fn index_at_display_col(&self, row: usize, display_col: usize, tab_width: usize) -> Index2 {
+        let Some(line) = self.data.row(row) else {
+            return Index2::new(row, 0);
+        };
+        let mut end = 0;
+        for (col, width) in char_widths(line, tab_width).enumerate() {
+            end += width;
+            if end > display_col {
+                return Index2::new(row, col);
+            }
+        }
+        Index2::new(row, line.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_col_roundtrip() {
+        let data = Jagged::from("a\tb日e\u{301}\t\u{FF21}");
+        for col in 0..=data.len_col(0).unwrap() {
+            let index = Index2::new(0, col);
+            let display_col = data.display_col(index, 4);
+            // The combining mark occupies no cell, its cell belongs to the tab.
+            let expected = if col == 5 { Index2::new(0, 6) } else { index };
+            assert_eq!(data.index_at_display_col(0, display_col, 4), expected);
+        }
+        assert_eq!(data.display_col(Index2::new(0, 9), 4), 14);
+    }
+
+    #[test]
+    fn test_index_at_display_col_within_wide_chars() {
+        let data = Jagged::from("\t日");
+        assert_eq!(data.index_at_display_col(0, 3, 8), Index2::new(0, 0));
+        assert_eq!(data.index_at_display_col(0, 9, 8), Index2::new(0, 1));
+        assert_eq!(data.index_at_display_col(0, 10, 8), Index2::new(0, 2));
+        assert_eq!(data.index_at_display_col(3, 10, 8), Index2::new(3, 0));
+    }
+
+    #[test]
+    fn test_display_col_of_wide_chars() {
+        // Transport symbols, symbols and pictographs extended-A, an umbrella with
+        // emoji presentation and the ideographic space.
+        for text in ["\u{1F680}", "\u{1FA70}", "\u{2614}", "\u{3000}"] {
+            assert_eq!(Jagged::from(text).display_col(Index2::new(0, 1), 4), 2);
+        }
+        // A text symbol is narrow, unless it is followed by the emoji selector.
+        let data = Jagged::from("\u{2764}\u{2764}\u{FE0F}");
+        assert_eq!(data.display_col(Index2::new(0, 1), 4), 1);
+        assert_eq!(data.display_col(Index2::new(0, 3), 4), 3);
+    }
+
+    #[test]
+    fn test_display_col_of_emoji_sequences() {
+        // A family joined by zero width joiners, a flag and a skin tone modifier.
+        let data = Jagged::from(
+            "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{1F1E9}\u{1F1EA}\u{1F44D}\u{1F3FD}x",
+        );
+        assert_eq!(data.display_col(Index2::new(0, 1), 4), 2);
+        assert_eq!(data.display_col(Index2::new(0, 5), 4), 2);
+        assert_eq!(data.display_col(Index2::new(0, 7), 4), 4);
+        assert_eq!(data.display_col(Index2::new(0, 9), 4), 6);
+        assert_eq!(data.index_at_display_col(0, 1, 4), Index2::new(0, 0));
+        assert_eq!(data.index_at_display_col(0, 6, 4), Index2::new(0, 9));
+    }
+}
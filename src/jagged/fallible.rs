@@ -0,0 +1,242 @@
+use std::ops::{Bound, RangeBounds};
+
+use crate::{
+    traits::{JaggedRemove, JaggedSlice},
+    Index2, Jagged, JaggedError, JaggedIndex, JaggedStorage,
+};
+
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Returns the number of columns of `row` or an error if it is out of bounds.
+    pub(crate) fn check_row(&self, row: usize) -> Result<usize, JaggedError> {
+        self.len_col(row).ok_or(JaggedError::RowOutOfBounds {
+            row,
+            len: self.len(),
+        })
+    }
+
+    /// Checks that `index` points to an element or to the end of its row.
+    pub(crate) fn check_position(&self, index: Index2) -> Result<(), JaggedError> {
+        let len = self.check_row(index.row)?;
+        if index.col > len {
+            return Err(JaggedError::ColOutOfBounds { index, len });
+        }
+        Ok(())
+    }
+
+    /// Inserts an element at `position` within the rows, shifting all
+    /// elements after it.
+    ///
+    /// Fallible version of [`Jagged::insert`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `index` is out of bounds.
+    pub fn try_insert<I, U>(&mut self, index: I, slice: U) -> Result<(), JaggedError>
+    where
+        I: JaggedIndex<T, S>,
+        U: JaggedSlice<T, S, Index = I>,
+    {
+        index.check_insert(self)?;
+        self.insert(index, slice);
+        Ok(())
+    }
+
+    /// Removes and returns the element at position index within the jagged array.
+    ///
+    /// Fallible version of [`Jagged::remove`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the array is empty or if `index` is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged, JaggedError};
+    ///
+    /// let mut data = Jagged::from("ab");
+    /// assert_eq!(data.try_remove(Index2::new(0, 1)), Ok('b'));
+    /// assert_eq!(
+    ///     data.try_remove(Index2::new(1, 0)),
+    ///     Err(JaggedError::RowOutOfBounds { row: 1, len: 1 })
+    /// );
+    /// ```
+    pub fn try_remove<I>(&mut self, index: I) -> Result<I::Output, JaggedError>
+    where
+        I: JaggedRemove<T, S>,
+    {
+        if self.is_empty() {
+            return Err(JaggedError::EmptyBuffer);
+        }
+        index.check_remove(self)?;
+        Ok(self.remove(index))
+    }
+
+    /// Joins two consecutive rows together. Merge `row_index` with `row_index` + 1.
+    ///
+    /// Fallible version of [`Jagged::join_lines`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `row_index` is the last row or out of bounds.
+    pub fn try_join_lines(&mut self, row_index: usize) -> Result<(), JaggedError> {
+        self.check_row(row_index)?;
+        self.check_row(row_index + 1)?;
+        self.join_lines(row_index);
+        Ok(())
+    }
+
+    /// Truncate lines up to the specified position.
+    ///
+    /// Fallible version of [`Jagged::truncate`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `index` is out of bounds.
+    pub fn try_truncate<I>(&mut self, index: I) -> Result<(), JaggedError>
+    where
+        I: Into<Index2>,
+    {
+        let index = index.into();
+        self.check_position(index)?;
+        self.truncate(index);
+        Ok(())
+    }
+
+    /// Splits a `Jagged` array into two at the given index.
+    ///
+    /// Fallible version of [`Jagged::split_off`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `at` is out of bounds.
+    pub fn try_split_off<I>(&mut self, at: I) -> Result<Self, JaggedError>
+    where
+        I: Into<Index2>,
+    {
+        let at = at.into();
+        if at.col != 0 || at.row != self.len() {
+            self.check_position(at)?;
+        }
+        Ok(self.split_off(at))
+    }
+
+    /// Extracts a range of [Index2]..[Index2] and returns a newly allocated `Jagged<T>`.
+    ///
+    /// Fallible version of [`Jagged::extract`]. Positions at the end of a row
+    /// are valid and refer to the line break.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the array is empty, if a bound of the range is out of
+    /// bounds or if the start of the range lies behind its end.
+    pub fn try_extract<R>(&mut self, range: R) -> Result<Self, JaggedError>
+    where
+        R: RangeBounds<Index2>,
+    {
+        if self.is_empty() {
+            return Err(JaggedError::EmptyBuffer);
+        }
+        let start = match range.start_bound() {
+            Bound::Included(&start) | Bound::Excluded(&start) => Some(start),
+            Bound::Unbounded => None,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) | Bound::Excluded(&end) => Some(end),
+            Bound::Unbounded => None,
+        };
+        for index in start.iter().chain(end.iter()) {
+            self.check_position(*index)?;
+        }
+        if let (Some(start), Some(end)) = (start, end) {
+            if start > end {
+                return Err(JaggedError::InvertedRange { start, end });
+            }
+        }
+        Ok(self.extract(range))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::RowIndex;
+
+    #[test]
+    fn test_try_insert() {
+        let mut data = Jagged::from("ab\ncd");
+        assert_eq!(data.try_insert(Index2::new(1, 2), 'e'), Ok(()));
+        assert_eq!(data.try_insert(RowIndex::new(2), vec!['f']), Ok(()));
+        assert_eq!(data, Jagged::from("ab\ncde\nf"));
+
+        assert_eq!(
+            data.try_insert(Index2::new(0, 3), 'x'),
+            Err(JaggedError::ColOutOfBounds {
+                index: Index2::new(0, 3),
+                len: 2
+            })
+        );
+        assert_eq!(
+            data.try_insert(RowIndex::new(4), vec![]),
+            Err(JaggedError::RowOutOfBounds { row: 4, len: 3 })
+        );
+    }
+
+    #[test]
+    fn test_try_remove() {
+        let mut data = Jagged::from("ab\n");
+        assert_eq!(
+            data.try_remove(Index2::new(1, 0)),
+            Err(JaggedError::ColOutOfBounds {
+                index: Index2::new(1, 0),
+                len: 0
+            })
+        );
+        assert_eq!(data.try_remove(RowIndex::new(1)), Ok(vec![]));
+        assert_eq!(data.try_remove(RowIndex::new(0)), Ok(vec!['a', 'b']));
+        assert_eq!(
+            data.try_remove(RowIndex::new(0)),
+            Err(JaggedError::EmptyBuffer)
+        );
+    }
+
+    #[test]
+    fn test_try_extract() {
+        let mut data = Jagged::from("first\nsecond");
+        assert_eq!(
+            data.try_extract(Index2::new(0, 3)..Index2::new(0, 1)),
+            Err(JaggedError::InvertedRange {
+                start: Index2::new(0, 3),
+                end: Index2::new(0, 1)
+            })
+        );
+        assert_eq!(
+            data.try_extract(Index2::new(0, 0)..Index2::new(0, 99)),
+            Err(JaggedError::ColOutOfBounds {
+                index: Index2::new(0, 99),
+                len: 5
+            })
+        );
+        assert_eq!(
+            data.try_extract(Index2::new(0, 5)..Index2::new(1, 3)),
+            Ok(Jagged::from("\nsec"))
+        );
+        assert_eq!(data, Jagged::from("firstond"));
+    }
+
+    #[test]
+    fn test_try_split_off_join_truncate() {
+        let mut data = Jagged::from("ab\ncd");
+        assert!(data.try_split_off(Index2::new(2, 1)).is_err());
+        assert_eq!(
+            data.try_split_off(Index2::new(2, 0)),
+            Ok(Jagged::new(vec![]))
+        );
+
+        assert!(data.try_join_lines(1).is_err());
+        assert_eq!(data.try_join_lines(0), Ok(()));
+
+        assert!(data.try_truncate(Index2::new(0, 5)).is_err());
+        assert_eq!(data.try_truncate(Index2::new(0, 3)), Ok(()));
+        assert_eq!(data, Jagged::from("abc"));
+    }
+}
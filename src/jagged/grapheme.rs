@@ -0,0 +1,366 @@
+//! # Grapheme Module
+//!
+//! The `grapheme` module provides a grapheme-aware view of a `Jagged<char>`.
+//! Its methods move, extract and remove whole user-perceived characters, so
+//! that a position never ends up between a letter and its combining accent or
+//! within an emoji sequence.
+//!
+//! Clusters are segmented by the extended grapheme cluster rules of Unicode
+//! Standard Annex #29, based on the tables in [`super::unicode_tables`].
+use std::ops::RangeBounds;
+
+use super::unicode_tables::{GraphemeCategory, GRAPHEME_CATEGORIES};
+use crate::{Index2, Jagged, JaggedStorage};
+
+const HANGUL_SYLLABLES: std::ops::RangeInclusive<char> = '\u{AC00}'..='\u{D7A3}';
+
+/// The number of Hangul syllables that share a leading and a vowel jamo.
+const HANGUL_TRAILING_COUNT: u32 = 28;
+
+/// Returns the grapheme cluster break category of `ch`.
+pub(super) fn category(ch: char) -> GraphemeCategory {
+    if HANGUL_SYLLABLES.contains(&ch) {
+        let index = ch as u32 - *HANGUL_SYLLABLES.start() as u32;
+        return if index % HANGUL_TRAILING_COUNT == 0 {
+            GraphemeCategory::Lv
+        } else {
+            GraphemeCategory::Lvt
+        };
+    }
+    GRAPHEME_CATEGORIES
+        .binary_search_by(|&(start, end, _)| {
+            if end < ch {
+                std::cmp::Ordering::Less
+            } else if start > ch {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .map_or(GraphemeCategory::Other, |i| GRAPHEME_CATEGORIES[i].2)
+}
+
+/// Returns true if a grapheme cluster boundary lies before `row[col]`.
+pub(super) fn is_boundary(row: &[char], col: usize) -> bool {
+    use GraphemeCategory::{
+        Control, Cr, Extend, ExtendedPictographic, Lf, Lv, Lvt, Prepend, RegionalIndicator,
+        SpacingMark, Zwj, L, T, V,
+    };
+    if col == 0 || col >= row.len() {
+        return true;
+    }
+    match (category(row[col - 1]), category(row[col])) {
+        (Cr, Lf) => false,
+        (Control | Cr | Lf, _) | (_, Control | Cr | Lf) => true,
+        (L, L | V | Lv | Lvt) | (Lv | V, V | T) | (Lvt | T, T) => false,
+        (_, Extend | Zwj | SpacingMark) | (Prepend, _) => false,
+        (Zwj, ExtendedPictographic) => {
+            // An emoji zwj sequence: a pictograph, extenders and a joiner.
+            let before = row[..col - 1].iter().rev().map(|&ch| category(ch));
+            let mut before = before.skip_while(|&category| category == Extend);
+            before.next() != Some(ExtendedPictographic)
+        }
+        (RegionalIndicator, RegionalIndicator) => {
+            // Regional indicators pair up, starting from the first of a run.
+            let run = row[..col]
+                .iter()
+                .rev()
+                .take_while(|&&ch| category(ch) == RegionalIndicator)
+                .count();
+            run % 2 == 0
+        }
+        _ => true,
+    }
+}
+
+impl<S: JaggedStorage<char>> Jagged<char, S> {
+    /// Returns true if `index` lies on a grapheme cluster boundary.
+    ///
+    /// The start and the end of a row are always boundaries.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("e\u{301}x");
+    /// assert!(data.is_grapheme_boundary(Index2::new(0, 0)));
+    /// assert!(!data.is_grapheme_boundary(Index2::new(0, 1)));
+    /// assert!(data.is_grapheme_boundary(Index2::new(0, 2)));
+    /// ```
+    #[must_use]
+    pub fn is_grapheme_boundary(&self, index: Index2) -> bool {
+        self.data
+            .row(index.row)
+            .map_or(true, |row| is_boundary(row, index.col))
+    }
+
+    /// Returns the start and the (exclusive) end of the grapheme cluster that
+    /// contains `index`.
+    ///
+    /// Returns None if there is no element at `index`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("a👍🏽b");
+    /// let grapheme = data.grapheme_at(Index2::new(0, 2));
+    /// assert_eq!(grapheme, Some((Index2::new(0, 1), Index2::new(0, 3))));
+    /// ```
+    #[must_use]
+    pub fn grapheme_at(&self, index: Index2) -> Option<(Index2, Index2)> {
+        let row = self.data.row(index.row)?;
+        if index.col >= row.len() {
+            return None;
+        }
+        let start = (0..=index.col)
+            .rev()
+            .find(|&col| is_boundary(row, col))
+            .unwrap_or_default();
+        let end = (index.col + 1..=row.len())
+            .find(|&col| is_boundary(row, col))
+            .unwrap_or(row.len());
+        Some((Index2::new(index.row, start), Index2::new(index.row, end)))
+    }
+
+    /// Returns the grapheme clusters of a row.
+    ///
+    /// Returns None if the row is out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::Jagged;
+    ///
+    /// let data = Jagged::from("🇩🇪!");
+    /// let graphemes: Vec<String> = data
+    ///     .graphemes(0)
+    ///     .unwrap()
+    ///     .map(|grapheme| grapheme.iter().collect())
+    ///     .collect();
+    /// assert_eq!(graphemes, vec!["🇩🇪", "!"]);
+    /// ```
+    #[must_use]
+    pub fn graphemes(&self, row: usize) -> Option<impl Iterator<Item = &[char]>> {
+        let row = self.data.row(row)?;
+        let mut start = 0;
+        Some(std::iter::from_fn(move || {
+            if start >= row.len() {
+                return None;
+            }
+            let end = (start + 1..=row.len())
+                .find(|&col| is_boundary(row, col))
+                .unwrap_or(row.len());
+            let grapheme = &row[start..end];
+            start = end;
+            Some(grapheme)
+        }))
+    }
+
+    /// Returns the start of the grapheme cluster that follows the one at `index`.
+    ///
+    /// Like [`Jagged::next`], moves to the start of the next row at the end of a row
+    /// and returns None at the end of the array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("e\u{301}x\ny");
+    /// assert_eq!(data.next_grapheme(Index2::new(0, 0)), Some(Index2::new(0, 2)));
+    /// assert_eq!(data.next_grapheme(Index2::new(0, 2)), Some(Index2::new(1, 0)));
+    /// assert_eq!(data.next_grapheme(Index2::new(1, 0)), None);
+    /// ```
+    #[must_use]
+    pub fn next_grapheme(&self, index: Index2) -> Option<Index2> {
+        let len = self.data.row(index.row)?.len();
+        let end = self.grapheme_at(index).map_or(len, |(_, end)| end.col);
+        if end < len {
+            Some(Index2::new(index.row, end))
+        } else if index.row + 1 < self.len() {
+            Some(Index2::new(index.row + 1, 0))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the start of the grapheme cluster that precedes the one at `index`.
+    ///
+    /// Like [`Jagged::prev`], moves to the last grapheme cluster of the previous
+    /// row at the start of a row and returns None at the start of the array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("x👨‍👩‍👧\ny");
+    /// assert_eq!(data.prev_grapheme(Index2::new(1, 0)), Some(Index2::new(0, 1)));
+    /// assert_eq!(data.prev_grapheme(Index2::new(0, 1)), Some(Index2::new(0, 0)));
+    /// assert_eq!(data.prev_grapheme(Index2::new(0, 0)), None);
+    /// ```
+    #[must_use]
+    pub fn prev_grapheme(&self, index: Index2) -> Option<Index2> {
+        let len = self.data.row(index.row)?.len();
+        let start = self.grapheme_at(index).map_or(len, |(start, _)| start.col);
+        if start > 0 {
+            let (prev, _) = self.grapheme_at(Index2::new(index.row, start - 1))?;
+            Some(prev)
+        } else if index.row > 0 {
+            let row = index.row - 1;
+            let last = self.len_col_unchecked(row).saturating_sub(1);
+            Some(
+                self.grapheme_at(Index2::new(row, last))
+                    .map_or(Index2::new(row, 0), |(s, _)| s),
+            )
+        } else {
+            None
+        }
+    }
+
+    /// Removes the grapheme cluster at `index` and returns its chars.
+    ///
+    /// Returns None if there is no element at `index`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("ae\u{301}b");
+    /// let removed = data.remove_grapheme(Index2::new(0, 2));
+    /// assert_eq!(removed, Some(vec!['e', '\u{301}']));
+    /// assert_eq!(data, Jagged::from("ab"));
+    /// ```
+    pub fn remove_grapheme(&mut self, index: Index2) -> Option<Vec<char>> {
+        let (start, end) = self.grapheme_at(index)?;
+        let (removed, _) = self.splice(start, end, Self::empty());
+        removed.data.into_rows().into_iter().next()
+    }
+
+    /// Extracts a range like [`Jagged::extract`], widened to whole grapheme clusters.
+    ///
+    /// A range that starts or ends within a grapheme cluster includes the entire
+    /// cluster.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("ae\u{301}bc");
+    /// let extracted = data.extract_graphemes(Index2::new(0, 0)..Index2::new(0, 2));
+    /// assert_eq!(extracted, Jagged::from("ae\u{301}"));
+    /// assert_eq!(data, Jagged::from("bc"));
+    /// ```
+    #[must_use]
+    pub fn extract_graphemes<R>(&mut self, range: R) -> Self
+    where
+        R: RangeBounds<Index2>,
+    {
+        let Some((start, end)) = self.range_bounds(range) else {
+            return Self::empty();
+        };
+        let start = self.grapheme_at(start).map_or(start, |(start, _)| start);
+        let end = self
+            .grapheme_at(end)
+            .map_or(end, |(_, end)| Index2::new(end.row, end.col - 1));
+        self.extract(start..=end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graphemes(text: &str) -> Vec<String> {
+        let data = Jagged::new(vec![text.chars().collect()]);
+        let graphemes = data.graphemes(0).unwrap();
+        graphemes
+            .map(|grapheme| grapheme.iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_graphemes() {
+        assert_eq!(graphemes("a\u{308}o"), vec!["a\u{308}", "o"]);
+        assert_eq!(graphemes("👨‍👩‍👧x"), vec!["👨‍👩‍👧", "x"]);
+        assert_eq!(graphemes("❤\u{FE0F}👋🏿"), vec!["❤\u{FE0F}", "👋🏿"]);
+        assert_eq!(graphemes("🇩🇪🇫🇷🇮"), vec!["🇩🇪", "🇫🇷", "🇮"]);
+        assert_eq!(graphemes("a\r\nb"), vec!["a", "\r\n", "b"]);
+        assert!(graphemes("").is_empty());
+    }
+
+    #[test]
+    fn test_graphemes_of_scripts() {
+        // Hebrew points, viramas and Thai vowels extend the preceding letter.
+        assert_eq!(
+            graphemes("\u{5E9}\u{5C1}\u{5D1}\u{5BF}"),
+            vec!["\u{5E9}\u{5C1}", "\u{5D1}\u{5BF}"]
+        );
+        assert_eq!(
+            graphemes("\u{995}\u{9CD}\u{9B7}"),
+            vec!["\u{995}\u{9CD}", "\u{9B7}"]
+        );
+        assert_eq!(graphemes("\u{B95}\u{BCD}"), vec!["\u{B95}\u{BCD}"]);
+        assert_eq!(
+            graphemes("\u{E01}\u{E31}\u{E17}\u{E35}\u{E48}"),
+            vec!["\u{E01}\u{E31}", "\u{E17}\u{E35}\u{E48}"]
+        );
+        // Spacing marks and prepended marks.
+        assert_eq!(
+            graphemes("\u{915}\u{93F}\u{E01}\u{E33}"),
+            vec!["\u{915}\u{93F}", "\u{E01}\u{E33}"]
+        );
+        assert_eq!(graphemes("\u{600}\u{661}"), vec!["\u{600}\u{661}"]);
+        // Hangul jamo sequences and syllables.
+        assert_eq!(
+            graphemes("\u{1100}\u{1161}\u{11A8}x"),
+            vec!["\u{1100}\u{1161}\u{11A8}", "x"]
+        );
+        assert_eq!(
+            graphemes("\u{AC00}\u{11A8}\u{AC01}\u{1161}"),
+            vec!["\u{AC00}\u{11A8}", "\u{AC01}", "\u{1161}"]
+        );
+        // Controls do not take extenders, joiners only join pictographs.
+        assert_eq!(graphemes("\u{0}\u{301}"), vec!["\u{0}", "\u{301}"]);
+        assert_eq!(graphemes("a\u{200D}👍"), vec!["a\u{200D}", "👍"]);
+        assert_eq!(graphemes("🚀\u{200D}🔥"), vec!["🚀\u{200D}🔥"]);
+    }
+
+    #[test]
+    fn test_step_through_graphemes() {
+        let data = Jagged::from("🇩🇪e\u{301}\n\nx");
+        let mut forward = vec![Index2::new(0, 0)];
+        while let Some(index) = data.next_grapheme(*forward.last().unwrap()) {
+            forward.push(index);
+        }
+        assert_eq!(
+            forward,
+            vec![
+                Index2::new(0, 0),
+                Index2::new(0, 2),
+                Index2::new(1, 0),
+                Index2::new(2, 0)
+            ]
+        );
+
+        let mut backward = vec![Index2::new(2, 0)];
+        while let Some(index) = data.prev_grapheme(*backward.last().unwrap()) {
+            backward.push(index);
+        }
+        forward.reverse();
+        assert_eq!(backward, forward);
+    }
+
+    #[test]
+    fn test_extract_graphemes_across_rows() {
+        let mut data = Jagged::from("ab\u{301}\nc\u{301}d");
+        let extracted = data.extract_graphemes(Index2::new(0, 2)..=Index2::new(1, 0));
+        assert_eq!(extracted, Jagged::from("b\u{301}\nc\u{301}"));
+        assert_eq!(data, Jagged::from("ad"));
+    }
+}
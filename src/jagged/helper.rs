@@ -1,6 +1,6 @@
-use crate::{index::RowIndex, Index2, Jagged};
+use crate::{index::RowIndex, Index2, Jagged, JaggedStorage};
 
-impl<T> Jagged<T> {
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
     /// Returns the first row's index.
     #[must_use]
     pub fn first_row_index(&self) -> usize {
@@ -79,7 +79,7 @@ impl<T> Jagged<T> {
         I: Into<Index2>,
     {
         let index = index.into();
-        match self.data.get(index.row) {
+        match self.data.row(index.row) {
             Some(_) => index.col == 0,
             None => false,
         }
@@ -92,7 +92,7 @@ impl<T> Jagged<T> {
         I: Into<Index2>,
     {
         let index = index.into();
-        match self.data.get(index.row) {
+        match self.data.row(index.row) {
             Some(row) => index.col >= row.len().saturating_sub(1),
             None => false,
         }
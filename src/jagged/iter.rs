@@ -3,21 +3,20 @@
     clippy::cast_sign_loss,
     clippy::into_iter_without_iter
 )]
-use crate::{Index2, Jagged};
-use core::slice::Iter;
+use crate::{Index2, Jagged, JaggedStorage};
 
-pub struct JaggedIterator<'a, T> {
-    pub(super) data: &'a Jagged<T>,
+pub struct JaggedIterator<'a, T, S = Vec<Vec<T>>> {
+    pub(super) data: &'a Jagged<T, S>,
     pub(super) row: isize,
     pub(super) col: usize,
     pub(super) end: Option<Index2>,
     pub(super) stop: bool,
 }
 
-impl<'a, T> JaggedIterator<'a, T> {
+impl<'a, T, S: JaggedStorage<T>> JaggedIterator<'a, T, S> {
     /// Instantiates a new [`LinesIterator`] that starts from a given position.
     #[must_use]
-    pub fn new(data: &'a Jagged<T>) -> Self {
+    pub fn new(data: &'a Jagged<T, S>) -> Self {
         Self {
             data,
             row: 0,
@@ -54,7 +53,7 @@ impl<'a, T> JaggedIterator<'a, T> {
     }
 }
 
-impl<'a, T> Iterator for JaggedIterator<'a, T> {
+impl<'a, T, S: JaggedStorage<T>> Iterator for JaggedIterator<'a, T, S> {
     type Item = (Option<&'a T>, Index2);
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -75,7 +74,7 @@ impl<'a, T> Iterator for JaggedIterator<'a, T> {
     }
 }
 
-impl<'a, T> DoubleEndedIterator for JaggedIterator<'a, T> {
+impl<'a, T, S: JaggedStorage<T>> DoubleEndedIterator for JaggedIterator<'a, T, S> {
     fn next_back(&mut self) -> Option<Self::Item> {
         if self.stop || self.row < 0 {
             return None;
@@ -94,7 +93,7 @@ impl<'a, T> DoubleEndedIterator for JaggedIterator<'a, T> {
     }
 }
 
-impl<T> Jagged<T> {
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
     /// Returns an iterator that yields the element of a jagged array along with its
     /// current index.
     ///
@@ -115,7 +114,7 @@ impl<T> Jagged<T> {
     /// }
     /// ```
     #[must_use]
-    pub fn iter(&self) -> JaggedIterator<'_, T> {
+    pub fn iter(&self) -> JaggedIterator<'_, T, S> {
         JaggedIterator::new(self)
     }
 
@@ -136,13 +135,13 @@ impl<T> Jagged<T> {
     ///     println!("Row: {:?}", row);
     /// }
     /// ```
-    pub fn iter_row(&self) -> Iter<'_, Vec<T>> {
-        self.data.iter()
+    pub fn iter_row(&self) -> S::RowIter<'_> {
+        self.data.iter_rows()
     }
 }
 
-impl<'a, T> IntoIterator for &'a Jagged<T> {
-    type IntoIter = JaggedIterator<'a, T>;
+impl<'a, T, S: JaggedStorage<T>> IntoIterator for &'a Jagged<T, S> {
+    type IntoIter = JaggedIterator<'a, T, S>;
 
     type Item = (Option<&'a T>, Index2);
 
@@ -151,15 +150,17 @@ impl<'a, T> IntoIterator for &'a Jagged<T> {
     }
 }
 
-impl<'a, T: Clone> FromIterator<<JaggedIterator<'a, T> as Iterator>::Item> for Jagged<T> {
+impl<'a, T: Clone + 'a, S: JaggedStorage<T>> FromIterator<(Option<&'a T>, Index2)>
+    for Jagged<T, S>
+{
     /// Collects the elements from the iterator into a new Jagged array.
     ///
     /// Returns a new Jagged array and collects the indices of each element.
     fn from_iter<I>(iter: I) -> Self
     where
-        I: IntoIterator<Item = <JaggedIterator<'a, T> as Iterator>::Item>,
+        I: IntoIterator<Item = (Option<&'a T>, Index2)>,
     {
-        let mut result = Jagged::default();
+        let mut result = Self::empty();
         let mut current_row = 0;
 
         for (value, index) in iter {
@@ -0,0 +1,311 @@
+//! # Line Ending Module
+//!
+//! The `line_ending` module detects the line terminators of a text, keeps track of
+//! them while the array is edited and writes them back when the array is converted
+//! into a string.
+use std::ops::Range;
+
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// A line terminator.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// A line feed, `\n`, used on Unix.
+    #[default]
+    Lf,
+    /// A carriage return followed by a line feed, `\r\n`, used on Windows.
+    CrLf,
+    /// A single carriage return, `\r`, used on classic Mac OS.
+    Cr,
+}
+
+impl LineEnding {
+    /// Returns the terminator as a string.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+            Self::Cr => "\r",
+        }
+    }
+}
+
+/// The line terminators of an array.
+///
+/// `rows` holds the terminator behind every row but the last. It is empty if
+/// all rows are terminated by `default`, which also terminates inserted rows.
+#[derive(Default, Debug, Clone)]
+pub(crate) struct LineEndings {
+    default: LineEnding,
+    rows: Vec<LineEnding>,
+}
+
+impl LineEndings {
+    /// Instantiates the line terminators of a text, given in the order of the rows.
+    pub(crate) fn new(rows: Vec<LineEnding>) -> Self {
+        let default = rows.first().copied().unwrap_or_default();
+        if rows.iter().all(|&ending| ending == default) {
+            return Self {
+                default,
+                rows: Vec::new(),
+            };
+        }
+        Self { default, rows }
+    }
+
+    /// Returns the terminator behind `row`.
+    pub(crate) fn get(&self, row: usize) -> LineEnding {
+        self.rows.get(row).copied().unwrap_or(self.default)
+    }
+
+    /// Drops the terminators that equal the default.
+    fn compact(mut self) -> Self {
+        if self.rows.iter().all(|&ending| ending == self.default) {
+            self.rows.clear();
+        }
+        self
+    }
+
+    /// Returns the terminators behind `rows`, for rows that are moved into
+    /// another array.
+    pub(crate) fn slice(&self, rows: Range<usize>) -> Self {
+        let end = rows.end.min(self.rows.len());
+        Self {
+            default: self.default,
+            rows: self.rows[rows.start.min(end)..end].to_vec(),
+        }
+        .compact()
+    }
+
+    /// Takes the terminators, leaving all rows terminated by the default.
+    pub(crate) fn take(&mut self) -> Self {
+        Self {
+            default: self.default,
+            rows: std::mem::take(&mut self.rows),
+        }
+    }
+
+    /// Terminates `count` rows starting at `row` by the terminators of the first
+    /// `count` rows of `other`.
+    pub(crate) fn replace(&mut self, row: usize, count: usize, other: &Self) {
+        if self.rows.is_empty() && other.rows.is_empty() && self.default == other.default {
+            return;
+        }
+        let mut rows = std::mem::take(&mut self.rows);
+        if rows.len() < row + count {
+            rows.resize(row + count, self.default);
+        }
+        for (i, ending) in rows[row..row + count].iter_mut().enumerate() {
+            *ending = other.get(i);
+        }
+        self.rows = rows;
+        *self = std::mem::take(self).compact();
+    }
+
+    /// Updates the terminators after the elements from `start` to `old_end` were
+    /// replaced by elements that end at `new_end`.
+    ///
+    /// The line breaks within the replaced range are removed, the inserted line
+    /// breaks are terminated by the default.
+    pub(crate) fn adjust(&mut self, start: Index2, old_end: Index2, new_end: Index2) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let end = old_end.row.min(self.rows.len());
+        let inserted = new_end.row.saturating_sub(start.row);
+        let default = self.default;
+        self.rows.splice(
+            start.row.min(end)..end,
+            std::iter::repeat(default).take(inserted),
+        );
+    }
+}
+
+impl<S: JaggedStorage<char>> Jagged<char, S> {
+    /// Returns the line ending of the array, or None if the rows are terminated by
+    /// different line endings.
+    ///
+    /// The line ending is detected when the array is created from a string and
+    /// defaults to [`LineEnding::Lf`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Jagged, LineEnding};
+    ///
+    /// assert_eq!(Jagged::from("a\r\nb").line_ending(), Some(LineEnding::CrLf));
+    /// assert_eq!(Jagged::from("a\r\nb\nc").line_ending(), None);
+    /// ```
+    #[must_use]
+    pub fn line_ending(&self) -> Option<LineEnding> {
+        let rows = self.len().saturating_sub(1);
+        let first = self.line_endings.get(0);
+        (1..rows)
+            .all(|row| self.line_endings.get(row) == first)
+            .then_some(first)
+    }
+
+    /// Returns the line ending that terminates `row`, or None for the last row and
+    /// rows that are out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Jagged, LineEnding};
+    ///
+    /// let data = Jagged::from("a\r\nb\rc");
+    /// assert_eq!(data.line_ending_at(1), Some(LineEnding::Cr));
+    /// assert_eq!(data.line_ending_at(2), None);
+    /// ```
+    #[must_use]
+    pub fn line_ending_at(&self, row: usize) -> Option<LineEnding> {
+        (row < self.len().saturating_sub(1)).then(|| self.line_endings.get(row))
+    }
+
+    /// Terminates all rows, including rows inserted later, by `line_ending`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Jagged, LineEnding};
+    ///
+    /// let mut data = Jagged::from("a\r\nb\nc");
+    /// data.set_line_ending(LineEnding::CrLf);
+    /// assert_eq!(data.to_string(), "a\r\nb\r\nc");
+    /// ```
+    pub fn set_line_ending(&mut self, line_ending: LineEnding) {
+        self.line_endings = LineEndings {
+            default: line_ending,
+            rows: Vec::new(),
+        };
+        self.update_line_ending_bytes(0..self.len());
+    }
+
+    /// Returns the data as a single String, with all rows terminated by `line_ending`.
+    ///
+    /// Use [`Jagged::to_string`] to keep the line ending of every row.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Jagged, LineEnding};
+    ///
+    /// let data = Jagged::from("a\r\nb\n");
+    /// assert_eq!(data.to_string_with(LineEnding::Lf), "a\nb\n");
+    /// ```
+    #[must_use]
+    pub fn to_string_with(&self, line_ending: LineEnding) -> String {
+        self.to_string_by(|_| line_ending)
+    }
+
+    /// Returns the data as a single String, with rows terminated by the line ending
+    /// returned by `line_ending` for the row.
+    pub(crate) fn to_string_by<F>(&self, line_ending: F) -> String
+    where
+        F: Fn(usize) -> LineEnding,
+    {
+        let mut text = String::new();
+        for (row, line) in self.data.iter_rows().enumerate() {
+            if row > 0 {
+                text.push_str(line_ending(row - 1).as_str());
+            }
+            text.extend(line.iter());
+        }
+        text
+    }
+}
+
+/// Splits `text` into rows and their terminators. A trailing line ending is
+/// followed by an empty row.
+///
+/// Every row is pushed into the storage as soon as it is complete.
+pub(crate) fn split_lines<S: JaggedStorage<char>>(text: &str) -> (S, Vec<LineEnding>) {
+    let mut rows = S::default();
+    let mut endings = Vec::new();
+    let mut row = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let ending = match ch {
+            '\n' => LineEnding::Lf,
+            '\r' if chars.next_if_eq(&'\n').is_some() => LineEnding::CrLf,
+            '\r' => LineEnding::Cr,
+            _ => {
+                row.push(ch);
+                continue;
+            }
+        };
+        rows.push_row(std::mem::take(&mut row));
+        endings.push(ending);
+    }
+    if !text.is_empty() {
+        rows.push_row(row);
+    }
+    (rows, endings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::RowIndex;
+
+    #[test]
+    fn test_roundtrip_mixed_line_endings() {
+        for text in ["", "a", "a\r\n", "\r\n\r\n", "a\r\nb\nc\rd", "\r\r\n\n"] {
+            assert_eq!(Jagged::from(text).to_string(), text);
+            assert_eq!(String::from(Jagged::from(text)), text);
+        }
+    }
+
+    #[test]
+    fn test_line_endings_follow_edits() {
+        let mut data = Jagged::from("a\r\nb\nc\r\nd");
+        assert_eq!(data.line_ending(), None);
+
+        // Joining the first rows removes the `\r\n` between them.
+        data.join_lines(0);
+        assert_eq!(data.to_string(), "ab\nc\r\nd");
+
+        // Inserted line breaks are terminated by the default, the first detected
+        // line ending.
+        let index = Index2::new(0, 1);
+        let _ = data.splice(index, index, Jagged::from("x\ny"));
+        assert_eq!(data.to_string(), "ax\r\nyb\nc\r\nd");
+
+        data.remove(RowIndex::new(1));
+        assert_eq!(data.to_string(), "ax\r\nc\r\nd");
+        assert_eq!(data.line_ending(), Some(LineEnding::CrLf));
+    }
+
+    #[test]
+    fn test_line_endings_of_moved_rows() {
+        let mut data = Jagged::from("a\r\nb\nc\rd");
+        let mut tail = data.split_off(Index2::new(1, 1));
+        assert_eq!(data.to_string(), "a\r\nb");
+        assert_eq!(tail.to_string(), "\nc\rd");
+
+        let mut drained = tail.extract(Index2::new(0, 0)..Index2::new(1, 1));
+        assert_eq!(drained.to_string(), "\nc");
+        data.merge(&mut drained);
+        assert_eq!(data.to_string(), "a\r\nb\nc");
+
+        // The line break between the arrays is terminated by the default.
+        data.append(&mut Jagged::from("x\ry"));
+        assert_eq!(data.to_string(), "a\r\nb\nc\r\nx\ry");
+        let drained = data.extract(Index2::new(0, 1)..Index2::new(2, 0));
+        assert_eq!(drained.to_string(), "\r\nb");
+        assert_eq!(data.to_string(), "ac\r\nx\ry");
+
+        let mut rows = data.extract_rows(1..3);
+        assert_eq!(rows.to_string(), "x\ry");
+        data.merge(&mut rows);
+        assert_eq!(data.to_string(), "acx\ry");
+    }
+
+    #[test]
+    fn test_line_ending_at_empty() {
+        let data = Jagged::from("");
+        assert_eq!(data.line_ending_at(0), None);
+        assert_eq!(data.line_ending_at(usize::MAX), None);
+    }
+}
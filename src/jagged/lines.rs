@@ -1,83 +1,65 @@
-use crate::{Index2, Jagged};
+use std::{convert::Infallible, str::FromStr};
 
-type Lines = Jagged<char>;
+use super::line_ending::{split_lines, LineEndings};
+use crate::{Index2, Jagged, JaggedStorage};
+
+type Lines<S> = Jagged<char, S>;
 
 impl<T: AsRef<str>> From<T> for Jagged<char> {
+    /// Splits the text at `\n`, `\r\n` and `\r` and keeps the line endings, see
+    /// [`Jagged::line_ending`].
     fn from(value: T) -> Self {
-        let mut data: Vec<Vec<char>> = Vec::new();
-        for line in value.as_ref().lines() {
-            data.push(line.chars().collect());
-        }
-
-        if let Some(last) = value.as_ref().chars().last() {
-            if last == '\n' {
-                data.push(Vec::new());
-            }
-        }
+        Self::from_text(value.as_ref())
+    }
+}
 
-        Self { data }
+impl<S: JaggedStorage<char>> FromStr for Lines<S> {
+    type Err = Infallible;
+
+    /// Splits the text like [`Jagged::from`] into any storage backend.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{storage::Rope, Jagged};
+    ///
+    /// let data: Jagged<char, Rope<char>> = "a\r\nb".parse().unwrap();
+    /// assert_eq!(data.to_string(), "a\r\nb");
+    /// ```
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from_text(text))
     }
 }
 
-impl From<Jagged<char>> for String {
-    fn from(value: Jagged<char>) -> String {
-        value.flatten(&Some('\n')).into_iter().collect()
+impl<S: JaggedStorage<char>> From<Jagged<char, S>> for String {
+    fn from(value: Jagged<char, S>) -> String {
+        value.to_string()
     }
 }
 
-impl Lines {
-    /// Returns the data as a single String, with lines joined by newlines.
+impl<S: JaggedStorage<char>> Lines<S> {
+    /// Splits the text into rows, pushing every row into the storage directly.
+    fn from_text(text: &str) -> Self {
+        let (rows, line_endings) = split_lines(text);
+        let mut data = Self::from_storage(rows);
+        data.line_endings = LineEndings::new(line_endings);
+        data.track_byte_offsets(true);
+        data
+    }
+
+    /// Returns the data as a single String, with lines joined by their line endings.
+    ///
+    /// See [`Jagged::to_string_with`] to join lines by a single line ending.
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
-        self.data
-            .iter()
-            .map(|line| line.iter().collect::<String>())
-            .collect::<Vec<String>>()
-            .join("\n")
+        self.to_string_by(|row| self.line_ending_at(row).unwrap_or_default())
     }
 
     /// Finds the index of the matching (closing or opening) bracket from a given starting point.
     #[must_use]
-    #[deprecated(
-        since = "0.1.9",
-        note = "Line specifics should not be part of this library"
-    )]
+    #[deprecated(since = "0.1.9", note = "use `find_matching_pair` instead")]
     pub fn find_matching_bracket(&self, index: Index2) -> Option<Index2> {
-        let &opening_bracket = self.get(index)?;
-
-        let (closing_bracket, reverse) = match opening_bracket {
-            '{' => ('}', false),
-            '}' => ('{', true),
-            '(' => (')', false),
-            ')' => ('(', true),
-            '[' => (']', false),
-            ']' => ('[', true),
-            _ => return None,
-        };
-
-        let mut counter = 0;
-
-        let iter: Box<dyn Iterator<Item = (Option<&char>, Index2)>> = if reverse {
-            Box::new(self.iter().from(index).rev().skip(1))
-        } else {
-            Box::new(self.iter().from(index).skip(1))
-        };
-
-        for (value, index) in iter {
-            let Some(&value) = value else { continue };
-
-            if value == opening_bracket {
-                counter += 1;
-            }
-
-            if value == closing_bracket {
-                if counter == 0 {
-                    return Some(index);
-                }
-                counter -= 1;
-            }
-        }
-
-        None
+        self.find_matching_pair(index, &[('{', '}'), ('(', ')'), ('[', ']')])
     }
 }
 
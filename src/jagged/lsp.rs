@@ -0,0 +1,290 @@
+//! # LSP Module
+//!
+//! The `lsp` module converts between [`Index2`] and the positions of the
+//! language server protocol, which count columns in code units of a negotiated
+//! encoding, and applies LSP text edits to a `Jagged<char>`.
+use crate::{Index2, Jagged, JaggedError, JaggedStorage};
+
+/// The encoding in which the columns of a [`Position`] are counted.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Columns count UTF-8 bytes.
+    Utf8,
+    /// Columns count UTF-16 code units, the default of the protocol.
+    #[default]
+    Utf16,
+    /// Columns count chars.
+    Utf32,
+}
+
+impl Encoding {
+    /// Returns the number of code units of `ch`.
+    fn len(self, ch: char) -> usize {
+        match self {
+            Self::Utf8 => ch.len_utf8(),
+            Self::Utf16 => ch.len_utf16(),
+            Self::Utf32 => 1,
+        }
+    }
+}
+
+/// A position in a text document, see the LSP specification.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    /// The zero based line.
+    pub line: u32,
+    /// The zero based column, counted in code units of the [`Encoding`].
+    pub character: u32,
+}
+
+impl Position {
+    /// Instantiates a new [`Position`].
+    #[must_use]
+    pub fn new(line: u32, character: u32) -> Self {
+        Self { line, character }
+    }
+}
+
+/// A range in a text document with an exclusive end, see the LSP specification.
+///
+/// # Example
+///
+/// ```
+/// use edtui_jagged::{Position, Range, TextEdit};
+///
+/// let range = Range { start: Position::new(0, 1), end: Position::new(1, 0) };
+/// let edit = TextEdit { range, new_text: String::new() };
+/// assert_eq!(edit, TextEdit::new(Position::new(0, 1), Position::new(1, 0), ""));
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    /// The start of the range.
+    pub start: Position,
+    /// The (exclusive) end of the range.
+    pub end: Position,
+}
+
+/// A textual edit of a document, see the LSP specification.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The range that is replaced.
+    pub range: Range,
+    /// The text that replaces the range, possibly empty.
+    pub new_text: String,
+}
+
+impl TextEdit {
+    /// Instantiates a new [`TextEdit`].
+    #[must_use]
+    pub fn new(start: Position, end: Position, new_text: impl Into<String>) -> Self {
+        Self {
+            range: Range { start, end },
+            new_text: new_text.into(),
+        }
+    }
+}
+
+impl<S: JaggedStorage<char>> Jagged<char, S> {
+    /// Converts an [`Index2`] into an LSP [`Position`].
+    ///
+    /// Returns None if the index is out of bounds. The end of a row is a valid index.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Encoding, Index2, Jagged, Position};
+    ///
+    /// let data = Jagged::from("a😀b");
+    /// let index = Index2::new(0, 2);
+    /// assert_eq!(data.to_lsp_position(index, Encoding::Utf16), Some(Position::new(0, 3)));
+    /// assert_eq!(data.to_lsp_position(index, Encoding::Utf8), Some(Position::new(0, 5)));
+    /// assert_eq!(data.to_lsp_position(index, Encoding::Utf32), Some(Position::new(0, 2)));
+    /// ```
+    #[must_use]
+    pub fn to_lsp_position(&self, index: Index2, encoding: Encoding) -> Option<Position> {
+        let row = self.data.row(index.row)?;
+        let character: usize = row
+            .get(..index.col)?
+            .iter()
+            .map(|&ch| encoding.len(ch))
+            .sum();
+        Some(Position {
+            line: u32::try_from(index.row).ok()?,
+            character: u32::try_from(character).ok()?,
+        })
+    }
+
+    /// Converts an LSP [`Position`] into an [`Index2`].
+    ///
+    /// As demanded by the protocol, a column behind the end of a line is clamped to
+    /// the end of the line. A column within a char resolves to that char.
+    /// Returns None if the line is out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Encoding, Index2, Jagged, Position};
+    ///
+    /// let data = Jagged::from("a😀b\nc");
+    /// let position = Position::new(0, 3);
+    /// assert_eq!(data.from_lsp_position(position, Encoding::Utf16), Some(Index2::new(0, 2)));
+    /// assert_eq!(data.from_lsp_position(Position::new(1, 9), Encoding::Utf16), Some(Index2::new(1, 1)));
+    /// ```
+    #[must_use]
+    pub fn from_lsp_position(&self, position: Position, encoding: Encoding) -> Option<Index2> {
+        let row = usize::try_from(position.line).ok()?;
+        let line = self.data.row(row)?;
+        let mut remaining = usize::try_from(position.character).ok()?;
+        for (col, &ch) in line.iter().enumerate() {
+            let len = encoding.len(ch);
+            if remaining < len {
+                return Some(Index2::new(row, col));
+            }
+            remaining -= len;
+        }
+        Some(Index2::new(row, line.len()))
+    }
+
+    /// Applies a batch of LSP text edits.
+    ///
+    /// All ranges refer to the document before the edits and must not overlap.
+    /// Edits are applied in the order of their start, insertions at the same position
+    /// in the order in which they are given. Either all edits are applied or, on
+    /// error, none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line is out of bounds, if a range is inverted or if two
+    /// ranges overlap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Encoding, Jagged, Position, TextEdit};
+    ///
+    /// let mut data = Jagged::from("let x = 1;");
+    /// let edits = vec![
+    ///     TextEdit::new(Position::new(0, 9), Position::new(0, 9), "\n"),
+    ///     TextEdit::new(Position::new(0, 4), Position::new(0, 5), "y"),
+    /// ];
+    /// data.apply_lsp_text_edits(&edits, Encoding::Utf16).unwrap();
+    /// assert_eq!(data, Jagged::from("let y = 1\n;"));
+    /// ```
+    pub fn apply_lsp_text_edits(
+        &mut self,
+        edits: &[TextEdit],
+        encoding: Encoding,
+    ) -> Result<(), JaggedError> {
+        let mut ranges = Vec::with_capacity(edits.len());
+        for (i, edit) in edits.iter().enumerate() {
+            let start = self.lsp_index(edit.range.start, encoding)?;
+            let end = self.lsp_index(edit.range.end, encoding)?;
+            if start > end {
+                return Err(JaggedError::InvertedRange { start, end });
+            }
+            ranges.push((start, end, i));
+        }
+        // The sort is stable, so insertions at the same position keep their order.
+        ranges.sort_by_key(|&(start, end, _)| (start, end));
+        for pair in ranges.windows(2) {
+            if pair[1].0 < pair[0].1 {
+                return Err(JaggedError::OverlappingRanges { start: pair[1].0 });
+            }
+        }
+        let order: Vec<usize> = ranges.iter().map(|&(_, _, i)| i).collect();
+        let ranges = ranges
+            .into_iter()
+            .map(|(start, end, _)| (start, end))
+            .collect();
+        self.replace_ranges(ranges, |i| {
+            let rows = edits[order[i]].new_text.split('\n');
+            let rows: Vec<Vec<char>> = rows
+                .map(|row| row.strip_suffix('\r').unwrap_or(row).chars().collect())
+                .collect();
+            Self::from_storage(S::from_rows(rows))
+        });
+        Ok(())
+    }
+
+    /// Converts an LSP [`Position`] into an [`Index2`], treating an empty array as
+    /// a single empty row.
+    fn lsp_index(&self, position: Position, encoding: Encoding) -> Result<Index2, JaggedError> {
+        if self.is_empty() && position.line == 0 {
+            return Ok(Index2::default());
+        }
+        self.from_lsp_position(position, encoding)
+            .ok_or(JaggedError::RowOutOfBounds {
+                row: position.line as usize,
+                len: self.len(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lsp_position_roundtrip() {
+        let data = Jagged::from("ä😀x\n\n€");
+        for encoding in [Encoding::Utf8, Encoding::Utf16, Encoding::Utf32] {
+            for (row, line) in data.iter_row().enumerate() {
+                for col in 0..=line.len() {
+                    let index = Index2::new(row, col);
+                    let position = data.to_lsp_position(index, encoding).unwrap();
+                    assert_eq!(data.from_lsp_position(position, encoding), Some(index));
+                }
+            }
+        }
+        assert_eq!(
+            data.to_lsp_position(Index2::new(1, 1), Encoding::Utf16),
+            None
+        );
+        assert_eq!(
+            data.from_lsp_position(Position::new(3, 0), Encoding::Utf16),
+            None
+        );
+    }
+
+    #[test]
+    fn test_apply_lsp_text_edits() {
+        let mut data = Jagged::from("fn a() {}\nfn b() {}");
+        let edits = vec![
+            TextEdit::new(Position::new(1, 3), Position::new(1, 4), "bar"),
+            TextEdit::new(Position::new(0, 8), Position::new(1, 0), "\r\n}\r\n"),
+            TextEdit::new(Position::new(0, 3), Position::new(0, 4), "foo"),
+            TextEdit::new(Position::new(0, 8), Position::new(0, 8), "x"),
+        ];
+        data.apply_lsp_text_edits(&edits, Encoding::Utf16).unwrap();
+        assert_eq!(data, Jagged::from("fn foo() {x\n}\nfn bar() {}"));
+    }
+
+    #[test]
+    fn test_apply_lsp_text_edits_errors() {
+        let mut data = Jagged::from("abc");
+        let overlapping = vec![
+            TextEdit::new(Position::new(0, 0), Position::new(0, 2), ""),
+            TextEdit::new(Position::new(0, 1), Position::new(0, 3), ""),
+        ];
+        assert_eq!(
+            data.apply_lsp_text_edits(&overlapping, Encoding::Utf16),
+            Err(JaggedError::OverlappingRanges {
+                start: Index2::new(0, 1)
+            })
+        );
+        let out_of_bounds = vec![TextEdit::new(Position::new(1, 0), Position::new(1, 0), "")];
+        assert!(data
+            .apply_lsp_text_edits(&out_of_bounds, Encoding::Utf16)
+            .is_err());
+        assert_eq!(data, Jagged::from("abc"));
+
+        let mut data = Jagged::default();
+        let insert = vec![TextEdit::new(
+            Position::new(0, 0),
+            Position::new(0, 0),
+            "a\n",
+        )];
+        data.apply_lsp_text_edits(&insert, Encoding::Utf16).unwrap();
+        assert_eq!(data, Jagged::from("a\n"));
+    }
+}
@@ -0,0 +1,263 @@
+//! # Marks Module
+//!
+//! The `marks` module contains anchored positions within a [`Jagged`] array.
+//! A mark is an [`Index2`] that is adjusted by every mutating method of the array,
+//! so it keeps pointing at the same element while the content around it changes.
+use std::{collections::BTreeMap, ops::RangeBounds};
+
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// The identifier of a mark, returned by [`Jagged::add_mark`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MarkId(usize);
+
+/// Decides where a mark goes when elements are inserted at its position, or when
+/// the elements around it are replaced.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gravity {
+    /// The mark stays in front of inserted elements.
+    Left,
+    /// The mark moves behind inserted elements.
+    #[default]
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Mark {
+    index: Index2,
+    gravity: Gravity,
+}
+
+/// The marks of an array.
+#[derive(Default, Debug, Clone)]
+pub(crate) struct Marks {
+    next_id: usize,
+    marks: BTreeMap<MarkId, Mark>,
+}
+
+impl Marks {
+    /// Adjusts all marks to an edit that replaced the elements from `start` to
+    /// `old_end` (exclusive) by elements that end at `new_end`.
+    pub(crate) fn adjust(&mut self, start: Index2, old_end: Index2, new_end: Index2) {
+        for mark in self.marks.values_mut() {
+            mark.index = adjust_index(mark.index, mark.gravity, start, old_end, new_end);
+        }
+    }
+}
+
+/// Returns the position of `index` after the elements from `start` to `old_end`
+/// (exclusive) were replaced by elements that end at `new_end`.
+///
+/// Positions within the replaced elements move to the start or the end of the
+/// inserted elements, depending on their gravity.
+pub(crate) fn adjust_index(
+    index: Index2,
+    gravity: Gravity,
+    start: Index2,
+    old_end: Index2,
+    new_end: Index2,
+) -> Index2 {
+    if index < start {
+        return index;
+    }
+    if index > old_end || (index == old_end && start < old_end) {
+        return if index.row == old_end.row {
+            Index2::new(new_end.row, new_end.col + index.col - old_end.col)
+        } else {
+            Index2::new(index.row - old_end.row + new_end.row, index.col)
+        };
+    }
+    match gravity {
+        Gravity::Left => start,
+        Gravity::Right => new_end,
+    }
+}
+
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Adds a mark at `index` and returns its identifier.
+    ///
+    /// The mark is adjusted by every edit of the array, see [`Gravity`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Gravity, Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("hello world");
+    /// let mark = data.add_mark(Index2::new(0, 6), Gravity::Right);
+    ///
+    /// data.insert(Index2::new(0, 0), '>');
+    /// assert_eq!(data.mark(mark), Some(Index2::new(0, 7)));
+    ///
+    /// data.split_off(Index2::new(0, 1));
+    /// assert_eq!(data.mark(mark), Some(Index2::new(0, 1)));
+    /// ```
+    pub fn add_mark(&mut self, index: Index2, gravity: Gravity) -> MarkId {
+        let id = MarkId(self.marks.next_id);
+        self.marks.next_id += 1;
+        self.marks.marks.insert(id, Mark { index, gravity });
+        id
+    }
+
+    /// Returns the position of a mark.
+    /// Returns None if the mark does not exist.
+    #[must_use]
+    pub fn mark(&self, id: MarkId) -> Option<Index2> {
+        self.marks.marks.get(&id).map(|mark| mark.index)
+    }
+
+    /// Moves a mark to `index`.
+    /// Returns false if the mark does not exist.
+    pub fn move_mark(&mut self, id: MarkId, index: Index2) -> bool {
+        self.marks
+            .marks
+            .get_mut(&id)
+            .map(|mark| mark.index = index)
+            .is_some()
+    }
+
+    /// Removes a mark and returns its last position.
+    pub fn remove_mark(&mut self, id: MarkId) -> Option<Index2> {
+        self.marks.marks.remove(&id).map(|mark| mark.index)
+    }
+
+    /// Removes all marks.
+    pub fn clear_marks(&mut self) {
+        self.marks.marks.clear();
+    }
+
+    /// Returns an iterator over all marks in the order they were added.
+    pub fn marks(&self) -> impl Iterator<Item = (MarkId, Index2)> + '_ {
+        self.marks.marks.iter().map(|(id, mark)| (*id, mark.index))
+    }
+
+    /// Returns the marks within `range`, ordered by their position.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Gravity, Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("ab\ncd");
+    /// let a = data.add_mark(Index2::new(1, 1), Gravity::Left);
+    /// let b = data.add_mark(Index2::new(0, 1), Gravity::Left);
+    /// data.add_mark(Index2::new(1, 2), Gravity::Left);
+    ///
+    /// let marks = data.marks_in(Index2::new(0, 1)..Index2::new(1, 2));
+    /// assert_eq!(marks, vec![(b, Index2::new(0, 1)), (a, Index2::new(1, 1))]);
+    /// ```
+    #[must_use]
+    pub fn marks_in<R>(&self, range: R) -> Vec<(MarkId, Index2)>
+    where
+        R: RangeBounds<Index2>,
+    {
+        let mut marks: Vec<_> = self
+            .marks()
+            .filter(|(_, index)| range.contains(index))
+            .collect();
+        marks.sort_by_key(|&(id, index)| (index, id));
+        marks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::RowIndex;
+
+    fn marks(data: &Jagged<char>) -> Vec<Index2> {
+        data.marks().map(|(_, index)| index).collect()
+    }
+
+    #[test]
+    fn test_gravity_on_insert() {
+        let mut data = Jagged::from("ab");
+        data.add_mark(Index2::new(0, 1), Gravity::Left);
+        data.add_mark(Index2::new(0, 1), Gravity::Right);
+
+        data.insert(Index2::new(0, 1), 'x');
+        assert_eq!(marks(&data), vec![Index2::new(0, 1), Index2::new(0, 2)]);
+    }
+
+    #[test]
+    fn test_marks_follow_rows() {
+        let mut data = Jagged::from("one\ntwo\nthree");
+        data.add_mark(Index2::new(1, 1), Gravity::Right);
+        data.add_mark(Index2::new(2, 3), Gravity::Right);
+
+        data.insert(RowIndex::new(0), vec!['z']);
+        assert_eq!(marks(&data), vec![Index2::new(2, 1), Index2::new(3, 3)]);
+
+        data.join_lines(1);
+        assert_eq!(marks(&data), vec![Index2::new(1, 4), Index2::new(2, 3)]);
+
+        data.remove(RowIndex::new(0));
+        assert_eq!(marks(&data), vec![Index2::new(0, 4), Index2::new(1, 3)]);
+
+        data.push(vec!['!']);
+        data.remove(Index2::new(1, 0));
+        assert_eq!(marks(&data), vec![Index2::new(0, 4), Index2::new(1, 2)]);
+        assert_eq!(data, Jagged::from("onetwo\nhree\n!"));
+    }
+
+    #[test]
+    fn test_marks_in_removed_range() {
+        let mut data = Jagged::from("hello\nworld");
+        data.add_mark(Index2::new(0, 4), Gravity::Left);
+        data.add_mark(Index2::new(1, 1), Gravity::Right);
+        data.add_mark(Index2::new(1, 3), Gravity::Right);
+
+        let _ = data.extract(Index2::new(0, 3)..Index2::new(1, 2));
+        assert_eq!(data, Jagged::from("helrld"));
+        assert_eq!(
+            marks(&data),
+            vec![Index2::new(0, 3), Index2::new(0, 3), Index2::new(0, 4)]
+        );
+
+        data.clear();
+        assert_eq!(marks(&data), vec![Index2::new(0, 0); 3]);
+    }
+
+    #[test]
+    fn test_marks_after_replace() {
+        let mut data = Jagged::from("a-b-c");
+        data.add_mark(Index2::new(0, 4), Gravity::Left);
+        data.add_mark(Index2::new(0, 3), Gravity::Left);
+        data.add_mark(Index2::new(0, 3), Gravity::Right);
+
+        let options = crate::SearchOptions::default();
+        let _ = data.replace_all(&['-'], &['\n', '\n'], .., options);
+        assert_eq!(
+            marks(&data),
+            vec![Index2::new(0, 6), Index2::new(0, 4), Index2::new(0, 6)]
+        );
+    }
+
+    #[test]
+    fn test_marks_after_extract() {
+        // Every element is unique, so a mark must point at its element after an edit.
+        let original = Jagged::new(vec![vec![0, 1], vec![], vec![2, 3, 4], vec![5]]);
+        let mut positions: Vec<Index2> = Vec::new();
+        for (row, line) in original.iter_row().enumerate() {
+            positions.extend((0..=line.len()).map(|col| Index2::new(row, col)));
+        }
+        for &start in &positions {
+            for &end in &positions {
+                let mut data = original.clone();
+                let elements: Vec<(usize, MarkId)> = positions
+                    .iter()
+                    .filter_map(|&index| Some((*original.get(index)?, index)))
+                    .map(|(element, index)| (element, data.add_mark(index, Gravity::Left)))
+                    .collect();
+                let _ = data.extract(start..end);
+
+                for (element, id) in elements {
+                    let index = data.mark(id).unwrap();
+                    if data.iter().any(|(value, _)| value == Some(&element)) {
+                        assert_eq!(data.get(index), Some(&element), "{start:?}..{end:?}");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,17 +1,90 @@
 use std::{char, collections::VecDeque, fmt::Debug};
 
-use crate::{Index2, Jagged};
+use super::search::{Case, Pattern, SearchOptions};
+use crate::{index::RowIndex, Index2, Jagged, JaggedStorage};
 
 /// An iterator over the disjoint matches of a pattern within this array.
-pub struct MatchIndices<'a, 'b, T> {
+pub struct MatchIndices<'a, 'b, T, S = Vec<Vec<T>>> {
     /// The array to be search through.
-    data: &'a Jagged<T>,
+    data: &'a Jagged<T, S>,
 
     /// The pattern that is to be seached for
-    pattern: &'b [T],
+    matcher: Matcher<'b, T>,
 
     /// The index of the start position.
     start_index: Option<Index2>,
+
+    /// The position from which the search started.
+    origin: Index2,
+
+    /// Whether the search continues at the start of the array.
+    wrap_around: bool,
+
+    /// Whether the search already continued at the start of the array.
+    wrapped: bool,
+}
+
+/// An iterator over the disjoint matches of a pattern within this array, which
+/// walks backwards from a given position.
+pub struct MatchIndicesRev<'a, 'b, T, S = Vec<Vec<T>>> {
+    /// The array to be search through.
+    data: &'a Jagged<T, S>,
+
+    /// The pattern that is to be seached for
+    matcher: Matcher<'b, T>,
+
+    /// The index from which the next iteration walks backwards.
+    start_index: Option<Index2>,
+
+    /// The position from which the search started.
+    origin: Index2,
+
+    /// Whether the search continues at the end of the array.
+    wrap_around: bool,
+
+    /// Whether the search already continued at the end of the array.
+    wrapped: bool,
+}
+
+/// An iterator over the disjoint matches of a pattern that may span multiple rows.
+///
+/// Yields the start and the (exclusive) end of every match. A match that ends with
+/// a [`Pattern::LineBreak`] ends at the start of the following row.
+///
+/// The array is scanned once as a stream of elements and line breaks, a failed
+/// partial match falls back to its longest prefix that is also a suffix, as in
+/// the Knuth-Morris-Pratt algorithm.
+pub struct MultiLineMatches<'a, 'b, T, S = Vec<Vec<T>>> {
+    /// The array to be search through.
+    data: &'a Jagged<T, S>,
+
+    /// The pattern that is to be seached for
+    pattern: &'b [Pattern<T>],
+
+    /// The length of the longest proper prefix of `pattern[..=i]` that is also
+    /// a suffix of it.
+    fallback: Vec<usize>,
+
+    /// Whether elements are compared case insensitive.
+    ignore_case: bool,
+
+    /// Whether only whole words are matched.
+    whole_word: bool,
+
+    /// The index of the next item of the stream.
+    position: Option<Index2>,
+
+    /// The positions of the items that match the start of the pattern.
+    matched: VecDeque<Index2>,
+
+    /// The position from which the search started.
+    origin: Index2,
+
+    /// Whether the search continues at the start of the array.
+    wrap_around: bool,
+
+    /// Whether the search already continued at the start of the array.
+    wrapped: bool,
 }
 
 pub trait MatchIndicesEq {
@@ -19,49 +92,134 @@ pub trait MatchIndicesEq {
     /// by `==`.
     #[must_use]
     fn eq(&self, other: &Self) -> bool;
+
+    /// Tests for `self` and `other` to be equal regardless of their case.
+    #[must_use]
+    fn eq_ignore_case(&self, other: &Self) -> bool {
+        self.eq(other)
+    }
+
+    /// Whether the element is uppercase. Used by [`Case::Smart`].
+    #[must_use]
+    fn is_uppercase(&self) -> bool {
+        false
+    }
+
+    /// Whether the element is part of a word. Used by whole word searches.
+    #[must_use]
+    fn is_word(&self) -> bool {
+        false
+    }
 }
+
 impl MatchIndicesEq for char {
     fn eq(&self, other: &Self) -> bool {
-        self.to_lowercase().collect::<String>() == other.to_lowercase().collect::<String>()
+        self == other
+    }
+
+    fn eq_ignore_case(&self, other: &Self) -> bool {
+        self.to_lowercase().eq(other.to_lowercase())
+    }
+
+    fn is_uppercase(&self) -> bool {
+        char::is_uppercase(*self)
+    }
+
+    fn is_word(&self) -> bool {
+        self.is_alphanumeric() || *self == '_'
     }
 }
 
-impl<'a, 'b, T: MatchIndicesEq> MatchIndices<'a, 'b, T> {
-    /// Instantiates a new [`MatchIndices`] that starts from a given position.
-    #[must_use]
-    pub(super) fn new(data: &'a Jagged<T>, pattern: &'b [T]) -> Self {
+/// Compares sequences of elements against a pattern.
+struct Matcher<'b, T> {
+    /// The pattern that is to be seached for
+    pattern: &'b [T],
+
+    /// Whether elements are compared case insensitive.
+    ignore_case: bool,
+
+    /// Whether only whole words are matched.
+    whole_word: bool,
+}
+
+/// Whether a pattern with the given elements is compared case insensitive.
+fn ignore_case<'t, T: MatchIndicesEq + 't>(
+    case: Case,
+    mut elements: impl Iterator<Item = &'t T>,
+) -> bool {
+    match case {
+        Case::Sensitive => false,
+        Case::Insensitive => true,
+        Case::Smart => !elements.any(MatchIndicesEq::is_uppercase),
+    }
+}
+
+fn elements_eq<T: MatchIndicesEq>(ignore_case: bool, a: &T, b: &T) -> bool {
+    if ignore_case {
+        a.eq_ignore_case(b)
+    } else {
+        a.eq(b)
+    }
+}
+
+impl<'b, T: MatchIndicesEq> Matcher<'b, T> {
+    fn new(pattern: &'b [T], options: SearchOptions) -> Self {
         Self {
-            data,
             pattern,
-            start_index: Some(Index2::default()),
+            ignore_case: ignore_case(options.case, pattern.iter()),
+            whole_word: options.whole_word,
         }
     }
 
     fn match_found(&self, other: &VecDeque<&T>) -> bool {
-        if self.pattern.len() != other.len() {
-            return false;
-        }
-        for (a, b) in self.pattern.iter().zip(other.iter()) {
-            if !a.eq(b) {
-                return false;
-            }
+        self.pattern.len() == other.len()
+            && self
+                .pattern
+                .iter()
+                .zip(other.iter())
+                .all(|(a, b)| elements_eq(self.ignore_case, a, b))
+    }
+
+    /// Whether the match that starts at `start` is accepted. With whole word search,
+    /// the match must not be surrounded by word elements.
+    fn accepts<S: JaggedStorage<T>>(&self, data: &Jagged<T, S>, start: Index2) -> bool {
+        if !self.whole_word {
+            return true;
         }
-        true
+        let is_word = |index: Index2| data.get(index).is_some_and(MatchIndicesEq::is_word);
+        let end = start.col + self.pattern.len();
+        let before = start.col > 0 && is_word(Index2::new(start.row, start.col - 1));
+        let after = is_word(Index2::new(start.row, end));
+        !before && !after
     }
 }
 
-impl<'a, 'b, T: MatchIndicesEq + Debug> Iterator for MatchIndices<'a, 'b, T> {
-    type Item = (&'b [T], Index2);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.data.is_empty() || self.pattern.is_empty() {
-            return None;
+impl<'a, 'b, T: MatchIndicesEq, S: JaggedStorage<T>> MatchIndices<'a, 'b, T, S> {
+    /// Instantiates a new [`MatchIndices`] that starts from a given position.
+    #[must_use]
+    pub(super) fn new(
+        data: &'a Jagged<T, S>,
+        pattern: &'b [T],
+        origin: Index2,
+        options: SearchOptions,
+    ) -> Self {
+        Self {
+            data,
+            matcher: Matcher::new(pattern, options),
+            start_index: Some(origin),
+            origin,
+            wrap_around: options.wrap_around,
+            wrapped: false,
         }
+    }
+
+    /// Finds the next match starting from `start_index`.
+    fn find_next(&mut self) -> Option<Index2> {
         // If the start index is None at this point, this means that the
         // previous iteration ended at the last element of the array and
         // we can stop here prematurely.
         let start_index = self.start_index?;
-        let pattern_len = self.pattern.len();
+        let pattern_len = self.matcher.pattern.len();
         let mut sequence_buffer = VecDeque::<&T>::new();
         for (value, index) in self.data.iter().from(start_index) {
             let Some(value) = value else { continue };
@@ -72,21 +230,280 @@ impl<'a, 'b, T: MatchIndicesEq + Debug> Iterator for MatchIndices<'a, 'b, T> {
                 sequence_buffer.pop_front();
             }
             sequence_buffer.push_back(value);
-            if self.match_found(&sequence_buffer) {
+            if self.matcher.match_found(&sequence_buffer) {
+                // The match was found n elements before where n is the
+                // length of the pattern.
+                let mut start = index;
+                start.col -= pattern_len.saturating_sub(1);
+                if !self.matcher.accepts(self.data, start) {
+                    continue;
+                }
                 // We set the start index for the next iteration.The index might
                 // be none in which case the next iteration will stop.
                 self.start_index = self.data.next(index).map(|(_, index)| index);
-                // The match was found n elements before where n is the
-                // length of the pattern.
-                let mut index = index;
-                index.col -= pattern_len.saturating_sub(1);
-                return Some((self.pattern, index));
+                return Some(start);
             }
         }
+        self.start_index = None;
         None
     }
 }
 
+impl<'a, 'b, T: MatchIndicesEq + Debug, S: JaggedStorage<T>> Iterator
+    for MatchIndices<'a, 'b, T, S>
+{
+    type Item = (&'b [T], Index2);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() || self.matcher.pattern.is_empty() {
+            return None;
+        }
+        loop {
+            if let Some(start) = self.find_next() {
+                // After wrapping around, the search ends at the origin.
+                if self.wrapped && start >= self.origin {
+                    self.start_index = None;
+                    return None;
+                }
+                return Some((self.matcher.pattern, start));
+            }
+            if self.wrapped || !self.wrap_around {
+                return None;
+            }
+            self.wrapped = true;
+            self.start_index = Some(Index2::default());
+        }
+    }
+}
+
+impl<'a, 'b, T: MatchIndicesEq, S: JaggedStorage<T>> MatchIndicesRev<'a, 'b, T, S> {
+    /// Instantiates a new [`MatchIndicesRev`] that walks backwards from a given position.
+    #[must_use]
+    pub(super) fn new(
+        data: &'a Jagged<T, S>,
+        pattern: &'b [T],
+        origin: Index2,
+        options: SearchOptions,
+    ) -> Self {
+        // Matches that start before the origin may end behind it.
+        let last_col = data.last_col_index(origin.row);
+        let start_index = Index2::new(origin.row, (origin.col + pattern.len()).min(last_col));
+        Self {
+            data,
+            matcher: Matcher::new(pattern, options),
+            start_index: (origin.row < data.len()).then_some(start_index),
+            origin,
+            wrap_around: options.wrap_around,
+            wrapped: false,
+        }
+    }
+
+    /// Finds the previous match walking backwards from `start_index`.
+    fn find_prev(&mut self) -> Option<Index2> {
+        let start_index = self.start_index?;
+        let pattern_len = self.matcher.pattern.len();
+        let mut sequence_buffer = VecDeque::<&T>::new();
+        let mut current_row = start_index.row;
+        for (value, index) in self.data.iter().from(start_index).rev() {
+            if index.row != current_row {
+                current_row = index.row;
+                sequence_buffer.clear();
+            }
+            let Some(value) = value else { continue };
+            if sequence_buffer.len() >= pattern_len {
+                sequence_buffer.pop_back();
+            }
+            sequence_buffer.push_front(value);
+            if !self.wrapped && index >= self.origin {
+                continue;
+            }
+            if self.matcher.match_found(&sequence_buffer) && self.matcher.accepts(self.data, index)
+            {
+                self.start_index = self.data.prev(index).map(|(_, index)| index);
+                return Some(index);
+            }
+        }
+        self.start_index = None;
+        None
+    }
+}
+
+impl<'a, 'b, T: MatchIndicesEq + Debug, S: JaggedStorage<T>> Iterator
+    for MatchIndicesRev<'a, 'b, T, S>
+{
+    type Item = (&'b [T], Index2);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() || self.matcher.pattern.is_empty() {
+            return None;
+        }
+        loop {
+            if let Some(start) = self.find_prev() {
+                // After wrapping around, the search ends at the origin.
+                if self.wrapped && start < self.origin {
+                    self.start_index = None;
+                    return None;
+                }
+                return Some((self.matcher.pattern, start));
+            }
+            if self.wrapped || !self.wrap_around {
+                return None;
+            }
+            self.wrapped = true;
+            let last_row = self.data.last_row_index();
+            self.start_index = Some(Index2::new(last_row, self.data.last_col_index(last_row)));
+        }
+    }
+}
+
+impl<'a, 'b, T: MatchIndicesEq, S: JaggedStorage<T>> MultiLineMatches<'a, 'b, T, S> {
+    /// Instantiates a new [`MultiLineMatches`] that starts from a given position.
+    #[must_use]
+    pub(super) fn new(
+        data: &'a Jagged<T, S>,
+        pattern: &'b [Pattern<T>],
+        origin: Index2,
+        options: SearchOptions,
+    ) -> Self {
+        let elements = pattern.iter().filter_map(|item| match item {
+            Pattern::Element(element) => Some(element),
+            Pattern::LineBreak => None,
+        });
+        let ignore_case = ignore_case(options.case, elements);
+        Self {
+            data,
+            pattern,
+            fallback: Self::fallback(pattern, ignore_case),
+            ignore_case,
+            whole_word: options.whole_word,
+            position: Some(origin),
+            matched: VecDeque::new(),
+            origin,
+            wrap_around: options.wrap_around,
+            wrapped: false,
+        }
+    }
+
+    /// Computes the fallback table of the pattern.
+    fn fallback(pattern: &[Pattern<T>], ignore_case: bool) -> Vec<usize> {
+        let items_eq = |a: &Pattern<T>, b: &Pattern<T>| match (a, b) {
+            (Pattern::Element(a), Pattern::Element(b)) => elements_eq(ignore_case, a, b),
+            (Pattern::LineBreak, Pattern::LineBreak) => true,
+            _ => false,
+        };
+        let mut fallback = vec![0; pattern.len()];
+        let mut len = 0;
+        for i in 1..pattern.len() {
+            while len > 0 && !items_eq(&pattern[i], &pattern[len]) {
+                len = fallback[len - 1];
+            }
+            if items_eq(&pattern[i], &pattern[len]) {
+                len += 1;
+            }
+            fallback[i] = len;
+        }
+        fallback
+    }
+
+    /// Returns the item of the stream at `index`, an element or a line break
+    /// (`None`), and the index of the following item.
+    fn item_at(&self, mut index: Index2) -> Option<(Index2, Option<&'a T>, Index2)> {
+        let data = self.data;
+        loop {
+            let row = data.get(RowIndex::new(index.row))?;
+            if let Some(value) = row.get(index.col) {
+                return Some((index, Some(value), Index2::new(index.row, index.col + 1)));
+            }
+            if index.row + 1 >= data.len() {
+                return None;
+            }
+            // A row can be matched up to its end, where a line break follows.
+            if index.col == row.len() {
+                return Some((index, None, Index2::new(index.row + 1, 0)));
+            }
+            index = Index2::new(index.row + 1, 0);
+        }
+    }
+
+    /// Whether the item of the stream matches the pattern at `i`.
+    fn item_matches(&self, i: usize, item: Option<&T>) -> bool {
+        match (&self.pattern[i], item) {
+            (Pattern::Element(element), Some(value)) => {
+                elements_eq(self.ignore_case, element, value)
+            }
+            (Pattern::LineBreak, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Falls back to the longest prefix of the partial match that can still be
+    /// extended to a match.
+    fn fall_back(&mut self) {
+        let len = self.matched.len();
+        let keep = self.fallback[len - 1];
+        self.matched.drain(..len - keep);
+    }
+
+    /// Finds the next match starting from `position`.
+    fn find_next(&mut self) -> Option<(Index2, Index2)> {
+        while let Some(position) = self.position {
+            // After wrapping around, the search ends at the origin.
+            let start = self.matched.front().copied().unwrap_or(position);
+            if self.wrapped && start >= self.origin {
+                break;
+            }
+            let Some((index, item, next)) = self.item_at(position) else {
+                break;
+            };
+            self.position = Some(next);
+            while !self.matched.is_empty() && !self.item_matches(self.matched.len(), item) {
+                self.fall_back();
+            }
+            if self.item_matches(self.matched.len(), item) {
+                self.matched.push_back(index);
+            }
+            if self.matched.len() == self.pattern.len() {
+                let start = self.matched[0];
+                if !self.whole_word || self.is_whole_word(start, next) {
+                    self.matched.clear();
+                    return Some((start, next));
+                }
+                self.fall_back();
+            }
+        }
+        self.position = None;
+        self.matched.clear();
+        None
+    }
+
+    /// Whether the match is not surrounded by word elements.
+    fn is_whole_word(&self, start: Index2, end: Index2) -> bool {
+        let is_word = |index: Index2| self.data.get(index).is_some_and(MatchIndicesEq::is_word);
+        let before = start.col > 0 && is_word(Index2::new(start.row, start.col - 1));
+        !before && !is_word(end)
+    }
+}
+
+impl<'a, 'b, T: MatchIndicesEq, S: JaggedStorage<T>> Iterator for MultiLineMatches<'a, 'b, T, S> {
+    type Item = (Index2, Index2);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pattern.is_empty() {
+            return None;
+        }
+        loop {
+            if let Some(found) = self.find_next() {
+                return Some(found);
+            }
+            if self.wrapped || !self.wrap_around {
+                return None;
+            }
+            self.wrapped = true;
+            self.position = Some(Index2::default());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,4 +560,166 @@ mod tests {
         let index = match_indices.next().map(|(_, index)| index);
         assert_eq!(index, None);
     }
+
+    #[test]
+    fn test_search_case() {
+        let jagged = Jagged::from("abc Abc ABC");
+        let find = |pattern: &str, case: Case| {
+            let pattern: Vec<char> = pattern.chars().collect();
+            let options = SearchOptions::default().case(case);
+            jagged
+                .search(&pattern, options)
+                .map(|(_, index)| index.col)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(find("abc", Case::Insensitive), vec![0, 4, 8]);
+        assert_eq!(find("Abc", Case::Sensitive), vec![4]);
+        assert_eq!(find("abc", Case::Smart), vec![0, 4, 8]);
+        assert_eq!(find("ABC", Case::Smart), vec![8]);
+    }
+
+    #[test]
+    fn test_search_whole_word() {
+        let jagged = Jagged::from("foo_bar foo\nfoo.");
+        let pattern: Vec<char> = vec!['f', 'o', 'o'];
+        let options = SearchOptions::default().whole_word(true);
+
+        let matches: Vec<_> = jagged.search(&pattern, options).map(|(_, i)| i).collect();
+        assert_eq!(matches, vec![Index2::new(0, 8), Index2::new(1, 0)]);
+    }
+
+    #[test]
+    fn test_search_from() {
+        let jagged = test_obj_long();
+        let pattern: Vec<char> = vec!['a', 'b', 'c'];
+        let find = |index: Index2, wrap_around: bool| {
+            let options = SearchOptions::default().wrap_around(wrap_around);
+            jagged
+                .search_from(&pattern, index, options)
+                .map(|(_, index)| index)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            find(Index2::new(0, 1), false),
+            vec![Index2::new(0, 1), Index2::new(0, 5), Index2::new(2, 1)]
+        );
+        assert_eq!(
+            find(Index2::new(0, 2), false),
+            vec![Index2::new(0, 5), Index2::new(2, 1)]
+        );
+        assert_eq!(
+            find(Index2::new(0, 2), true),
+            vec![Index2::new(0, 5), Index2::new(2, 1), Index2::new(0, 1)]
+        );
+    }
+
+    #[test]
+    fn test_search_backward() {
+        let jagged = test_obj_long();
+        let pattern: Vec<char> = vec!['a', 'b', 'c'];
+        let find = |index: Index2, wrap_around: bool| {
+            let options = SearchOptions::default().wrap_around(wrap_around);
+            jagged
+                .search_backward(&pattern, index, options)
+                .map(|(_, index)| index)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            find(Index2::new(2, 4), false),
+            vec![Index2::new(2, 1), Index2::new(0, 5), Index2::new(0, 1)]
+        );
+        assert_eq!(find(Index2::new(0, 5), false), vec![Index2::new(0, 1)]);
+        assert_eq!(find(Index2::new(0, 2), false), vec![Index2::new(0, 1)]);
+        assert_eq!(
+            find(Index2::new(0, 5), true),
+            vec![Index2::new(0, 1), Index2::new(2, 1), Index2::new(0, 5)]
+        );
+        assert_eq!(find(Index2::new(0, 1), false), vec![]);
+    }
+
+    #[test]
+    fn test_search_multiline() {
+        let jagged = Jagged::from("foo\nbar\nfoo\nBar\n");
+        let pattern = Pattern::from_text("foo\nbar");
+
+        let matches: Vec<_> = jagged
+            .search_multiline(&pattern, SearchOptions::default())
+            .collect();
+        assert_eq!(
+            matches,
+            vec![
+                (Index2::new(0, 0), Index2::new(1, 3)),
+                (Index2::new(2, 0), Index2::new(3, 3)),
+            ]
+        );
+
+        let options = SearchOptions::default().case(Case::Sensitive);
+        assert_eq!(jagged.search_multiline(&pattern, options).count(), 1);
+    }
+
+    #[test]
+    fn test_search_multiline_line_breaks() {
+        let jagged = Jagged::from("a\n\nab\n");
+        let pattern = Pattern::from_text("\n");
+
+        let matches: Vec<_> = jagged
+            .search_multiline(&pattern, SearchOptions::default())
+            .map(|(start, _)| start)
+            .collect();
+        assert_eq!(
+            matches,
+            vec![Index2::new(0, 1), Index2::new(1, 0), Index2::new(2, 2)]
+        );
+
+        let pattern = Pattern::from_text("b\n");
+        let options = SearchOptions::default().whole_word(true);
+        assert_eq!(jagged.search_multiline(&pattern, options).next(), None);
+        let pattern = Pattern::from_text("ab\n");
+        assert_eq!(
+            jagged.search_multiline(&pattern, options).next(),
+            Some((Index2::new(2, 0), Index2::new(3, 0)))
+        );
+    }
+
+    #[test]
+    fn test_search_multiline_overlapping_prefix() {
+        let jagged = Jagged::from("aab\naab\nb");
+        let pattern = Pattern::from_text("ab\naab\nb");
+
+        let matches: Vec<_> = jagged
+            .search_multiline(&pattern, SearchOptions::default())
+            .collect();
+        assert_eq!(matches, vec![(Index2::new(0, 1), Index2::new(2, 1))]);
+    }
+
+    #[test]
+    fn test_search_multiline_wrap_around() {
+        let jagged = Jagged::from("ab\ncd\nb\nc");
+        let pattern = Pattern::from_text("b\nc");
+        let find = |index: Index2, wrap_around: bool| {
+            let options = SearchOptions::default().wrap_around(wrap_around);
+            jagged
+                .search_multiline_from(&pattern, index, options)
+                .map(|(start, _)| start)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(find(Index2::new(1, 0), false), vec![Index2::new(2, 0)]);
+        // The match that crosses the origin is found after wrapping around.
+        assert_eq!(
+            find(Index2::new(1, 0), true),
+            vec![Index2::new(2, 0), Index2::new(0, 1)]
+        );
+        assert_eq!(
+            find(Index2::new(0, 1), true),
+            vec![Index2::new(0, 1), Index2::new(2, 0)]
+        );
+        assert_eq!(
+            find(Index2::new(0, 2), true),
+            vec![Index2::new(2, 0), Index2::new(0, 1)]
+        );
+    }
 }
@@ -0,0 +1,605 @@
+//! # Motions Module
+//!
+//! The `motions` module implements vim's word motions `w`, `b`, `e` and `ge`,
+//! paragraph motions `}` and `{` and sentence motions `)` and `(` on a
+//! `Jagged<char>`. What forms a word is decided by a [`CharClassifier`]: the
+//! [`DefaultClassifier`] gives the motions of `w`, `b`, `e` and `ge`, the
+//! [`BigWordClassifier`] those of `W`, `B`, `E` and `gE`.
+//!
+//! A line break separates words and counts as whitespace, an empty row is a word
+//! of its own.
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// The class of a char, consecutive chars of the same class form a word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    /// Whitespace, which separates words.
+    Whitespace,
+    /// Punctuation, such as brackets and operators.
+    Punctuation,
+    /// Keyword chars, such as letters and digits.
+    Word,
+}
+
+/// Decides the [`CharClass`] of a char.
+///
+/// Implemented for closures, so a custom classifier can be passed as
+/// `&|ch: char| ...`.
+pub trait CharClassifier {
+    /// Returns the class of `ch`.
+    fn classify(&self, ch: char) -> CharClass;
+}
+
+impl<F: Fn(char) -> CharClass> CharClassifier for F {
+    fn classify(&self, ch: char) -> CharClass {
+        self(ch)
+    }
+}
+
+/// Classifies alphanumeric chars and `_` as keyword chars, like vim's default
+/// `iskeyword`, and all other non-whitespace chars as punctuation.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct DefaultClassifier;
+
+impl CharClassifier for DefaultClassifier {
+    fn classify(&self, ch: char) -> CharClass {
+        if ch.is_whitespace() {
+            CharClass::Whitespace
+        } else if ch.is_alphanumeric() || ch == '_' {
+            CharClass::Word
+        } else {
+            CharClass::Punctuation
+        }
+    }
+}
+
+/// Classifies all non-whitespace chars as keyword chars, like vim's `WORD`.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct BigWordClassifier;
+
+impl CharClassifier for BigWordClassifier {
+    fn classify(&self, ch: char) -> CharClass {
+        if ch.is_whitespace() {
+            CharClass::Whitespace
+        } else {
+            CharClass::Word
+        }
+    }
+}
+
+impl<S: JaggedStorage<char>> Jagged<char, S> {
+    /// Returns the start of the next word, like vim's `w`.
+    ///
+    /// Stops at empty rows. Returns the last position of the array if there is no
+    /// next word.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{BigWordClassifier, DefaultClassifier, Index2, Jagged};
+    ///
+    /// let data = Jagged::from("foo.bar baz\n\nqux");
+    /// let start = Index2::new(0, 0);
+    /// assert_eq!(data.next_word_start(start, &DefaultClassifier), Index2::new(0, 3));
+    /// assert_eq!(data.next_word_start(start, &BigWordClassifier), Index2::new(0, 8));
+    /// assert_eq!(data.next_word_start(Index2::new(0, 8), &DefaultClassifier), Index2::new(1, 0));
+    /// ```
+    #[must_use]
+    pub fn next_word_start<C: CharClassifier>(&self, index: Index2, classifier: &C) -> Index2 {
+        let Some(mut pos) = self.motion_start(index) else {
+            return Index2::default();
+        };
+        if let Some(class) = self.word_class(pos, classifier) {
+            pos = self.word_bound_forward(pos, class, classifier);
+        }
+        while let Some(next) = self.step_forward_char(pos) {
+            pos = next;
+            if self.class_at(pos, classifier) != Some(CharClass::Whitespace) {
+                break;
+            }
+        }
+        pos
+    }
+
+    /// Returns the start of the current or previous word, like vim's `b`.
+    ///
+    /// Stops at empty rows. Returns the first position of the array if there is no
+    /// previous word.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{DefaultClassifier, Index2, Jagged};
+    ///
+    /// let data = Jagged::from("foo(bar)\n  baz");
+    /// let start = Index2::new(1, 2);
+    /// assert_eq!(data.prev_word_start(start, &DefaultClassifier), Index2::new(0, 7));
+    /// assert_eq!(data.prev_word_start(Index2::new(0, 6), &DefaultClassifier), Index2::new(0, 4));
+    /// ```
+    #[must_use]
+    pub fn prev_word_start<C: CharClassifier>(&self, index: Index2, classifier: &C) -> Index2 {
+        let Some(mut pos) = self.motion_start(index) else {
+            return Index2::default();
+        };
+        while let Some(prev) = self.step_back_char(pos) {
+            pos = prev;
+            if self.class_at(pos, classifier) != Some(CharClass::Whitespace) {
+                break;
+            }
+        }
+        match self.word_class(pos, classifier) {
+            Some(class) => self.word_bound_back(pos, class, classifier),
+            None => pos,
+        }
+    }
+
+    /// Returns the end of the current or next word, like vim's `e`.
+    ///
+    /// Skips empty rows. Returns the last position of the array if there is no
+    /// next word.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{DefaultClassifier, Index2, Jagged};
+    ///
+    /// let data = Jagged::from("ab cd\n\nef");
+    /// assert_eq!(data.next_word_end(Index2::new(0, 0), &DefaultClassifier), Index2::new(0, 1));
+    /// assert_eq!(data.next_word_end(Index2::new(0, 4), &DefaultClassifier), Index2::new(2, 1));
+    /// ```
+    #[must_use]
+    pub fn next_word_end<C: CharClassifier>(&self, index: Index2, classifier: &C) -> Index2 {
+        let Some(mut pos) = self.motion_start(index) else {
+            return Index2::default();
+        };
+        while let Some(next) = self.step_forward_char(pos) {
+            pos = next;
+            if let Some(class) = self.word_class(pos, classifier) {
+                return self.word_bound_forward(pos, class, classifier);
+            }
+        }
+        pos
+    }
+
+    /// Returns the end of the previous word, like vim's `ge`.
+    ///
+    /// Stops at empty rows. Returns the first position of the array if there is no
+    /// previous word.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{DefaultClassifier, Index2, Jagged};
+    ///
+    /// let data = Jagged::from("ab\n\ncd ef");
+    /// assert_eq!(data.prev_word_end(Index2::new(2, 4), &DefaultClassifier), Index2::new(2, 1));
+    /// assert_eq!(data.prev_word_end(Index2::new(2, 1), &DefaultClassifier), Index2::new(1, 0));
+    /// ```
+    #[must_use]
+    pub fn prev_word_end<C: CharClassifier>(&self, index: Index2, classifier: &C) -> Index2 {
+        let Some(mut pos) = self.motion_start(index) else {
+            return Index2::default();
+        };
+        if let Some(class) = self.word_class(pos, classifier) {
+            pos = self.word_bound_back(pos, class, classifier);
+        }
+        while let Some(prev) = self.step_back_char(pos) {
+            pos = prev;
+            if self.class_at(pos, classifier) != Some(CharClass::Whitespace) {
+                break;
+            }
+        }
+        pos
+    }
+
+    /// Clamps `index` to a position of a motion, which is either an element or
+    /// the start of an empty row. Returns None if the array is empty.
+    pub(super) fn motion_start(&self, index: Index2) -> Option<Index2> {
+        if self.is_empty() {
+            return None;
+        }
+        let row = index.row.min(self.last_row_index());
+        Some(Index2::new(row, index.col.min(self.last_col_index(row))))
+    }
+
+    /// Returns the class of the char at `pos`, or None for an empty row.
+    fn class_at<C: CharClassifier>(&self, pos: Index2, classifier: &C) -> Option<CharClass> {
+        self.get(pos).map(|&ch| classifier.classify(ch))
+    }
+
+    /// Returns the class of the char at `pos` if it is part of a word.
+    fn word_class<C: CharClassifier>(&self, pos: Index2, classifier: &C) -> Option<CharClass> {
+        self.class_at(pos, classifier)
+            .filter(|&class| class != CharClass::Whitespace)
+    }
+
+    /// Returns the last position of the word of class `class` at `pos`.
+    fn word_bound_forward<C>(&self, mut pos: Index2, class: CharClass, classifier: &C) -> Index2
+    where
+        C: CharClassifier,
+    {
+        while let Some(next) = self.step_forward_char(pos) {
+            if next.row != pos.row || self.class_at(next, classifier) != Some(class) {
+                break;
+            }
+            pos = next;
+        }
+        pos
+    }
+
+    /// Returns the first position of the word of class `class` at `pos`.
+    fn word_bound_back<C>(&self, mut pos: Index2, class: CharClass, classifier: &C) -> Index2
+    where
+        C: CharClassifier,
+    {
+        while let Some(prev) = self.step_back_char(pos) {
+            if prev.row != pos.row || self.class_at(prev, classifier) != Some(class) {
+                break;
+            }
+            pos = prev;
+        }
+        pos
+    }
+
+    /// Returns the next position of a motion, which is either an element or the
+    /// start of an empty row.
+    pub(super) fn step_forward_char(&self, pos: Index2) -> Option<Index2> {
+        if pos.col + 1 < self.len_col_unchecked(pos.row) {
+            Some(Index2::new(pos.row, pos.col + 1))
+        } else if pos.row + 1 < self.len() {
+            Some(Index2::new(pos.row + 1, 0))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the previous position of a motion, which is either an element or
+    /// the start of an empty row.
+    pub(super) fn step_back_char(&self, pos: Index2) -> Option<Index2> {
+        if pos.col > 0 {
+            Some(Index2::new(pos.row, pos.col - 1))
+        } else if pos.row > 0 {
+            let row = pos.row - 1;
+            Some(Index2::new(row, self.last_col_index(row)))
+        } else {
+            None
+        }
+    }
+}
+
+impl<S: JaggedStorage<char>> Jagged<char, S> {
+    /// Returns the empty row behind the `count`-th next paragraph, like vim's `}`.
+    ///
+    /// Paragraphs are separated by empty rows. Returns the last position of the
+    /// array if there are fewer paragraphs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("a\nb\n\n\nc\n\nd");
+    /// assert_eq!(data.next_paragraph(Index2::new(0, 0), 1), Index2::new(2, 0));
+    /// assert_eq!(data.next_paragraph(Index2::new(0, 0), 2), Index2::new(5, 0));
+    /// assert_eq!(data.next_paragraph(Index2::new(0, 0), 3), Index2::new(6, 0));
+    /// ```
+    #[must_use]
+    pub fn next_paragraph(&self, index: Index2, count: usize) -> Index2 {
+        let Some(mut pos) = self.motion_start(index) else {
+            return Index2::default();
+        };
+        let is_empty_row = |row: usize| self.len_col_unchecked(row) == 0;
+        for _ in 0..count {
+            let mut row = pos.row;
+            while row < self.len() && is_empty_row(row) {
+                row += 1;
+            }
+            while row < self.len() && !is_empty_row(row) {
+                row += 1;
+            }
+            if row >= self.len() {
+                let last_row = self.last_row_index();
+                return Index2::new(last_row, self.last_col_index(last_row));
+            }
+            pos = Index2::new(row, 0);
+        }
+        pos
+    }
+
+    /// Returns the empty row before the `count`-th previous paragraph, like vim's `{`.
+    ///
+    /// Paragraphs are separated by empty rows. Returns the first position of the
+    /// array if there are fewer paragraphs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("a\n\nb\nc\n\nd");
+    /// assert_eq!(data.prev_paragraph(Index2::new(5, 0), 1), Index2::new(4, 0));
+    /// assert_eq!(data.prev_paragraph(Index2::new(5, 0), 2), Index2::new(1, 0));
+    /// assert_eq!(data.prev_paragraph(Index2::new(5, 0), 3), Index2::new(0, 0));
+    /// ```
+    #[must_use]
+    pub fn prev_paragraph(&self, index: Index2, count: usize) -> Index2 {
+        let Some(mut pos) = self.motion_start(index) else {
+            return Index2::default();
+        };
+        let is_empty_row = |row: usize| self.len_col_unchecked(row) == 0;
+        for _ in 0..count {
+            let mut row = pos.row;
+            while row > 0 && is_empty_row(row) {
+                row -= 1;
+            }
+            while row > 0 && !is_empty_row(row) {
+                row -= 1;
+            }
+            if !is_empty_row(row) {
+                return Index2::default();
+            }
+            pos = Index2::new(row, 0);
+        }
+        pos
+    }
+
+    /// Returns the start of the `count`-th next sentence, like vim's `)`.
+    ///
+    /// A sentence ends at a `.`, `!` or `?`, optionally followed by closing
+    /// brackets or quotes, that is followed by whitespace or a line break. An
+    /// empty row also ends a sentence and starts a new one. Returns the last
+    /// position of the array if there are fewer sentences.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("Hi there. (Whats up?) Ok\nfine.\n\nBye");
+    /// assert_eq!(data.next_sentence(Index2::new(0, 0), 1), Index2::new(0, 10));
+    /// assert_eq!(data.next_sentence(Index2::new(0, 0), 2), Index2::new(0, 22));
+    /// assert_eq!(data.next_sentence(Index2::new(0, 22), 1), Index2::new(2, 0));
+    /// assert_eq!(data.next_sentence(Index2::new(0, 22), 2), Index2::new(3, 0));
+    /// ```
+    #[must_use]
+    pub fn next_sentence(&self, index: Index2, count: usize) -> Index2 {
+        let Some(mut pos) = self.motion_start(index) else {
+            return Index2::default();
+        };
+        for _ in 0..count {
+            loop {
+                let Some(next) = self.step_forward_char(pos) else {
+                    return pos;
+                };
+                pos = next;
+                if self.is_sentence_start(pos) {
+                    break;
+                }
+            }
+        }
+        pos
+    }
+
+    /// Returns the start of the `count`-th previous sentence, like vim's `(`.
+    ///
+    /// Moves to the start of the current sentence first if `index` lies within a
+    /// sentence. Returns the first position of the array if there are fewer
+    /// sentences. See [`Jagged::next_sentence`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("One. Two! Three");
+    /// assert_eq!(data.prev_sentence(Index2::new(0, 12), 1), Index2::new(0, 10));
+    /// assert_eq!(data.prev_sentence(Index2::new(0, 12), 2), Index2::new(0, 5));
+    /// ```
+    #[must_use]
+    pub fn prev_sentence(&self, index: Index2, count: usize) -> Index2 {
+        let Some(mut pos) = self.motion_start(index) else {
+            return Index2::default();
+        };
+        for _ in 0..count {
+            loop {
+                let Some(prev) = self.step_back_char(pos) else {
+                    return pos;
+                };
+                pos = prev;
+                if self.is_sentence_start(pos) {
+                    break;
+                }
+            }
+        }
+        pos
+    }
+
+    /// Returns true if a sentence starts at `pos`.
+    fn is_sentence_start(&self, pos: Index2) -> bool {
+        let Some(&ch) = self.get(pos) else {
+            // An empty row starts a sentence, unless it follows another empty row.
+            return pos.row == 0 || self.len_col_unchecked(pos.row - 1) > 0;
+        };
+        if ch.is_whitespace() {
+            return false;
+        }
+        // Skip the whitespace before `pos`, line breaks count as whitespace.
+        let mut prev = pos;
+        let mut separated = false;
+        loop {
+            let Some(index) = self.step_back_char(prev) else {
+                return true;
+            };
+            separated |= index.row != prev.row;
+            prev = index;
+            match self.get(prev) {
+                None => return true,
+                Some(ch) if ch.is_whitespace() => separated = true,
+                Some(_) => break,
+            }
+        }
+        if !separated {
+            return false;
+        }
+        let row = self.data.row(prev.row).expect("row index out of bounds");
+        row[..=prev.col]
+            .iter()
+            .rev()
+            .find(|ch| !matches!(ch, ')' | ']' | '"' | '\''))
+            .is_some_and(|ch| matches!(ch, '.' | '!' | '?'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Repeats `motion` from `start` until it stops moving.
+    fn collect<F>(start: Index2, motion: F) -> Vec<Index2>
+    where
+        F: Fn(Index2) -> Index2,
+    {
+        let mut positions = vec![start];
+        loop {
+            let next = motion(*positions.last().unwrap());
+            if positions.contains(&next) {
+                return positions;
+            }
+            positions.push(next);
+        }
+    }
+
+    #[test]
+    fn test_word_motions_across_rows() {
+        let data = Jagged::from("fn a(b)  \n\n  c.d\ne");
+        let words: Vec<Index2> = [
+            (0, 0),
+            (0, 3),
+            (0, 4),
+            (0, 5),
+            (0, 6),
+            (1, 0),
+            (2, 2),
+            (2, 3),
+            (2, 4),
+            (3, 0),
+        ]
+        .into_iter()
+        .map(|(row, col)| Index2::new(row, col))
+        .collect();
+
+        let w = collect(Index2::new(0, 0), |i| {
+            data.next_word_start(i, &DefaultClassifier)
+        });
+        assert_eq!(w, words);
+
+        let b = collect(Index2::new(3, 0), |i| {
+            data.prev_word_start(i, &DefaultClassifier)
+        });
+        assert_eq!(b, words.iter().rev().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_word_end_motions() {
+        let data = Jagged::from("ab.cd\n\n x");
+        let e = collect(Index2::new(0, 0), |i| {
+            data.next_word_end(i, &DefaultClassifier)
+        });
+        assert_eq!(
+            e,
+            vec![
+                Index2::new(0, 0),
+                Index2::new(0, 1),
+                Index2::new(0, 2),
+                Index2::new(0, 4),
+                Index2::new(2, 1)
+            ]
+        );
+
+        let ge = collect(Index2::new(2, 1), |i| {
+            data.prev_word_end(i, &DefaultClassifier)
+        });
+        assert_eq!(
+            ge,
+            vec![
+                Index2::new(2, 1),
+                Index2::new(1, 0),
+                Index2::new(0, 4),
+                Index2::new(0, 2),
+                Index2::new(0, 1),
+                Index2::new(0, 0)
+            ]
+        );
+
+        let big_e = data.next_word_end(Index2::new(0, 0), &BigWordClassifier);
+        assert_eq!(big_e, Index2::new(0, 4));
+    }
+
+    #[test]
+    fn test_paragraph_motions() {
+        let data = Jagged::from("\na\nb\n\n\nc\n\nd");
+        let next = collect(Index2::new(0, 0), |i| data.next_paragraph(i, 1));
+        assert_eq!(
+            next,
+            vec![
+                Index2::new(0, 0),
+                Index2::new(3, 0),
+                Index2::new(6, 0),
+                Index2::new(7, 0)
+            ]
+        );
+        let prev = collect(Index2::new(7, 0), |i| data.prev_paragraph(i, 1));
+        assert_eq!(
+            prev,
+            vec![
+                Index2::new(7, 0),
+                Index2::new(6, 0),
+                Index2::new(4, 0),
+                Index2::new(0, 0)
+            ]
+        );
+        assert_eq!(data.next_paragraph(Index2::new(1, 0), 0), Index2::new(1, 0));
+    }
+
+    #[test]
+    fn test_sentence_motions() {
+        let data = Jagged::from("A b.  \"C.\"\n  d!e. F\n\n\nG");
+        let next = collect(Index2::new(0, 0), |i| data.next_sentence(i, 1));
+        assert_eq!(
+            next,
+            vec![
+                Index2::new(0, 0),
+                Index2::new(0, 6),
+                Index2::new(1, 2),
+                Index2::new(1, 7),
+                Index2::new(2, 0),
+                Index2::new(4, 0)
+            ]
+        );
+        let prev = collect(Index2::new(4, 0), |i| data.prev_sentence(i, 1));
+        assert_eq!(prev, next.iter().rev().copied().collect::<Vec<_>>());
+        assert_eq!(data.next_sentence(Index2::new(0, 1), 3), Index2::new(1, 7));
+        assert_eq!(data.prev_sentence(Index2::new(1, 4), 1), Index2::new(1, 2));
+    }
+
+    #[test]
+    fn test_custom_classifier() {
+        let data = Jagged::from("foo-bar baz");
+        let classifier = |ch: char| match ch {
+            ' ' => CharClass::Whitespace,
+            _ => CharClass::Word,
+        };
+        assert_eq!(
+            data.next_word_start(Index2::new(0, 0), &classifier),
+            Index2::new(0, 8)
+        );
+        assert_eq!(
+            data.next_word_start(Index2::default(), &DefaultClassifier),
+            Index2::new(0, 3)
+        );
+        assert_eq!(
+            Jagged::default().next_word_start(Index2::new(1, 1), &DefaultClassifier),
+            Index2::default()
+        );
+    }
+}
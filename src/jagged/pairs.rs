@@ -0,0 +1,249 @@
+use crate::{Index2, Jagged, JaggedStorage};
+
+impl<T: PartialEq, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Finds the delimiter that matches the one at `index`.
+    ///
+    /// `pairs` lists the opening and closing delimiters. If the element at `index`
+    /// opens a pair, the matching closing delimiter is searched forward, if it
+    /// closes a pair, the opening delimiter is searched backward. Nested pairs of
+    /// the same kind are skipped.
+    ///
+    /// Returns None if the element at `index` is no delimiter or has no match.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::new(vec![vec![1, 0, 1], vec![2, 2]]);
+    /// let pairs = [(0, 2), (1, 3)];
+    /// assert_eq!(data.find_matching_pair(Index2::new(0, 1), &pairs), Some(Index2::new(1, 0)));
+    /// assert_eq!(data.find_matching_pair(Index2::new(0, 0), &pairs), None);
+    /// ```
+    #[must_use]
+    pub fn find_matching_pair(&self, index: Index2, pairs: &[(T, T)]) -> Option<Index2> {
+        self.find_matching_pair_with(index, pairs, |_| false)
+    }
+
+    /// Finds the delimiter that matches the one at `index`, ignoring all delimiters
+    /// for which `skip` returns true.
+    ///
+    /// `skip` excludes delimiters within strings or comments. See
+    /// [`Jagged::find_matching_pair`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from(r#"f(")", x)"#);
+    /// let in_string = |index: Index2| {
+    ///     data.inner_quote(index, '"')
+    ///         .is_some_and(|(start, end)| start <= index && index <= end)
+    /// };
+    /// let matching = data.find_matching_pair_with(Index2::new(0, 1), &[('(', ')')], in_string);
+    /// assert_eq!(matching, Some(Index2::new(0, 8)));
+    /// ```
+    #[must_use]
+    pub fn find_matching_pair_with<F>(
+        &self,
+        index: Index2,
+        pairs: &[(T, T)],
+        skip: F,
+    ) -> Option<Index2>
+    where
+        F: Fn(Index2) -> bool,
+    {
+        let element = self.get(index)?;
+        if skip(index) {
+            return None;
+        }
+        for (open, close) in pairs {
+            if element == open {
+                return Self::find_pair_end(self.elements_after(index), open, close, &skip);
+            }
+            if element == close {
+                return Self::find_pair_end(self.elements_before(index), close, open, &skip);
+            }
+        }
+        None
+    }
+
+    /// Finds the innermost pair of delimiters that encloses `index` and returns
+    /// the positions of its opening and closing delimiter.
+    ///
+    /// A delimiter at `index` encloses itself. Pairs of different kinds must be
+    /// properly nested.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("[a, (b,\nc), d]");
+    /// let pairs = [('(', ')'), ('[', ']')];
+    /// assert_eq!(
+    ///     data.find_enclosing_pair(Index2::new(1, 0), &pairs),
+    ///     Some((Index2::new(0, 4), Index2::new(1, 1)))
+    /// );
+    /// assert_eq!(
+    ///     data.find_enclosing_pair(Index2::new(1, 3), &pairs),
+    ///     Some((Index2::new(0, 0), Index2::new(1, 5)))
+    /// );
+    /// ```
+    #[must_use]
+    pub fn find_enclosing_pair(&self, index: Index2, pairs: &[(T, T)]) -> Option<(Index2, Index2)> {
+        self.find_enclosing_pair_with(index, pairs, |_| false)
+    }
+
+    /// Finds the innermost pair of delimiters that encloses `index`, ignoring all
+    /// delimiters for which `skip` returns true.
+    ///
+    /// See [`Jagged::find_enclosing_pair`] and [`Jagged::find_matching_pair_with`].
+    #[must_use]
+    pub fn find_enclosing_pair_with<F>(
+        &self,
+        index: Index2,
+        pairs: &[(T, T)],
+        skip: F,
+    ) -> Option<(Index2, Index2)>
+    where
+        F: Fn(Index2) -> bool,
+    {
+        if let Some(element) = self.get(index).filter(|_| !skip(index)) {
+            if let Some(matching) = self.find_matching_pair_with(index, pairs, &skip) {
+                let is_open = pairs.iter().any(|(open, _)| open == element);
+                return Some(if is_open {
+                    (index, matching)
+                } else {
+                    (matching, index)
+                });
+            }
+        }
+        // The kinds of the pairs that are closed between the candidate and `index`.
+        let mut closed: Vec<usize> = Vec::new();
+        for (pos, element) in self.elements_before(index) {
+            if skip(pos) {
+                continue;
+            }
+            if let Some(kind) = pairs.iter().position(|(_, close)| close == element) {
+                closed.push(kind);
+                continue;
+            }
+            let Some(kind) = pairs.iter().position(|(open, _)| open == element) else {
+                continue;
+            };
+            if closed.is_empty() {
+                let matching = self.find_matching_pair_with(pos, pairs, &skip);
+                if let Some(end) = matching.filter(|&end| end >= index) {
+                    return Some((pos, end));
+                }
+            } else if closed.last() == Some(&kind) {
+                closed.pop();
+            }
+        }
+        None
+    }
+
+    /// Returns the position of the first `target` in `elements` that is not
+    /// balanced by a preceding `nested`.
+    fn find_pair_end<'a, I, F>(elements: I, nested: &T, target: &T, skip: &F) -> Option<Index2>
+    where
+        T: 'a,
+        I: Iterator<Item = (Index2, &'a T)>,
+        F: Fn(Index2) -> bool,
+    {
+        let mut depth = 0usize;
+        for (pos, element) in elements {
+            if skip(pos) {
+                continue;
+            }
+            if element == target {
+                if depth == 0 {
+                    return Some(pos);
+                }
+                depth -= 1;
+            } else if element == nested {
+                depth += 1;
+            }
+        }
+        None
+    }
+
+    /// Returns the elements behind `index` and their positions.
+    pub(super) fn elements_after(&self, index: Index2) -> impl Iterator<Item = (Index2, &T)> {
+        (index.row..self.len()).flat_map(move |row| {
+            let first = if row == index.row { index.col + 1 } else { 0 };
+            let elements = self.data.row(row).map_or(&[][..], Vec::as_slice);
+            let elements = elements.iter().enumerate().skip(first);
+            elements.map(move |(col, element)| (Index2::new(row, col), element))
+        })
+    }
+
+    /// Returns the elements before `index` and their positions, in reverse order.
+    pub(super) fn elements_before(&self, index: Index2) -> impl Iterator<Item = (Index2, &T)> {
+        let rows = if self.is_empty() {
+            0
+        } else {
+            index.row.min(self.last_row_index()) + 1
+        };
+        (0..rows).rev().flat_map(move |row| {
+            let elements = self.data.row(row).map_or(&[][..], Vec::as_slice);
+            let end = if row == index.row {
+                index.col.min(elements.len())
+            } else {
+                elements.len()
+            };
+            let elements = elements[..end].iter().enumerate().rev();
+            elements.map(move |(col, element)| (Index2::new(row, col), element))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::RowIndex;
+
+    #[test]
+    fn test_find_matching_pair_nested() {
+        let data = Jagged::from("{a(b)\n{c}}");
+        let pairs = [('{', '}'), ('(', ')')];
+        assert_eq!(
+            data.find_matching_pair(Index2::new(0, 0), &pairs),
+            Some(Index2::new(1, 3))
+        );
+        assert_eq!(
+            data.find_matching_pair(Index2::new(1, 3), &pairs),
+            Some(Index2::new(0, 0))
+        );
+        assert_eq!(
+            data.find_matching_pair(Index2::new(0, 4), &pairs),
+            Some(Index2::new(0, 2))
+        );
+        assert_eq!(data.find_matching_pair(Index2::new(0, 1), &pairs), None);
+    }
+
+    #[test]
+    fn test_find_enclosing_pair_skips() {
+        let data = Jagged::from("(a // )\n\n b)");
+        let in_comment = |index: Index2| {
+            let row = data.get(RowIndex::new(index.row)).unwrap();
+            row[..index.col].windows(2).any(|w| w == ['/', '/'])
+        };
+        let pairs = [('(', ')')];
+        assert_eq!(
+            data.find_enclosing_pair(Index2::new(0, 3), &pairs),
+            Some((Index2::new(0, 0), Index2::new(0, 6)))
+        );
+        assert_eq!(
+            data.find_enclosing_pair_with(Index2::new(0, 3), &pairs, in_comment),
+            Some((Index2::new(0, 0), Index2::new(2, 2)))
+        );
+        assert_eq!(data.find_enclosing_pair(Index2::new(2, 3), &pairs), None);
+        assert_eq!(
+            Jagged::<char>::default().find_enclosing_pair(Index2::new(0, 0), &pairs),
+            None
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use std::ops::RangeBounds;
+
+use regex::Regex;
+
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// The text of a `Jagged<char>` with rows joined by `\n` and the byte offset
+/// at which each row starts.
+pub(crate) struct FlatText {
+    pub(crate) text: String,
+    row_offsets: Vec<usize>,
+}
+
+impl FlatText {
+    pub(crate) fn new<S: JaggedStorage<char>>(data: &Jagged<char, S>) -> Self {
+        let mut text = String::new();
+        let mut row_offsets = Vec::with_capacity(data.len());
+        for (i, row) in data.iter_row().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            row_offsets.push(text.len());
+            text.extend(row.iter());
+        }
+        Self { text, row_offsets }
+    }
+
+    /// Converts a byte offset on a char boundary into an [`Index2`].
+    pub(crate) fn index(&self, byte: usize) -> Index2 {
+        let row = self
+            .row_offsets
+            .partition_point(|&offset| offset <= byte)
+            .saturating_sub(1);
+        let row_start = self.row_offsets.get(row).copied().unwrap_or(0);
+        let col = self.text[row_start..byte].chars().count();
+        Index2::new(row, col)
+    }
+}
+
+/// An iterator over the non-overlapping matches of a regex within a `Jagged<char>`.
+///
+/// Yields the start and the (exclusive) end of every match.
+///
+/// Created by [`Jagged::find_regex_iter`].
+pub struct RegexMatches<'r> {
+    flat: FlatText,
+    regex: &'r Regex,
+    position: usize,
+    last_end: Option<usize>,
+}
+
+impl Iterator for RegexMatches<'_> {
+    type Item = (Index2, Index2);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.position > self.flat.text.len() {
+                return None;
+            }
+            let found = self.regex.find_at(&self.flat.text, self.position)?;
+            if found.is_empty() && Some(found.end()) == self.last_end {
+                // Skip an empty match directly behind the previous match.
+                self.position = match self.flat.text[found.end()..].chars().next() {
+                    Some(ch) => found.end() + ch.len_utf8(),
+                    None => self.flat.text.len() + 1,
+                };
+                continue;
+            }
+            self.position = found.end();
+            self.last_end = Some(found.end());
+            return Some((self.flat.index(found.start()), self.flat.index(found.end())));
+        }
+    }
+}
+
+impl<S: JaggedStorage<char>> Jagged<char, S> {
+    /// Returns the start and the (exclusive) end of the first match of `regex`.
+    ///
+    /// Rows are joined by `\n`, so a regex can match across rows.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    /// use regex::Regex;
+    ///
+    /// let data = Jagged::from("let foo\n= bar;");
+    /// let regex = Regex::new(r"foo\n.*bar").unwrap();
+    /// assert_eq!(
+    ///     data.find_regex(&regex),
+    ///     Some((Index2::new(0, 4), Index2::new(1, 5)))
+    /// );
+    /// ```
+    #[must_use]
+    pub fn find_regex(&self, regex: &Regex) -> Option<(Index2, Index2)> {
+        self.find_regex_iter(regex).next()
+    }
+
+    /// Returns an iterator over the non-overlapping matches of `regex`.
+    ///
+    /// Rows are joined by `\n`, so a regex can match across rows. Each item
+    /// is the start and the (exclusive) end of a match.
+    #[must_use]
+    pub fn find_regex_iter<'r>(&self, regex: &'r Regex) -> RegexMatches<'r> {
+        RegexMatches {
+            flat: FlatText::new(self),
+            regex,
+            position: 0,
+            last_end: None,
+        }
+    }
+
+    /// Replaces all matches of `regex` within `range` by `replacement`.
+    ///
+    /// `replacement` may refer to capture groups with `$1` or `$name`, see
+    /// [`regex::Captures::expand`], and may contain `\n` to insert line breaks.
+    /// Returns the start and the (exclusive) end of every replacement in the
+    /// modified array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    /// use regex::Regex;
+    ///
+    /// let mut data = Jagged::from("key=value\nfoo=bar");
+    /// let regex = Regex::new(r"(\w+)=(\w+)").unwrap();
+    /// let changed = data.replace_regex_all(&regex, "$2: $1", ..);
+    /// assert_eq!(data, Jagged::from("value: key\nbar: foo"));
+    /// assert_eq!(changed.len(), 2);
+    /// ```
+    pub fn replace_regex_all<R>(
+        &mut self,
+        regex: &Regex,
+        replacement: &str,
+        range: R,
+    ) -> Vec<(Index2, Index2)>
+    where
+        R: RangeBounds<Index2>,
+    {
+        self.replace_regex_n(regex, replacement, range, usize::MAX)
+    }
+
+    /// Replaces the first `n` matches of `regex` within `range` by `replacement`.
+    ///
+    /// See [`Jagged::replace_regex_all`].
+    pub fn replace_regex_n<R>(
+        &mut self,
+        regex: &Regex,
+        replacement: &str,
+        range: R,
+        n: usize,
+    ) -> Vec<(Index2, Index2)>
+    where
+        R: RangeBounds<Index2>,
+    {
+        let Some(bounds) = self.range_bounds(range) else {
+            return Vec::new();
+        };
+        let flat = FlatText::new(self);
+        let mut ranges = Vec::new();
+        let mut replacements = Vec::new();
+        for captures in regex.captures_iter(&flat.text) {
+            let found = captures.get(0).expect("group 0 is always present");
+            let (start, end) = (flat.index(found.start()), flat.index(found.end()));
+            if start > bounds.1 || ranges.len() == n {
+                break;
+            }
+            if !Self::match_within(bounds, start, end) {
+                continue;
+            }
+            let mut expanded = String::new();
+            captures.expand(replacement, &mut expanded);
+            ranges.push((start, end));
+            replacements.push(expanded);
+        }
+        self.replace_ranges(ranges, |i| {
+            let rows = replacements[i]
+                .split('\n')
+                .map(|line| line.chars().collect());
+            Self::from_storage(S::from_rows(rows.collect()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_regex_iter() {
+        let data = Jagged::from("fooäbar\n\nfoo bar");
+        let regex = Regex::new("foo.bar").unwrap();
+
+        let matches: Vec<_> = data.find_regex_iter(&regex).collect();
+        assert_eq!(
+            matches,
+            vec![
+                (Index2::new(0, 0), Index2::new(0, 7)),
+                (Index2::new(2, 0), Index2::new(2, 7)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_regex_multiline() {
+        let data = Jagged::from("ab\n\ncd");
+        let regex = Regex::new(r"b\n\nc").unwrap();
+        assert_eq!(
+            data.find_regex(&regex),
+            Some((Index2::new(0, 1), Index2::new(2, 1)))
+        );
+
+        let regex = Regex::new(r"(?m)^$").unwrap();
+        assert_eq!(
+            data.find_regex(&regex),
+            Some((Index2::new(1, 0), Index2::new(1, 0)))
+        );
+    }
+
+    #[test]
+    fn test_find_regex_empty_matches() {
+        let data = Jagged::from("aa");
+        let regex = Regex::new("a*").unwrap();
+        let matches: Vec<_> = data.find_regex_iter(&regex).collect();
+        assert_eq!(matches, vec![(Index2::new(0, 0), Index2::new(0, 2))]);
+    }
+
+    #[test]
+    fn test_replace_regex_with_captures() {
+        let mut data = Jagged::from("a1 b2\nc3");
+        let regex = Regex::new(r"([a-z])(\d)").unwrap();
+        let changed = data.replace_regex_all(&regex, "${2}\n$1", Index2::new(0, 3)..);
+
+        assert_eq!(data, Jagged::from("a1 2\nb\n3\nc"));
+        assert_eq!(
+            changed,
+            vec![
+                (Index2::new(0, 3), Index2::new(1, 1)),
+                (Index2::new(2, 0), Index2::new(3, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replace_regex_n_across_rows() {
+        let mut data = Jagged::from("a\nb\na\nb");
+        let regex = Regex::new(r"a\n").unwrap();
+        let changed = data.replace_regex_n(&regex, "", .., 1);
+
+        assert_eq!(data, Jagged::from("b\na\nb"));
+        assert_eq!(changed, vec![(Index2::new(0, 0), Index2::new(0, 0))]);
+    }
+}
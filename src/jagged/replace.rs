@@ -0,0 +1,190 @@
+use std::{fmt::Debug, ops::RangeBounds};
+
+use super::{match_indices::MatchIndicesEq, search::SearchOptions};
+use crate::{Index2, Jagged, JaggedStorage};
+
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Replaces disjoint ranges, given in ascending order of their start, in one pass.
+    ///
+    /// `replacement` is called with the position of each range in `ranges`. Returns
+    /// the start and the (exclusive) end of every inserted replacement, given in
+    /// coordinates of the modified array.
+    pub(crate) fn replace_ranges<F>(
+        &mut self,
+        ranges: Vec<(Index2, Index2)>,
+        mut replacement: F,
+    ) -> Vec<(Index2, Index2)>
+    where
+        F: FnMut(usize) -> Self,
+    {
+        let mut changed = Vec::with_capacity(ranges.len());
+        // The (exclusive) end of the previous range before and after its replacement.
+        let mut shift: Option<(Index2, Index2)> = None;
+        for (i, (start, end)) in ranges.into_iter().enumerate() {
+            let map = |index: Index2| match shift {
+                Some((old_end, new_end)) if index.row == old_end.row => {
+                    Index2::new(new_end.row, new_end.col + index.col - old_end.col)
+                }
+                Some((old_end, new_end)) => {
+                    Index2::new(index.row - old_end.row + new_end.row, index.col)
+                }
+                None => index,
+            };
+            let (start_mapped, end_mapped) = (map(start), map(end));
+            let (_, new_end) = self.splice(start_mapped, end_mapped, replacement(i));
+            changed.push((start_mapped, new_end));
+            shift = Some((end, new_end));
+        }
+        changed
+    }
+
+    /// Returns true if a match from `start` to `end` (exclusive) lies in the
+    /// inclusive bounds returned by [`Jagged::range_bounds`].
+    pub(crate) fn match_within(bounds: (Index2, Index2), start: Index2, end: Index2) -> bool {
+        let (lower, upper) = bounds;
+        start >= lower && end <= Index2::new(upper.row, upper.col + 1)
+    }
+}
+
+impl<T: MatchIndicesEq + Debug + Clone, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Replaces all matches of `pattern` within `range` by `replacement`.
+    ///
+    /// Matches are searched first and substituted afterwards, so a replacement is never
+    /// matched again. Returns the start and the (exclusive) end of every replacement
+    /// in the modified array. The number of replacements is the length of the
+    /// returned vector.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged, SearchOptions};
+    ///
+    /// let mut data = Jagged::from("foo foo\nfoo");
+    /// let pattern: Vec<char> = "foo".chars().collect();
+    /// let replacement: Vec<char> = "ba".chars().collect();
+    ///
+    /// let changed = data.replace_all(&pattern, &replacement, .., SearchOptions::default());
+    /// assert_eq!(data, Jagged::from("ba ba\nba"));
+    /// assert_eq!(changed.len(), 3);
+    /// assert_eq!(changed[1], (Index2::new(0, 3), Index2::new(0, 5)));
+    /// ```
+    pub fn replace_all<R>(
+        &mut self,
+        pattern: &[T],
+        replacement: &[T],
+        range: R,
+        options: SearchOptions,
+    ) -> Vec<(Index2, Index2)>
+    where
+        R: RangeBounds<Index2>,
+    {
+        self.replace_n(pattern, replacement, range, options, usize::MAX)
+    }
+
+    /// Replaces the first `n` matches of `pattern` within `range` by `replacement`.
+    ///
+    /// See [`Jagged::replace_all`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged, SearchOptions};
+    ///
+    /// let mut data = Jagged::from("ab\nab\nab");
+    /// let pattern: Vec<char> = vec!['a'];
+    /// let replacement: Vec<char> = vec!['x', 'y'];
+    ///
+    /// let range = Index2::new(1, 0)..;
+    /// let changed = data.replace_n(&pattern, &replacement, range, SearchOptions::default(), 1);
+    /// assert_eq!(data, Jagged::from("ab\nxyb\nab"));
+    /// assert_eq!(changed, vec![(Index2::new(1, 0), Index2::new(1, 2))]);
+    /// ```
+    pub fn replace_n<R>(
+        &mut self,
+        pattern: &[T],
+        replacement: &[T],
+        range: R,
+        options: SearchOptions,
+        n: usize,
+    ) -> Vec<(Index2, Index2)>
+    where
+        R: RangeBounds<Index2>,
+    {
+        let Some(bounds) = self.range_bounds(range) else {
+            return Vec::new();
+        };
+        let ranges: Vec<(Index2, Index2)> = self
+            .search_from(pattern, bounds.0, options.wrap_around(false))
+            .map(|(_, start)| (start, Index2::new(start.row, start.col + pattern.len())))
+            .take_while(|&(start, _)| start <= bounds.1)
+            .filter(|&(start, end)| Self::match_within(bounds, start, end))
+            .take(n)
+            .collect();
+        self.replace_ranges(ranges, |_| {
+            Self::from_storage(S::from_rows(vec![replacement.to_vec()]))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_all_shifts_following_matches() {
+        let mut data = Jagged::from("aXaXa\nXa");
+        let changed = data.replace_all(&['a'], &['b', 'b'], .., SearchOptions::default());
+
+        assert_eq!(data, Jagged::from("bbXbbXbb\nXbb"));
+        assert_eq!(
+            changed,
+            vec![
+                (Index2::new(0, 0), Index2::new(0, 2)),
+                (Index2::new(0, 3), Index2::new(0, 5)),
+                (Index2::new(0, 6), Index2::new(0, 8)),
+                (Index2::new(1, 1), Index2::new(1, 3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replace_all_within_range() {
+        let mut data = Jagged::from("foo\nfoo foo\nfoo");
+        let range = Index2::new(1, 0)..Index2::new(1, 6);
+        let changed = data.replace_all(&['f', 'o', 'o'], &[], range, SearchOptions::default());
+
+        assert_eq!(data, Jagged::from("foo\n foo\nfoo"));
+        assert_eq!(changed, vec![(Index2::new(1, 0), Index2::new(1, 0))]);
+    }
+
+    #[test]
+    fn test_replace_n() {
+        let mut data = Jagged::from("aaaa");
+        let changed = data.replace_n(&['a'], &['b'], .., SearchOptions::default(), 2);
+
+        assert_eq!(data, Jagged::from("bbaa"));
+        assert_eq!(changed.len(), 2);
+    }
+
+    #[test]
+    fn test_replace_ranges_with_line_breaks() {
+        let mut data = Jagged::from("ab\ncd");
+        let ranges = vec![
+            (Index2::new(0, 1), Index2::new(1, 0)),
+            (Index2::new(1, 1), Index2::new(1, 2)),
+        ];
+        let changed = data.replace_ranges(ranges, |i| match i {
+            0 => Jagged::from("1\n2\n"),
+            _ => Jagged::from("3"),
+        });
+
+        assert_eq!(data, Jagged::from("a1\n2\nc3"));
+        assert_eq!(
+            changed,
+            vec![
+                (Index2::new(0, 1), Index2::new(2, 0)),
+                (Index2::new(2, 1), Index2::new(2, 2)),
+            ]
+        );
+    }
+}
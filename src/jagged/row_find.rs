@@ -0,0 +1,222 @@
+//! # Row Find Module
+//!
+//! The `row_find` module implements vim's find motions `f`, `t`, `F` and `T`,
+//! which move to an element within the current row, and their repetition with
+//! `;` and `,`.
+//!
+//! Keep the [`RowFind`] of the last find motion to repeat it:
+//!
+//! ```
+//! use edtui_jagged::{Direction, Index2, Jagged, RowFind};
+//!
+//! let data = Jagged::from("a,b,c,d");
+//! let last_find = RowFind::new(',', Direction::Forward, false);
+//!
+//! let index = data.repeat_find_in_row(Index2::new(0, 0), &last_find, 1).unwrap();
+//! assert_eq!(index, Index2::new(0, 1));
+//!
+//! // `;` repeats the find, `,` repeats it in the opposite direction.
+//! let index = data.repeat_find_in_row(index, &last_find, 2).unwrap();
+//! assert_eq!(index, Index2::new(0, 5));
+//! let index = data.repeat_find_in_row(index, &last_find.reversed(), 1).unwrap();
+//! assert_eq!(index, Index2::new(0, 3));
+//! ```
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// The direction of a find motion.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Searches towards the end of the row, like vim's `f` and `t`.
+    #[default]
+    Forward,
+    /// Searches towards the start of the row, like vim's `F` and `T`.
+    Backward,
+}
+
+impl Direction {
+    /// Returns the opposite direction.
+    #[must_use]
+    pub fn reversed(self) -> Self {
+        match self {
+            Self::Forward => Self::Backward,
+            Self::Backward => Self::Forward,
+        }
+    }
+}
+
+/// A find motion within a row, stored to be repeated with `;` and `,`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowFind<T> {
+    /// The element that is searched for.
+    pub target: T,
+    /// The direction of the search.
+    pub direction: Direction,
+    /// Whether the motion stops before the target, like vim's `t` and `T`.
+    pub till: bool,
+}
+
+impl<T> RowFind<T> {
+    /// Instantiates a new [`RowFind`].
+    #[must_use]
+    pub fn new(target: T, direction: Direction, till: bool) -> Self {
+        Self {
+            target,
+            direction,
+            till,
+        }
+    }
+}
+
+impl<T: Clone> RowFind<T> {
+    /// Returns the motion in the opposite direction, as repeated by vim's `,`.
+    #[must_use]
+    pub fn reversed(&self) -> Self {
+        Self::new(self.target.clone(), self.direction.reversed(), self.till)
+    }
+}
+
+impl<T: PartialEq, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Finds the `count`-th occurrence of `target` in the row of `index`, like
+    /// vim's `f`, `t`, `F` and `T`.
+    ///
+    /// The search starts next to `index` and never leaves the row. With `till`,
+    /// the position next to the occurrence, on the side of `index`, is returned.
+    ///
+    /// Returns None if the row has fewer than `count` occurrences in the given
+    /// direction or `count` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Direction, Index2, Jagged};
+    ///
+    /// let data = Jagged::from("foo(bar(baz))");
+    /// let index = Index2::new(0, 0);
+    /// assert_eq!(data.find_in_row(index, &'(', Direction::Forward, false, 2), Some(Index2::new(0, 7)));
+    /// assert_eq!(data.find_in_row(index, &'(', Direction::Forward, true, 1), Some(Index2::new(0, 2)));
+    ///
+    /// let index = Index2::new(0, 12);
+    /// assert_eq!(data.find_in_row(index, &'b', Direction::Backward, true, 1), Some(Index2::new(0, 9)));
+    /// assert_eq!(data.find_in_row(index, &'x', Direction::Backward, false, 1), None);
+    /// ```
+    #[must_use]
+    pub fn find_in_row(
+        &self,
+        index: Index2,
+        target: &T,
+        direction: Direction,
+        till: bool,
+        count: usize,
+    ) -> Option<Index2> {
+        let row = self.data.row(index.row)?;
+        let nth = count.checked_sub(1)?;
+        let is_target = |col: &usize| row[*col] == *target;
+        let col = match direction {
+            Direction::Forward => {
+                let col = (index.col + 1..row.len()).filter(is_target).nth(nth)?;
+                if till {
+                    col - 1
+                } else {
+                    col
+                }
+            }
+            Direction::Backward => {
+                let col = (0..index.col.min(row.len()))
+                    .rev()
+                    .filter(is_target)
+                    .nth(nth)?;
+                if till {
+                    col + 1
+                } else {
+                    col
+                }
+            }
+        };
+        Some(Index2::new(index.row, col))
+    }
+
+    /// Repeats a find motion, like vim's `;`. Use [`RowFind::reversed`] to repeat
+    /// it in the opposite direction, like vim's `,`.
+    ///
+    /// Unlike [`Jagged::find_in_row`], a repeated till motion skips an occurrence
+    /// right next to `index`, so that it does not get stuck.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Direction, Index2, Jagged, RowFind};
+    ///
+    /// let data = Jagged::from("a.b.c");
+    /// let find = RowFind::new('.', Direction::Forward, true);
+    /// assert_eq!(data.repeat_find_in_row(Index2::new(0, 0), &find, 1), Some(Index2::new(0, 2)));
+    /// ```
+    #[must_use]
+    pub fn repeat_find_in_row(
+        &self,
+        index: Index2,
+        find: &RowFind<T>,
+        count: usize,
+    ) -> Option<Index2> {
+        let start = match (find.till, find.direction) {
+            (true, Direction::Forward) => Index2::new(index.row, index.col + 1),
+            (true, Direction::Backward) if index.col > 0 => Index2::new(index.row, index.col - 1),
+            _ => index,
+        };
+        self.find_in_row(start, &find.target, find.direction, find.till, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_in_row_stays_in_row() {
+        let data = Jagged::from("ab\nab");
+        let find = |index, direction| data.find_in_row(index, &'a', direction, false, 1);
+        assert_eq!(find(Index2::new(0, 0), Direction::Forward), None);
+        assert_eq!(find(Index2::new(1, 0), Direction::Backward), None);
+        assert_eq!(
+            find(Index2::new(1, 9), Direction::Backward),
+            Some(Index2::new(1, 0))
+        );
+        assert_eq!(
+            data.find_in_row(Index2::new(0, 0), &'b', Direction::Forward, false, 0),
+            None
+        );
+        assert_eq!(
+            data.find_in_row(Index2::new(2, 0), &'b', Direction::Forward, false, 1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_repeat_till() {
+        let data = Jagged::new(vec![vec![0, 1, 0, 1, 0, 1]]);
+        let find = RowFind::new(1, Direction::Forward, true);
+
+        // A plain till motion next to its target does not move.
+        assert_eq!(
+            data.find_in_row(Index2::new(0, 0), &1, find.direction, true, 1),
+            Some(Index2::new(0, 0))
+        );
+        assert_eq!(
+            data.repeat_find_in_row(Index2::new(0, 0), &find, 1),
+            Some(Index2::new(0, 2))
+        );
+        assert_eq!(
+            data.repeat_find_in_row(Index2::new(0, 0), &find, 2),
+            Some(Index2::new(0, 4))
+        );
+
+        let reversed = find.reversed();
+        assert_eq!(
+            data.repeat_find_in_row(Index2::new(0, 4), &reversed, 1),
+            Some(Index2::new(0, 2))
+        );
+        assert_eq!(
+            data.repeat_find_in_row(Index2::new(0, 0), &reversed, 1),
+            None
+        );
+    }
+}
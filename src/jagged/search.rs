@@ -0,0 +1,116 @@
+//! # Search Module
+//!
+//! The `search` module contains the options that configure how a pattern is
+//! searched within a [`Jagged`](crate::Jagged) array.
+
+/// The case sensitivity of a search.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    /// Elements must match exactly.
+    Sensitive,
+    /// Elements match regardless of their case.
+    #[default]
+    Insensitive,
+    /// Case insensitive, unless the pattern contains an uppercase element.
+    Smart,
+}
+
+/// Options that configure a search.
+///
+/// The default options search case insensitive, which matches the behaviour of
+/// [`Jagged::match_indices`](crate::Jagged::match_indices).
+///
+/// # Examples
+///
+/// ```
+/// use edtui_jagged::{Case, Index2, Jagged, SearchOptions};
+///
+/// let data = Jagged::from("Foo foobar foo");
+/// let pattern: Vec<char> = "foo".chars().collect();
+/// let options = SearchOptions::default().case(Case::Sensitive).whole_word(true);
+///
+/// let matches: Vec<Index2> = data.search(&pattern, options).map(|(_, i)| i).collect();
+/// assert_eq!(matches, vec![Index2::new(0, 11)]);
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// The case sensitivity.
+    pub case: Case,
+    /// Only match if the match is not surrounded by word elements.
+    pub whole_word: bool,
+    /// Continue at the start of the array when the end is reached.
+    ///
+    /// Only applies to searches that start at a given position.
+    pub wrap_around: bool,
+}
+
+impl SearchOptions {
+    /// Sets the case sensitivity.
+    #[must_use]
+    pub fn case(mut self, case: Case) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Sets whether only whole words are matched.
+    #[must_use]
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
+    /// Sets whether the search wraps around at the end of the array.
+    #[must_use]
+    pub fn wrap_around(mut self, wrap_around: bool) -> Self {
+        self.wrap_around = wrap_around;
+        self
+    }
+}
+
+/// An item of a pattern that may span multiple rows.
+///
+/// # Examples
+///
+/// ```
+/// use edtui_jagged::{Index2, Jagged, Pattern, SearchOptions};
+///
+/// let data = Jagged::from("let a = 1;\nlet b = 2;");
+/// let pattern = Pattern::from_text("1;\nlet");
+///
+/// let mut matches = data.search_multiline(&pattern, SearchOptions::default());
+/// assert_eq!(matches.next(), Some((Index2::new(0, 8), Index2::new(1, 3))));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern<T> {
+    /// Matches a single element.
+    Element(T),
+    /// Matches the end of a row, i.e. the transition to the next row.
+    LineBreak,
+}
+
+impl<T> Pattern<T> {
+    /// Creates a pattern from rows, separated by [`Pattern::LineBreak`].
+    #[must_use]
+    pub fn from_rows<I, R>(rows: I) -> Vec<Self>
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoIterator<Item = T>,
+    {
+        let mut pattern = Vec::new();
+        for (i, row) in rows.into_iter().enumerate() {
+            if i > 0 {
+                pattern.push(Self::LineBreak);
+            }
+            pattern.extend(row.into_iter().map(Self::Element));
+        }
+        pattern
+    }
+}
+
+impl Pattern<char> {
+    /// Creates a pattern from text, where every `\n` is a [`Pattern::LineBreak`].
+    #[must_use]
+    pub fn from_text(text: &str) -> Vec<Self> {
+        Self::from_rows(text.split('\n').map(str::chars))
+    }
+}
@@ -0,0 +1,368 @@
+//! # Selection Module
+//!
+//! The `selection` module contains [`Selection`], a directed range within a
+//! [`Jagged`] array that mirrors vim's visual modes.
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// The kind of a [`Selection`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionKind {
+    /// Selects all elements between anchor and head, like vim's `v`.
+    #[default]
+    Charwise,
+    /// Selects all rows between anchor and head, like vim's `V`.
+    Linewise,
+    /// Selects the rectangle spanned by anchor and head, like vim's `Ctrl-v`.
+    Blockwise,
+}
+
+/// A selection between an anchor and a head, both inclusive.
+///
+/// The anchor is where the selection started and the head is where it currently
+/// ends, so the head may lie before the anchor.
+///
+/// # Examples
+///
+/// ```
+/// use edtui_jagged::{Index2, Jagged, Selection};
+///
+/// let mut data = Jagged::from("hello\nworld");
+/// let mut selection = Selection::charwise(Index2::new(1, 1), Index2::new(1, 1));
+/// selection.extend_to(Index2::new(0, 3));
+///
+/// assert_eq!(selection.start(), Index2::new(0, 3));
+/// assert_eq!(data.copy_selection(&selection), Jagged::from("lo\nwo"));
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    /// The position where the selection started.
+    pub anchor: Index2,
+    /// The position where the selection ends.
+    pub head: Index2,
+    /// The kind of the selection.
+    pub kind: SelectionKind,
+}
+
+impl Selection {
+    /// Instantiates a new [`Selection`].
+    #[must_use]
+    pub fn new(anchor: Index2, head: Index2, kind: SelectionKind) -> Self {
+        Self { anchor, head, kind }
+    }
+
+    /// Instantiates a new charwise [`Selection`].
+    #[must_use]
+    pub fn charwise(anchor: Index2, head: Index2) -> Self {
+        Self::new(anchor, head, SelectionKind::Charwise)
+    }
+
+    /// Instantiates a new linewise [`Selection`].
+    #[must_use]
+    pub fn linewise(anchor: Index2, head: Index2) -> Self {
+        Self::new(anchor, head, SelectionKind::Linewise)
+    }
+
+    /// Instantiates a new blockwise [`Selection`].
+    #[must_use]
+    pub fn blockwise(anchor: Index2, head: Index2) -> Self {
+        Self::new(anchor, head, SelectionKind::Blockwise)
+    }
+
+    /// Returns the smaller of anchor and head.
+    #[must_use]
+    pub fn start(&self) -> Index2 {
+        self.anchor.min(self.head)
+    }
+
+    /// Returns the larger of anchor and head.
+    #[must_use]
+    pub fn end(&self) -> Index2 {
+        self.anchor.max(self.head)
+    }
+
+    /// Returns the selection with the anchor at its start and the head at its end.
+    ///
+    /// A blockwise selection is normalised to its upper left and lower right corner.
+    #[must_use]
+    pub fn normalized(&self) -> Self {
+        let (anchor, head) = match self.kind {
+            SelectionKind::Charwise | SelectionKind::Linewise => (self.start(), self.end()),
+            SelectionKind::Blockwise => self.corners(),
+        };
+        Self::new(anchor, head, self.kind)
+    }
+
+    /// Moves the head to `index`, keeping the anchor in place.
+    pub fn extend_to(&mut self, index: Index2) {
+        self.head = index;
+    }
+
+    /// Returns `true` if the selection contains `index`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Selection};
+    ///
+    /// let selection = Selection::blockwise(Index2::new(2, 1), Index2::new(0, 3));
+    /// assert!(selection.contains(Index2::new(1, 2)));
+    /// assert!(!selection.contains(Index2::new(1, 4)));
+    /// ```
+    #[must_use]
+    pub fn contains(&self, index: Index2) -> bool {
+        match self.kind {
+            SelectionKind::Charwise => self.start() <= index && index <= self.end(),
+            SelectionKind::Linewise => (self.start().row..=self.end().row).contains(&index.row),
+            SelectionKind::Blockwise => {
+                let (upper_left, lower_right) = self.corners();
+                (upper_left.row..=lower_right.row).contains(&index.row)
+                    && (upper_left.col..=lower_right.col).contains(&index.col)
+            }
+        }
+    }
+
+    /// Returns the upper left and the lower right corner of the selection.
+    fn corners(&self) -> (Index2, Index2) {
+        let (a, h) = (self.anchor, self.head);
+        (
+            Index2::new(a.row.min(h.row), a.col.min(h.col)),
+            Index2::new(a.row.max(h.row), a.col.max(h.col)),
+        )
+    }
+}
+
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Removes the selected elements and returns them.
+    ///
+    /// A charwise selection that ends behind the last element of a row includes the
+    /// line break. A blockwise selection returns one row for every selected row.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged, Selection};
+    ///
+    /// let mut data = Jagged::from("abc\ndef\nghi");
+    /// let selection = Selection::blockwise(Index2::new(0, 1), Index2::new(2, 1));
+    ///
+    /// assert_eq!(data.extract_selection(&selection), Jagged::from("b\ne\nh"));
+    /// assert_eq!(data, Jagged::from("ac\ndf\ngi"));
+    /// ```
+    pub fn extract_selection(&mut self, selection: &Selection) -> Self {
+        if self.is_empty() {
+            return Self::empty();
+        }
+        match selection.kind {
+            SelectionKind::Charwise => {
+                let (start, end) = self.charwise_range(selection);
+                self.splice(start, end, Self::empty()).0
+            }
+            SelectionKind::Linewise => {
+                let (first, last) = self.linewise_rows(selection);
+                self.extract_rows(first..=last)
+            }
+            SelectionKind::Blockwise => {
+                let (upper_left, lower_right) = selection.corners();
+                self.extract_block_unpadded(
+                    upper_left.row..=lower_right.row,
+                    upper_left.col..=lower_right.col,
+                )
+            }
+        }
+    }
+
+    /// Returns the (exclusive) range of elements of a charwise selection.
+    fn charwise_range(&self, selection: &Selection) -> (Index2, Index2) {
+        let clamp = |index: Index2| {
+            let row = index.row.min(self.last_row_index());
+            Index2::new(row, index.col.min(self.len_col_unchecked(row)))
+        };
+        let (start, end) = (clamp(selection.start()), clamp(selection.end()));
+        let end = if end.col < self.len_col_unchecked(end.row) {
+            Index2::new(end.row, end.col + 1)
+        } else if end.row < self.last_row_index() {
+            Index2::new(end.row + 1, 0)
+        } else {
+            end
+        };
+        (start, end)
+    }
+
+    /// Returns the first and the last row of a linewise selection.
+    fn linewise_rows(&self, selection: &Selection) -> (usize, usize) {
+        let last_row = self.last_row_index();
+        (
+            selection.start().row.min(last_row),
+            selection.end().row.min(last_row),
+        )
+    }
+}
+
+impl<T: Clone, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Returns a copy of the selected elements.
+    ///
+    /// See [`Jagged::extract_selection`].
+    #[must_use]
+    pub fn copy_selection(&self, selection: &Selection) -> Self {
+        if self.is_empty() {
+            return Self::empty();
+        }
+        match selection.kind {
+            SelectionKind::Charwise => {
+                let (start, end) = self.charwise_range(selection);
+                let rows = (start.row..=end.row)
+                    .map(|row| {
+                        let line = self.data.row(row).expect("row index out of bounds");
+                        let first = if row == start.row { start.col } else { 0 };
+                        let last = if row == end.row { end.col } else { line.len() };
+                        line[first..last].to_vec()
+                    })
+                    .collect();
+                Self::from_storage(S::from_rows(rows))
+            }
+            SelectionKind::Linewise => {
+                let (first, last) = self.linewise_rows(selection);
+                let rows = (first..=last)
+                    .filter_map(|row| self.data.row(row).cloned())
+                    .collect();
+                Self::from_storage(S::from_rows(rows))
+            }
+            SelectionKind::Blockwise => {
+                let (upper_left, lower_right) = selection.corners();
+                self.copy_block(
+                    upper_left.row..=lower_right.row,
+                    upper_left.col..=lower_right.col,
+                    None,
+                )
+            }
+        }
+    }
+
+    /// Replaces the selected elements with `replacement` and returns the removed
+    /// elements.
+    ///
+    /// For a blockwise selection, every selected row is replaced by the row of
+    /// `replacement` with the same offset. A single row `replacement` is inserted
+    /// into every selected row. Rows that end before the block are left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged, Selection};
+    ///
+    /// let mut data = Jagged::from("one\ntwo\nthree");
+    /// let selection = Selection::linewise(Index2::new(1, 2), Index2::new(0, 0));
+    ///
+    /// let removed = data.replace_selection(&selection, Jagged::from("1\n2\n3"));
+    /// assert_eq!(removed, Jagged::from("one\ntwo"));
+    /// assert_eq!(data, Jagged::from("1\n2\n3\nthree"));
+    /// ```
+    pub fn replace_selection(&mut self, selection: &Selection, replacement: Self) -> Self {
+        if self.is_empty() {
+            let mut replacement = replacement;
+            self.append(&mut replacement);
+            return Self::empty();
+        }
+        match selection.kind {
+            SelectionKind::Charwise => {
+                let (start, end) = self.charwise_range(selection);
+                self.splice(start, end, replacement).0
+            }
+            SelectionKind::Linewise => {
+                let (first, last) = self.linewise_rows(selection);
+                let removed = self.splice_rows(first, last - first + 1, replacement.data);
+                Self::from_storage(removed)
+            }
+            SelectionKind::Blockwise => {
+                let (upper_left, lower_right) = selection.corners();
+                // Like Vim, rows that end before the block are left untouched.
+                let skipped: Vec<bool> = (upper_left.row..=lower_right.row)
+                    .map(|row| self.len_col(row).is_some_and(|len| len <= upper_left.col))
+                    .collect();
+                let removed = self.extract_block(
+                    upper_left.row..=lower_right.row,
+                    upper_left.col..=lower_right.col,
+                    None,
+                );
+                for (i, skip) in skipped.into_iter().enumerate().take(removed.len()) {
+                    if skip {
+                        continue;
+                    }
+                    let elements = match replacement.data.len() {
+                        1 => replacement.data.row(0),
+                        _ => replacement.data.row(i),
+                    };
+                    let elements = elements.map(|row| row.to_vec()).unwrap_or_default();
+                    let index = Index2::new(upper_left.row + i, upper_left.col);
+                    let inserted = Self::from_storage(S::from_rows(vec![elements]));
+                    let _ = self.splice(index, index, inserted);
+                }
+                removed
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized() {
+        let selection = Selection::blockwise(Index2::new(2, 1), Index2::new(0, 3));
+        assert_eq!(
+            selection.normalized(),
+            Selection::blockwise(Index2::new(0, 1), Index2::new(2, 3))
+        );
+
+        let selection = Selection::charwise(Index2::new(2, 1), Index2::new(0, 3));
+        assert_eq!(selection.normalized().anchor, Index2::new(0, 3));
+        assert!(selection.contains(Index2::new(1, 9)));
+        assert!(!selection.contains(Index2::new(2, 2)));
+    }
+
+    #[test]
+    fn test_extract_charwise_selection() {
+        let mut data = Jagged::from("hello\nworld");
+        let selection = Selection::charwise(Index2::new(0, 5), Index2::new(0, 3));
+
+        // The selection ends on the line break of the first row.
+        assert_eq!(data.extract_selection(&selection), Jagged::from("lo\n"));
+        assert_eq!(data, Jagged::from("helworld"));
+    }
+
+    #[test]
+    fn test_copy_selection() {
+        let data = Jagged::from("abc\nd\nefg");
+        let copy = |selection: Selection| data.copy_selection(&selection);
+
+        assert_eq!(
+            copy(Selection::charwise(Index2::new(0, 2), Index2::new(2, 0))),
+            Jagged::from("c\nd\ne")
+        );
+        assert_eq!(
+            copy(Selection::linewise(Index2::new(1, 0), Index2::new(5, 0))),
+            Jagged::from("d\nefg")
+        );
+        assert_eq!(
+            copy(Selection::blockwise(Index2::new(0, 1), Index2::new(2, 2))),
+            Jagged::from("bc\n\nfg")
+        );
+    }
+
+    #[test]
+    fn test_replace_blockwise_selection() {
+        let mut data = Jagged::from("abc\nd\nefg");
+        let selection = Selection::blockwise(Index2::new(0, 1), Index2::new(2, 1));
+
+        // The second row ends before the block and is left untouched.
+        let removed = data.replace_selection(&selection, Jagged::from("XY"));
+        assert_eq!(removed, Jagged::from("b\n\nf"));
+        assert_eq!(data, Jagged::from("aXYc\nd\neXYg"));
+
+        let mut data = Jagged::from("abc\n\nefg");
+        let selection = Selection::blockwise(Index2::new(0, 0), Index2::new(2, 0));
+        let _ = data.replace_selection(&selection, Jagged::from("1\n2\n3"));
+        assert_eq!(data, Jagged::from("1bc\n\n3fg"));
+    }
+}
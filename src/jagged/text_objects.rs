@@ -0,0 +1,666 @@
+//! # Text Objects Module
+//!
+//! The `text_objects` module implements vim's text objects on a `Jagged<char>`:
+//! words (`iw`, `aw`), quotes (`i"`, `a'`), brackets (`i(`, `a{`), XML tags
+//! (`it`, `at`) and paragraphs (`ip`, `ap`).
+//!
+//! Every text object is returned as an inclusive range `(start, end)` that can be
+//! passed to [`Jagged::extract`] as `start..=end`, so `diw` is
+//!
+//! ```
+//! use edtui_jagged::{DefaultClassifier, Index2, Jagged};
+//!
+//! let mut data = Jagged::from("foo bar baz");
+//! let (start, end) = data.inner_word(Index2::new(0, 5), &DefaultClassifier).unwrap();
+//! let _ = data.extract(start..=end);
+//! assert_eq!(data, Jagged::from("foo  baz"));
+//! ```
+//!
+//! An end at the end of a row includes its line break, so linewise objects such
+//! as paragraphs remove entire rows.
+use super::motions::{CharClass, CharClassifier};
+use crate::{Index2, Jagged, JaggedStorage};
+
+/// Returns the first and the last column of the run of chars of the same class
+/// around `col`.
+fn class_run<C: CharClassifier>(row: &[char], col: usize, classifier: &C) -> (usize, usize) {
+    let class = classifier.classify(row[col]);
+    let same = |col: &usize| classifier.classify(row[*col]) == class;
+    let start = (0..col).rev().take_while(same).last().unwrap_or(col);
+    let end = (col + 1..row.len()).take_while(same).last().unwrap_or(col);
+    (start, end)
+}
+
+/// The kind of an XML tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TagKind {
+    Open,
+    Close,
+    SelfClosing,
+}
+
+/// An XML tag from its `<` to its `>`.
+#[derive(Debug)]
+struct Tag {
+    start: Index2,
+    end: Index2,
+    name: String,
+    kind: TagKind,
+}
+
+impl<S: JaggedStorage<char>> Jagged<char, S> {
+    /// Returns the word around `index`, like vim's `iw`.
+    ///
+    /// A word is a run of chars of the same [`CharClass`] within a row, so on
+    /// whitespace the whitespace is returned. Returns None for an empty row.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{DefaultClassifier, Index2, Jagged};
+    ///
+    /// let data = Jagged::from("let foo_bar = 1;");
+    /// let word = data.inner_word(Index2::new(0, 6), &DefaultClassifier);
+    /// assert_eq!(word, Some((Index2::new(0, 4), Index2::new(0, 10))));
+    /// ```
+    #[must_use]
+    pub fn inner_word<C: CharClassifier>(
+        &self,
+        index: Index2,
+        classifier: &C,
+    ) -> Option<(Index2, Index2)> {
+        let index = self.motion_start(index)?;
+        let row = self.data.row(index.row)?;
+        if row.is_empty() {
+            return None;
+        }
+        let (start, end) = class_run(row, index.col, classifier);
+        Some((Index2::new(index.row, start), Index2::new(index.row, end)))
+    }
+
+    /// Returns the word around `index` with its surrounding whitespace, like vim's `aw`.
+    ///
+    /// Includes the whitespace behind the word, or the whitespace before it if
+    /// there is none behind. On whitespace, the whitespace and the following word
+    /// are returned. Returns None for an empty row.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{DefaultClassifier, Index2, Jagged};
+    ///
+    /// let data = Jagged::from("foo bar");
+    /// let word = data.around_word(Index2::new(0, 5), &DefaultClassifier);
+    /// assert_eq!(word, Some((Index2::new(0, 3), Index2::new(0, 6))));
+    /// ```
+    #[must_use]
+    pub fn around_word<C: CharClassifier>(
+        &self,
+        index: Index2,
+        classifier: &C,
+    ) -> Option<(Index2, Index2)> {
+        let index = self.motion_start(index)?;
+        let row = self.data.row(index.row)?;
+        if row.is_empty() {
+            return None;
+        }
+        let is_whitespace = |col: usize| classifier.classify(row[col]) == CharClass::Whitespace;
+        let (mut start, mut end) = class_run(row, index.col, classifier);
+        if is_whitespace(index.col) {
+            if end + 1 < row.len() {
+                end = class_run(row, end + 1, classifier).1;
+            } else if start > 0 {
+                start = class_run(row, start - 1, classifier).0;
+            }
+        } else if end + 1 < row.len() && is_whitespace(end + 1) {
+            end = class_run(row, end + 1, classifier).1;
+        } else if start > 0 && is_whitespace(start - 1) {
+            start = class_run(row, start - 1, classifier).0;
+        }
+        Some((Index2::new(index.row, start), Index2::new(index.row, end)))
+    }
+
+    /// Returns the text between the quotes around `index`, like vim's `i"`.
+    ///
+    /// Quotes are paired from the start of the row and quotes escaped by a
+    /// backslash are skipped. If `index` is not within quotes, the next quoted
+    /// text of the row is used. Returns None if there is none or it is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from(r#"say("hi \"you\"")"#);
+    /// let quoted = data.inner_quote(Index2::new(0, 0), '"');
+    /// assert_eq!(quoted, Some((Index2::new(0, 5), Index2::new(0, 14))));
+    /// ```
+    #[must_use]
+    pub fn inner_quote(&self, index: Index2, quote: char) -> Option<(Index2, Index2)> {
+        let (open, close) = self.quote_pair(index, quote)?;
+        (open.col + 1 < close.col).then(|| {
+            (
+                Index2::new(open.row, open.col + 1),
+                Index2::new(close.row, close.col - 1),
+            )
+        })
+    }
+
+    /// Returns the quoted text around `index` including the quotes and the
+    /// whitespace behind them, or before them if there is none behind, like
+    /// vim's `a"`.
+    ///
+    /// See [`Jagged::inner_quote`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("a 'b' c");
+    /// let quoted = data.around_quote(Index2::new(0, 3), '\'');
+    /// assert_eq!(quoted, Some((Index2::new(0, 2), Index2::new(0, 5))));
+    /// ```
+    #[must_use]
+    pub fn around_quote(&self, index: Index2, quote: char) -> Option<(Index2, Index2)> {
+        let (mut open, mut close) = self.quote_pair(index, quote)?;
+        let row = self.data.row(open.row)?;
+        let trailing = row[close.col + 1..]
+            .iter()
+            .take_while(|ch| ch.is_whitespace())
+            .count();
+        if trailing > 0 {
+            close.col += trailing;
+        } else {
+            open.col -= row[..open.col]
+                .iter()
+                .rev()
+                .take_while(|ch| ch.is_whitespace())
+                .count();
+        }
+        Some((open, close))
+    }
+
+    /// Returns the positions of the quotes around or behind `index`.
+    fn quote_pair(&self, index: Index2, quote: char) -> Option<(Index2, Index2)> {
+        let index = self.motion_start(index)?;
+        let row = self.data.row(index.row)?;
+        let quotes: Vec<usize> = (0..row.len())
+            .filter(|&col| row[col] == quote && (col == 0 || row[col - 1] != '\\'))
+            .collect();
+        let pair = quotes.chunks_exact(2).find(|pair| pair[1] >= index.col)?;
+        Some((
+            Index2::new(index.row, pair[0]),
+            Index2::new(index.row, pair[1]),
+        ))
+    }
+
+    /// Returns the text between the brackets `open` and `close` that enclose
+    /// `index`, like vim's `i(`.
+    ///
+    /// Brackets may span multiple rows. If the opening bracket ends its row, the
+    /// text is taken linewise, keeping the brackets on their rows. Returns None
+    /// if there are no enclosing brackets or they are empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("fn f() {\n    g(1, (2));\n}");
+    /// let inner = data.inner_bracket(Index2::new(1, 7), '(', ')');
+    /// assert_eq!(inner, Some((Index2::new(1, 6), Index2::new(1, 11))));
+    ///
+    /// let (start, end) = data.inner_bracket(Index2::new(1, 0), '{', '}').unwrap();
+    /// let _ = data.extract(start..=end);
+    /// assert_eq!(data, Jagged::from("fn f() {\n}"));
+    /// ```
+    #[must_use]
+    pub fn inner_bracket(
+        &self,
+        index: Index2,
+        open: char,
+        close: char,
+    ) -> Option<(Index2, Index2)> {
+        let (open, close) = self.bracket_pair(index, open, close)?;
+        self.inner_range(open, close)
+    }
+
+    /// Returns the text between and including the brackets `open` and `close`
+    /// that enclose `index`, like vim's `a(`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("a[b[c]d]");
+    /// let around = data.around_bracket(Index2::new(0, 6), '[', ']');
+    /// assert_eq!(around, Some((Index2::new(0, 1), Index2::new(0, 7))));
+    /// ```
+    #[must_use]
+    pub fn around_bracket(
+        &self,
+        index: Index2,
+        open: char,
+        close: char,
+    ) -> Option<(Index2, Index2)> {
+        self.bracket_pair(index, open, close)
+    }
+
+    /// Returns the positions of the brackets that enclose `index`. A bracket at
+    /// `index` encloses itself.
+    fn bracket_pair(&self, index: Index2, open: char, close: char) -> Option<(Index2, Index2)> {
+        let index = self.motion_start(index)?;
+        self.find_enclosing_pair(index, &[(open, close)])
+    }
+
+    /// Returns the text between the XML tags that enclose `index`, like vim's `it`.
+    ///
+    /// Like [`Jagged::inner_bracket`], the text is taken linewise if the opening tag
+    /// ends its row. Returns None if there are no enclosing tags or they are empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("<a><b x=\"1\">hi</b></a>");
+    /// let inner = data.inner_tag(Index2::new(0, 12));
+    /// assert_eq!(inner, Some((Index2::new(0, 12), Index2::new(0, 13))));
+    /// ```
+    #[must_use]
+    pub fn inner_tag(&self, index: Index2) -> Option<(Index2, Index2)> {
+        let (open, close) = self.tag_pair(index)?;
+        self.inner_range(open.end, close.start)
+    }
+
+    /// Returns the text between and including the XML tags that enclose `index`,
+    /// like vim's `at`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let data = Jagged::from("<a><b>hi</b><br/></a>");
+    /// assert_eq!(data.around_tag(Index2::new(0, 6)), Some((Index2::new(0, 3), Index2::new(0, 11))));
+    /// assert_eq!(data.around_tag(Index2::new(0, 13)), Some((Index2::new(0, 0), Index2::new(0, 20))));
+    /// ```
+    #[must_use]
+    pub fn around_tag(&self, index: Index2) -> Option<(Index2, Index2)> {
+        let (open, close) = self.tag_pair(index)?;
+        Some((open.start, close.end))
+    }
+
+    /// Returns the innermost pair of opening and closing tag that encloses `index`.
+    ///
+    /// Like [`Jagged::find_enclosing_pair`], the opening tag is searched backward
+    /// from `index` and its closing tag forward from there. A tag at `index`
+    /// encloses itself.
+    fn tag_pair(&self, index: Index2) -> Option<(Tag, Tag)> {
+        let mut index = self.motion_start(index)?;
+        if let Some(tag) = self.tag_at(index) {
+            match tag.kind {
+                TagKind::Open => {
+                    if let Some(close) = self.closing_tag(&tag) {
+                        return Some((tag, close));
+                    }
+                }
+                TagKind::Close => {
+                    if let Some(open) = self.opening_tag(&tag) {
+                        return Some((open, tag));
+                    }
+                }
+                TagKind::SelfClosing => {}
+            }
+            index = tag.start;
+        }
+        // The names of the tags that are closed between the candidate and `index`.
+        let mut closed: Vec<String> = Vec::new();
+        for tag in self.tags_before(index) {
+            match tag.kind {
+                TagKind::Close => closed.push(tag.name),
+                TagKind::SelfClosing => {}
+                TagKind::Open => {
+                    if let Some(i) = closed.iter().rposition(|name| *name == tag.name) {
+                        closed.truncate(i);
+                    } else if let Some(close) = self.closing_tag(&tag) {
+                        return Some((tag, close));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the tag that contains `index`.
+    fn tag_at(&self, index: Index2) -> Option<Tag> {
+        let at = self.get(index).map(|ch| (index, ch));
+        let (start, _) = at
+            .into_iter()
+            .chain(self.elements_before(index))
+            .find(|&(pos, &ch)| ch == '<' || (ch == '>' && pos != index))
+            .filter(|&(_, &ch)| ch == '<')?;
+        self.parse_tag(start).filter(|tag| tag.end >= index)
+    }
+
+    /// Returns the closing tag that matches the opening tag `open`.
+    fn closing_tag(&self, open: &Tag) -> Option<Tag> {
+        let mut depth = 0;
+        for tag in self.tags_after(open.end) {
+            if tag.name != open.name {
+                continue;
+            }
+            match tag.kind {
+                TagKind::Open => depth += 1,
+                TagKind::Close if depth == 0 => return Some(tag),
+                TagKind::Close => depth -= 1,
+                TagKind::SelfClosing => {}
+            }
+        }
+        None
+    }
+
+    /// Returns the opening tag that matches the closing tag `close`.
+    fn opening_tag(&self, close: &Tag) -> Option<Tag> {
+        let mut depth = 0;
+        for tag in self.tags_before(close.start) {
+            if tag.name != close.name {
+                continue;
+            }
+            match tag.kind {
+                TagKind::Close => depth += 1,
+                TagKind::Open if depth == 0 => return Some(tag),
+                TagKind::Open => depth -= 1,
+                TagKind::SelfClosing => {}
+            }
+        }
+        None
+    }
+
+    /// Returns the tags behind `index`.
+    fn tags_after(&self, index: Index2) -> impl Iterator<Item = Tag> + '_ {
+        self.elements_after(index)
+            .filter(|&(_, &ch)| ch == '<')
+            .filter_map(|(pos, _)| self.parse_tag(pos))
+    }
+
+    /// Returns the tags before `index`, in reverse order.
+    fn tags_before(&self, index: Index2) -> impl Iterator<Item = Tag> + '_ {
+        self.elements_before(index)
+            .filter(|&(_, &ch)| ch == '<')
+            .filter_map(|(pos, _)| self.parse_tag(pos))
+    }
+
+    /// Parses the tag whose `<` is at `start`. Returns None for comments,
+    /// declarations and unterminated tags.
+    fn parse_tag(&self, start: Index2) -> Option<Tag> {
+        let mut text = String::new();
+        let mut pos = start;
+        let end = loop {
+            let next = self.step_forward_char(pos)?;
+            if next.row != pos.row {
+                text.push(' ');
+            }
+            pos = next;
+            match self.get(pos) {
+                Some('>') => break pos,
+                Some('<') => return None,
+                Some(&ch) => text.push(ch),
+                None => {}
+            }
+        };
+        let (kind, body) = if let Some(body) = text.strip_prefix('/') {
+            (TagKind::Close, body)
+        } else if let Some(body) = text.strip_suffix('/') {
+            (TagKind::SelfClosing, body)
+        } else {
+            (TagKind::Open, text.as_str())
+        };
+        let name = body.split_whitespace().next()?;
+        if name.starts_with(['!', '?']) || body.starts_with(char::is_whitespace) {
+            return None;
+        }
+        Some(Tag {
+            start,
+            end,
+            name: name.to_string(),
+            kind,
+        })
+    }
+
+    /// Returns the text between the last char `open` of an opening delimiter and
+    /// the first char `close` of a closing delimiter.
+    fn inner_range(&self, open: Index2, close: Index2) -> Option<(Index2, Index2)> {
+        let linewise = open.row < close.row && open.col + 1 >= self.len_col_unchecked(open.row);
+        let (start, end) = if linewise {
+            let close_row = self.data.row(close.row)?;
+            let end = if close_row[..close.col].iter().all(|ch| ch.is_whitespace()) {
+                let row = close.row - 1;
+                Index2::new(row, self.len_col_unchecked(row))
+            } else {
+                Index2::new(close.row, close.col - 1)
+            };
+            (Index2::new(open.row + 1, 0), end)
+        } else if close.col > 0 {
+            (
+                Index2::new(open.row, open.col + 1),
+                Index2::new(close.row, close.col - 1),
+            )
+        } else {
+            let row = close.row - 1;
+            (
+                Index2::new(open.row, open.col + 1),
+                Index2::new(row, self.last_col_index(row)),
+            )
+        };
+        (start <= end).then_some((start, end))
+    }
+
+    /// Returns the rows of the paragraph at `index`, like vim's `ip`.
+    ///
+    /// A paragraph is a run of non-empty rows, on an empty row the run of empty
+    /// rows is returned. The range ends behind the last row, so it removes the
+    /// rows entirely.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("a\n\nb\nc\n\nd");
+    /// let (start, end) = data.inner_paragraph(Index2::new(3, 0)).unwrap();
+    /// assert_eq!((start, end), (Index2::new(2, 0), Index2::new(3, 1)));
+    ///
+    /// let _ = data.extract(start..=end);
+    /// assert_eq!(data, Jagged::from("a\n\n\nd"));
+    /// ```
+    #[must_use]
+    pub fn inner_paragraph(&self, index: Index2) -> Option<(Index2, Index2)> {
+        let index = self.motion_start(index)?;
+        let (first, last) = self.row_run(index.row);
+        Some(self.rows_inclusive(first, last))
+    }
+
+    /// Returns the rows of the paragraph at `index` and the empty rows behind it,
+    /// or before it if there are none behind, like vim's `ap`.
+    ///
+    /// On an empty row, the empty rows and the paragraph behind them are returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::{Index2, Jagged};
+    ///
+    /// let mut data = Jagged::from("a\n\nb\nc\n\nd");
+    /// let (start, end) = data.around_paragraph(Index2::new(2, 0)).unwrap();
+    /// let _ = data.extract(start..=end);
+    /// assert_eq!(data, Jagged::from("a\n\nd"));
+    /// ```
+    #[must_use]
+    pub fn around_paragraph(&self, index: Index2) -> Option<(Index2, Index2)> {
+        let index = self.motion_start(index)?;
+        let (mut first, mut last) = self.row_run(index.row);
+        if last + 1 < self.len() {
+            last = self.row_run(last + 1).1;
+        } else if first > 0 && self.len_col_unchecked(index.row) > 0 {
+            first = self.row_run(first - 1).0;
+        }
+        Some(self.rows_inclusive(first, last))
+    }
+
+    /// Returns the first and the last row of the run of rows around `row` that are
+    /// either all empty or all non-empty.
+    fn row_run(&self, row: usize) -> (usize, usize) {
+        let is_empty = |row: usize| self.len_col_unchecked(row) == 0;
+        let empty = is_empty(row);
+        let first = (0..row).rev().take_while(|&r| is_empty(r) == empty).last();
+        let last = (row + 1..self.len())
+            .take_while(|&r| is_empty(r) == empty)
+            .last();
+        (first.unwrap_or(row), last.unwrap_or(row))
+    }
+
+    /// Returns the inclusive range that spans the rows `first` to `last`.
+    fn rows_inclusive(&self, first: usize, last: usize) -> (Index2, Index2) {
+        (
+            Index2::new(first, 0),
+            Index2::new(last, self.len_col_unchecked(last)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BigWordClassifier, DefaultClassifier};
+
+    fn extract(text: &str, range: Option<(Index2, Index2)>) -> (String, String) {
+        let mut data = Jagged::from(text);
+        let (start, end) = range.unwrap();
+        let removed = data.extract(start..=end);
+        (removed.to_string(), data.to_string())
+    }
+
+    #[test]
+    fn test_word_objects() {
+        let text = "  foo.bar  baz";
+        let data = Jagged::from(text);
+        let iw = data.inner_word(Index2::new(0, 7), &DefaultClassifier);
+        assert_eq!(extract(text, iw).1, "  foo.  baz");
+
+        let aw = data.around_word(Index2::new(0, 7), &DefaultClassifier);
+        assert_eq!(extract(text, aw).1, "  foo.baz");
+
+        let aw = data.around_word(Index2::new(0, 2), &BigWordClassifier);
+        assert_eq!(extract(text, aw).1, "  baz");
+
+        let aw = data.around_word(Index2::new(0, 0), &DefaultClassifier);
+        assert_eq!(extract(text, aw).1, ".bar  baz");
+
+        let aw = data.around_word(Index2::new(0, 13), &DefaultClassifier);
+        assert_eq!(extract(text, aw).1, "  foo.bar");
+        assert_eq!(
+            Jagged::from("a\n").inner_word(Index2::new(1, 0), &DefaultClassifier),
+            None
+        );
+    }
+
+    #[test]
+    fn test_quote_objects() {
+        let text = "x = 'a' + 'b'";
+        let data = Jagged::from(text);
+        assert_eq!(
+            extract(text, data.inner_quote(Index2::new(0, 11), '\'')).1,
+            "x = 'a' + ''"
+        );
+        assert_eq!(
+            extract(text, data.around_quote(Index2::new(0, 0), '\'')).1,
+            "x = + 'b'"
+        );
+        assert_eq!(
+            extract(text, data.around_quote(Index2::new(0, 12), '\'')).1,
+            "x = 'a' +"
+        );
+        assert_eq!(data.inner_quote(Index2::new(0, 0), '"'), None);
+        assert_eq!(
+            Jagged::from("''").inner_quote(Index2::new(0, 0), '\''),
+            None
+        );
+    }
+
+    #[test]
+    fn test_bracket_objects() {
+        let text = "f(a, (b),\n  c)";
+        let data = Jagged::from(text);
+        let inner = data.inner_bracket(Index2::new(1, 2), '(', ')');
+        assert_eq!(extract(text, inner), ("a, (b),\n  c".into(), "f()".into()));
+
+        let around = data.around_bracket(Index2::new(0, 7), '(', ')');
+        assert_eq!(extract(text, around).1, "f(a, ,\n  c)");
+        assert_eq!(data.inner_bracket(Index2::new(0, 0), '(', ')'), None);
+
+        let text = "{\n    a;\n    {}\n}";
+        let data = Jagged::from(text);
+        assert_eq!(data.inner_bracket(Index2::new(2, 5), '{', '}'), None);
+        assert_eq!(
+            extract(text, data.inner_bracket(Index2::new(1, 0), '{', '}')).1,
+            "{\n}"
+        );
+    }
+
+    #[test]
+    fn test_tag_objects() {
+        let text = "<div>\n  <p class=\"x\">a<br/>b</p>\n</div>";
+        let data = Jagged::from(text);
+        let inner = data.inner_tag(Index2::new(1, 14));
+        assert_eq!(
+            extract(text, inner).1,
+            "<div>\n  <p class=\"x\"></p>\n</div>"
+        );
+
+        let inner = data.inner_tag(Index2::new(1, 0));
+        assert_eq!(extract(text, inner).1, "<div>\n</div>");
+
+        let around = data.around_tag(Index2::new(1, 30));
+        assert_eq!(extract(text, around).1, "<div>\n  \n</div>");
+        assert_eq!(Jagged::from("<a></b>").around_tag(Index2::new(0, 1)), None);
+    }
+
+    #[test]
+    fn test_tag_pair_nesting() {
+        let text = "<a><a>x</a><br>y<b></b></a><a>z</a>";
+        let data = Jagged::from(text);
+        let around = |col| extract(text, data.around_tag(Index2::new(0, col))).0;
+
+        assert_eq!(around(6), "<a>x</a>");
+        assert_eq!(around(15), "<a><a>x</a><br>y<b></b></a>");
+        assert_eq!(around(19), "<b></b>");
+        assert_eq!(around(24), "<a><a>x</a><br>y<b></b></a>");
+        assert_eq!(around(30), "<a>z</a>");
+        assert_eq!(Jagged::from("<br>x").around_tag(Index2::new(0, 4)), None);
+    }
+
+    #[test]
+    fn test_paragraph_objects() {
+        let text = "a\nb\n\n\nc";
+        let data = Jagged::from(text);
+        assert_eq!(
+            extract(text, data.inner_paragraph(Index2::new(1, 0))).1,
+            "\n\nc"
+        );
+        assert_eq!(
+            extract(text, data.around_paragraph(Index2::new(0, 0))).1,
+            "c"
+        );
+        assert_eq!(
+            extract(text, data.around_paragraph(Index2::new(2, 0))).1,
+            "a\nb"
+        );
+        assert_eq!(
+            extract(text, data.around_paragraph(Index2::new(4, 0))).1,
+            "a\nb"
+        );
+    }
+}
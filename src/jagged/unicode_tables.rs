@@ -0,0 +1,1144 @@
+//! Unicode property tables, generated by `scripts/unicode_tables.py` from
+//! the Unicode Character Database 14.0.0. Do not edit by hand.
+
+/// The value of the Grapheme_Cluster_Break property of a char, see Unicode
+/// Standard Annex #29. Chars that are not listed in [`GRAPHEME_CATEGORIES`]
+/// are `Other`, except for the Hangul syllables `U+AC00..=U+D7A3`, which
+/// are `Lv` or `Lvt`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GraphemeCategory {
+    Cr,
+    Lf,
+    Control,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    Prepend,
+    SpacingMark,
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+    ExtendedPictographic,
+    Other,
+}
+
+/// The ranges of chars with a Grapheme_Cluster_Break other than `Other`,
+/// sorted by their first char.
+#[rustfmt::skip]
+pub(crate) const GRAPHEME_CATEGORIES: &[(char, char, GraphemeCategory)] = &[
+    ('\u{0}', '\u{9}', GraphemeCategory::Control),
+    ('\u{a}', '\u{a}', GraphemeCategory::Lf),
+    ('\u{b}', '\u{c}', GraphemeCategory::Control),
+    ('\u{d}', '\u{d}', GraphemeCategory::Cr),
+    ('\u{e}', '\u{1f}', GraphemeCategory::Control),
+    ('\u{7f}', '\u{9f}', GraphemeCategory::Control),
+    ('\u{a9}', '\u{a9}', GraphemeCategory::ExtendedPictographic),
+    ('\u{ad}', '\u{ad}', GraphemeCategory::Control),
+    ('\u{ae}', '\u{ae}', GraphemeCategory::ExtendedPictographic),
+    ('\u{300}', '\u{36f}', GraphemeCategory::Extend),
+    ('\u{483}', '\u{489}', GraphemeCategory::Extend),
+    ('\u{591}', '\u{5bd}', GraphemeCategory::Extend),
+    ('\u{5bf}', '\u{5bf}', GraphemeCategory::Extend),
+    ('\u{5c1}', '\u{5c2}', GraphemeCategory::Extend),
+    ('\u{5c4}', '\u{5c5}', GraphemeCategory::Extend),
+    ('\u{5c7}', '\u{5c7}', GraphemeCategory::Extend),
+    ('\u{600}', '\u{605}', GraphemeCategory::Prepend),
+    ('\u{610}', '\u{61a}', GraphemeCategory::Extend),
+    ('\u{61c}', '\u{61c}', GraphemeCategory::Control),
+    ('\u{64b}', '\u{65f}', GraphemeCategory::Extend),
+    ('\u{670}', '\u{670}', GraphemeCategory::Extend),
+    ('\u{6d6}', '\u{6dc}', GraphemeCategory::Extend),
+    ('\u{6dd}', '\u{6dd}', GraphemeCategory::Prepend),
+    ('\u{6df}', '\u{6e4}', GraphemeCategory::Extend),
+    ('\u{6e7}', '\u{6e8}', GraphemeCategory::Extend),
+    ('\u{6ea}', '\u{6ed}', GraphemeCategory::Extend),
+    ('\u{70f}', '\u{70f}', GraphemeCategory::Prepend),
+    ('\u{711}', '\u{711}', GraphemeCategory::Extend),
+    ('\u{730}', '\u{74a}', GraphemeCategory::Extend),
+    ('\u{7a6}', '\u{7b0}', GraphemeCategory::Extend),
+    ('\u{7eb}', '\u{7f3}', GraphemeCategory::Extend),
+    ('\u{7fd}', '\u{7fd}', GraphemeCategory::Extend),
+    ('\u{816}', '\u{819}', GraphemeCategory::Extend),
+    ('\u{81b}', '\u{823}', GraphemeCategory::Extend),
+    ('\u{825}', '\u{827}', GraphemeCategory::Extend),
+    ('\u{829}', '\u{82d}', GraphemeCategory::Extend),
+    ('\u{859}', '\u{85b}', GraphemeCategory::Extend),
+    ('\u{890}', '\u{891}', GraphemeCategory::Prepend),
+    ('\u{898}', '\u{89f}', GraphemeCategory::Extend),
+    ('\u{8ca}', '\u{8e1}', GraphemeCategory::Extend),
+    ('\u{8e2}', '\u{8e2}', GraphemeCategory::Prepend),
+    ('\u{8e3}', '\u{902}', GraphemeCategory::Extend),
+    ('\u{903}', '\u{903}', GraphemeCategory::SpacingMark),
+    ('\u{93a}', '\u{93a}', GraphemeCategory::Extend),
+    ('\u{93b}', '\u{93b}', GraphemeCategory::SpacingMark),
+    ('\u{93c}', '\u{93c}', GraphemeCategory::Extend),
+    ('\u{93e}', '\u{940}', GraphemeCategory::SpacingMark),
+    ('\u{941}', '\u{948}', GraphemeCategory::Extend),
+    ('\u{949}', '\u{94c}', GraphemeCategory::SpacingMark),
+    ('\u{94d}', '\u{94d}', GraphemeCategory::Extend),
+    ('\u{94e}', '\u{94f}', GraphemeCategory::SpacingMark),
+    ('\u{951}', '\u{957}', GraphemeCategory::Extend),
+    ('\u{962}', '\u{963}', GraphemeCategory::Extend),
+    ('\u{981}', '\u{981}', GraphemeCategory::Extend),
+    ('\u{982}', '\u{983}', GraphemeCategory::SpacingMark),
+    ('\u{9bc}', '\u{9bc}', GraphemeCategory::Extend),
+    ('\u{9be}', '\u{9be}', GraphemeCategory::Extend),
+    ('\u{9bf}', '\u{9c0}', GraphemeCategory::SpacingMark),
+    ('\u{9c1}', '\u{9c4}', GraphemeCategory::Extend),
+    ('\u{9c7}', '\u{9c8}', GraphemeCategory::SpacingMark),
+    ('\u{9cb}', '\u{9cc}', GraphemeCategory::SpacingMark),
+    ('\u{9cd}', '\u{9cd}', GraphemeCategory::Extend),
+    ('\u{9d7}', '\u{9d7}', GraphemeCategory::Extend),
+    ('\u{9e2}', '\u{9e3}', GraphemeCategory::Extend),
+    ('\u{9fe}', '\u{9fe}', GraphemeCategory::Extend),
+    ('\u{a01}', '\u{a02}', GraphemeCategory::Extend),
+    ('\u{a03}', '\u{a03}', GraphemeCategory::SpacingMark),
+    ('\u{a3c}', '\u{a3c}', GraphemeCategory::Extend),
+    ('\u{a3e}', '\u{a40}', GraphemeCategory::SpacingMark),
+    ('\u{a41}', '\u{a42}', GraphemeCategory::Extend),
+    ('\u{a47}', '\u{a48}', GraphemeCategory::Extend),
+    ('\u{a4b}', '\u{a4d}', GraphemeCategory::Extend),
+    ('\u{a51}', '\u{a51}', GraphemeCategory::Extend),
+    ('\u{a70}', '\u{a71}', GraphemeCategory::Extend),
+    ('\u{a75}', '\u{a75}', GraphemeCategory::Extend),
+    ('\u{a81}', '\u{a82}', GraphemeCategory::Extend),
+    ('\u{a83}', '\u{a83}', GraphemeCategory::SpacingMark),
+    ('\u{abc}', '\u{abc}', GraphemeCategory::Extend),
+    ('\u{abe}', '\u{ac0}', GraphemeCategory::SpacingMark),
+    ('\u{ac1}', '\u{ac5}', GraphemeCategory::Extend),
+    ('\u{ac7}', '\u{ac8}', GraphemeCategory::Extend),
+    ('\u{ac9}', '\u{ac9}', GraphemeCategory::SpacingMark),
+    ('\u{acb}', '\u{acc}', GraphemeCategory::SpacingMark),
+    ('\u{acd}', '\u{acd}', GraphemeCategory::Extend),
+    ('\u{ae2}', '\u{ae3}', GraphemeCategory::Extend),
+    ('\u{afa}', '\u{aff}', GraphemeCategory::Extend),
+    ('\u{b01}', '\u{b01}', GraphemeCategory::Extend),
+    ('\u{b02}', '\u{b03}', GraphemeCategory::SpacingMark),
+    ('\u{b3c}', '\u{b3c}', GraphemeCategory::Extend),
+    ('\u{b3e}', '\u{b3f}', GraphemeCategory::Extend),
+    ('\u{b40}', '\u{b40}', GraphemeCategory::SpacingMark),
+    ('\u{b41}', '\u{b44}', GraphemeCategory::Extend),
+    ('\u{b47}', '\u{b48}', GraphemeCategory::SpacingMark),
+    ('\u{b4b}', '\u{b4c}', GraphemeCategory::SpacingMark),
+    ('\u{b4d}', '\u{b4d}', GraphemeCategory::Extend),
+    ('\u{b55}', '\u{b57}', GraphemeCategory::Extend),
+    ('\u{b62}', '\u{b63}', GraphemeCategory::Extend),
+    ('\u{b82}', '\u{b82}', GraphemeCategory::Extend),
+    ('\u{bbe}', '\u{bbe}', GraphemeCategory::Extend),
+    ('\u{bbf}', '\u{bbf}', GraphemeCategory::SpacingMark),
+    ('\u{bc0}', '\u{bc0}', GraphemeCategory::Extend),
+    ('\u{bc1}', '\u{bc2}', GraphemeCategory::SpacingMark),
+    ('\u{bc6}', '\u{bc8}', GraphemeCategory::SpacingMark),
+    ('\u{bca}', '\u{bcc}', GraphemeCategory::SpacingMark),
+    ('\u{bcd}', '\u{bcd}', GraphemeCategory::Extend),
+    ('\u{bd7}', '\u{bd7}', GraphemeCategory::Extend),
+    ('\u{c00}', '\u{c00}', GraphemeCategory::Extend),
+    ('\u{c01}', '\u{c03}', GraphemeCategory::SpacingMark),
+    ('\u{c04}', '\u{c04}', GraphemeCategory::Extend),
+    ('\u{c3c}', '\u{c3c}', GraphemeCategory::Extend),
+    ('\u{c3e}', '\u{c40}', GraphemeCategory::Extend),
+    ('\u{c41}', '\u{c44}', GraphemeCategory::SpacingMark),
+    ('\u{c46}', '\u{c48}', GraphemeCategory::Extend),
+    ('\u{c4a}', '\u{c4d}', GraphemeCategory::Extend),
+    ('\u{c55}', '\u{c56}', GraphemeCategory::Extend),
+    ('\u{c62}', '\u{c63}', GraphemeCategory::Extend),
+    ('\u{c81}', '\u{c81}', GraphemeCategory::Extend),
+    ('\u{c82}', '\u{c83}', GraphemeCategory::SpacingMark),
+    ('\u{cbc}', '\u{cbc}', GraphemeCategory::Extend),
+    ('\u{cbe}', '\u{cbe}', GraphemeCategory::SpacingMark),
+    ('\u{cbf}', '\u{cbf}', GraphemeCategory::Extend),
+    ('\u{cc0}', '\u{cc1}', GraphemeCategory::SpacingMark),
+    ('\u{cc2}', '\u{cc2}', GraphemeCategory::Extend),
+    ('\u{cc3}', '\u{cc4}', GraphemeCategory::SpacingMark),
+    ('\u{cc6}', '\u{cc6}', GraphemeCategory::Extend),
+    ('\u{cc7}', '\u{cc8}', GraphemeCategory::SpacingMark),
+    ('\u{cca}', '\u{ccb}', GraphemeCategory::SpacingMark),
+    ('\u{ccc}', '\u{ccd}', GraphemeCategory::Extend),
+    ('\u{cd5}', '\u{cd6}', GraphemeCategory::Extend),
+    ('\u{ce2}', '\u{ce3}', GraphemeCategory::Extend),
+    ('\u{d00}', '\u{d01}', GraphemeCategory::Extend),
+    ('\u{d02}', '\u{d03}', GraphemeCategory::SpacingMark),
+    ('\u{d3b}', '\u{d3c}', GraphemeCategory::Extend),
+    ('\u{d3e}', '\u{d3e}', GraphemeCategory::Extend),
+    ('\u{d3f}', '\u{d40}', GraphemeCategory::SpacingMark),
+    ('\u{d41}', '\u{d44}', GraphemeCategory::Extend),
+    ('\u{d46}', '\u{d48}', GraphemeCategory::SpacingMark),
+    ('\u{d4a}', '\u{d4c}', GraphemeCategory::SpacingMark),
+    ('\u{d4d}', '\u{d4d}', GraphemeCategory::Extend),
+    ('\u{d4e}', '\u{d4e}', GraphemeCategory::Prepend),
+    ('\u{d57}', '\u{d57}', GraphemeCategory::Extend),
+    ('\u{d62}', '\u{d63}', GraphemeCategory::Extend),
+    ('\u{d81}', '\u{d81}', GraphemeCategory::Extend),
+    ('\u{d82}', '\u{d83}', GraphemeCategory::SpacingMark),
+    ('\u{dca}', '\u{dca}', GraphemeCategory::Extend),
+    ('\u{dcf}', '\u{dcf}', GraphemeCategory::Extend),
+    ('\u{dd0}', '\u{dd1}', GraphemeCategory::SpacingMark),
+    ('\u{dd2}', '\u{dd4}', GraphemeCategory::Extend),
+    ('\u{dd6}', '\u{dd6}', GraphemeCategory::Extend),
+    ('\u{dd8}', '\u{dde}', GraphemeCategory::SpacingMark),
+    ('\u{ddf}', '\u{ddf}', GraphemeCategory::Extend),
+    ('\u{df2}', '\u{df3}', GraphemeCategory::SpacingMark),
+    ('\u{e31}', '\u{e31}', GraphemeCategory::Extend),
+    ('\u{e33}', '\u{e33}', GraphemeCategory::SpacingMark),
+    ('\u{e34}', '\u{e3a}', GraphemeCategory::Extend),
+    ('\u{e47}', '\u{e4e}', GraphemeCategory::Extend),
+    ('\u{eb1}', '\u{eb1}', GraphemeCategory::Extend),
+    ('\u{eb3}', '\u{eb3}', GraphemeCategory::SpacingMark),
+    ('\u{eb4}', '\u{ebc}', GraphemeCategory::Extend),
+    ('\u{ec8}', '\u{ecd}', GraphemeCategory::Extend),
+    ('\u{f18}', '\u{f19}', GraphemeCategory::Extend),
+    ('\u{f35}', '\u{f35}', GraphemeCategory::Extend),
+    ('\u{f37}', '\u{f37}', GraphemeCategory::Extend),
+    ('\u{f39}', '\u{f39}', GraphemeCategory::Extend),
+    ('\u{f3e}', '\u{f3f}', GraphemeCategory::SpacingMark),
+    ('\u{f71}', '\u{f7e}', GraphemeCategory::Extend),
+    ('\u{f7f}', '\u{f7f}', GraphemeCategory::SpacingMark),
+    ('\u{f80}', '\u{f84}', GraphemeCategory::Extend),
+    ('\u{f86}', '\u{f87}', GraphemeCategory::Extend),
+    ('\u{f8d}', '\u{f97}', GraphemeCategory::Extend),
+    ('\u{f99}', '\u{fbc}', GraphemeCategory::Extend),
+    ('\u{fc6}', '\u{fc6}', GraphemeCategory::Extend),
+    ('\u{102d}', '\u{1030}', GraphemeCategory::Extend),
+    ('\u{1031}', '\u{1031}', GraphemeCategory::SpacingMark),
+    ('\u{1032}', '\u{1037}', GraphemeCategory::Extend),
+    ('\u{1039}', '\u{103a}', GraphemeCategory::Extend),
+    ('\u{103b}', '\u{103c}', GraphemeCategory::SpacingMark),
+    ('\u{103d}', '\u{103e}', GraphemeCategory::Extend),
+    ('\u{1056}', '\u{1057}', GraphemeCategory::SpacingMark),
+    ('\u{1058}', '\u{1059}', GraphemeCategory::Extend),
+    ('\u{105e}', '\u{1060}', GraphemeCategory::Extend),
+    ('\u{1071}', '\u{1074}', GraphemeCategory::Extend),
+    ('\u{1082}', '\u{1082}', GraphemeCategory::Extend),
+    ('\u{1084}', '\u{1084}', GraphemeCategory::SpacingMark),
+    ('\u{1085}', '\u{1086}', GraphemeCategory::Extend),
+    ('\u{108d}', '\u{108d}', GraphemeCategory::Extend),
+    ('\u{109d}', '\u{109d}', GraphemeCategory::Extend),
+    ('\u{1100}', '\u{115f}', GraphemeCategory::L),
+    ('\u{1160}', '\u{11a7}', GraphemeCategory::V),
+    ('\u{11a8}', '\u{11ff}', GraphemeCategory::T),
+    ('\u{135d}', '\u{135f}', GraphemeCategory::Extend),
+    ('\u{1712}', '\u{1714}', GraphemeCategory::Extend),
+    ('\u{1715}', '\u{1715}', GraphemeCategory::SpacingMark),
+    ('\u{1732}', '\u{1733}', GraphemeCategory::Extend),
+    ('\u{1734}', '\u{1734}', GraphemeCategory::SpacingMark),
+    ('\u{1752}', '\u{1753}', GraphemeCategory::Extend),
+    ('\u{1772}', '\u{1773}', GraphemeCategory::Extend),
+    ('\u{17b4}', '\u{17b5}', GraphemeCategory::Extend),
+    ('\u{17b6}', '\u{17b6}', GraphemeCategory::SpacingMark),
+    ('\u{17b7}', '\u{17bd}', GraphemeCategory::Extend),
+    ('\u{17be}', '\u{17c5}', GraphemeCategory::SpacingMark),
+    ('\u{17c6}', '\u{17c6}', GraphemeCategory::Extend),
+    ('\u{17c7}', '\u{17c8}', GraphemeCategory::SpacingMark),
+    ('\u{17c9}', '\u{17d3}', GraphemeCategory::Extend),
+    ('\u{17dd}', '\u{17dd}', GraphemeCategory::Extend),
+    ('\u{180b}', '\u{180d}', GraphemeCategory::Extend),
+    ('\u{180e}', '\u{180e}', GraphemeCategory::Control),
+    ('\u{180f}', '\u{180f}', GraphemeCategory::Extend),
+    ('\u{1885}', '\u{1886}', GraphemeCategory::Extend),
+    ('\u{18a9}', '\u{18a9}', GraphemeCategory::Extend),
+    ('\u{1920}', '\u{1922}', GraphemeCategory::Extend),
+    ('\u{1923}', '\u{1926}', GraphemeCategory::SpacingMark),
+    ('\u{1927}', '\u{1928}', GraphemeCategory::Extend),
+    ('\u{1929}', '\u{192b}', GraphemeCategory::SpacingMark),
+    ('\u{1930}', '\u{1931}', GraphemeCategory::SpacingMark),
+    ('\u{1932}', '\u{1932}', GraphemeCategory::Extend),
+    ('\u{1933}', '\u{1938}', GraphemeCategory::SpacingMark),
+    ('\u{1939}', '\u{193b}', GraphemeCategory::Extend),
+    ('\u{1a17}', '\u{1a18}', GraphemeCategory::Extend),
+    ('\u{1a19}', '\u{1a1a}', GraphemeCategory::SpacingMark),
+    ('\u{1a1b}', '\u{1a1b}', GraphemeCategory::Extend),
+    ('\u{1a55}', '\u{1a55}', GraphemeCategory::SpacingMark),
+    ('\u{1a56}', '\u{1a56}', GraphemeCategory::Extend),
+    ('\u{1a57}', '\u{1a57}', GraphemeCategory::SpacingMark),
+    ('\u{1a58}', '\u{1a5e}', GraphemeCategory::Extend),
+    ('\u{1a60}', '\u{1a60}', GraphemeCategory::Extend),
+    ('\u{1a62}', '\u{1a62}', GraphemeCategory::Extend),
+    ('\u{1a65}', '\u{1a6c}', GraphemeCategory::Extend),
+    ('\u{1a6d}', '\u{1a72}', GraphemeCategory::SpacingMark),
+    ('\u{1a73}', '\u{1a7c}', GraphemeCategory::Extend),
+    ('\u{1a7f}', '\u{1a7f}', GraphemeCategory::Extend),
+    ('\u{1ab0}', '\u{1ace}', GraphemeCategory::Extend),
+    ('\u{1b00}', '\u{1b03}', GraphemeCategory::Extend),
+    ('\u{1b04}', '\u{1b04}', GraphemeCategory::SpacingMark),
+    ('\u{1b34}', '\u{1b3a}', GraphemeCategory::Extend),
+    ('\u{1b3b}', '\u{1b3b}', GraphemeCategory::SpacingMark),
+    ('\u{1b3c}', '\u{1b3c}', GraphemeCategory::Extend),
+    ('\u{1b3d}', '\u{1b41}', GraphemeCategory::SpacingMark),
+    ('\u{1b42}', '\u{1b42}', GraphemeCategory::Extend),
+    ('\u{1b43}', '\u{1b44}', GraphemeCategory::SpacingMark),
+    ('\u{1b6b}', '\u{1b73}', GraphemeCategory::Extend),
+    ('\u{1b80}', '\u{1b81}', GraphemeCategory::Extend),
+    ('\u{1b82}', '\u{1b82}', GraphemeCategory::SpacingMark),
+    ('\u{1ba1}', '\u{1ba1}', GraphemeCategory::SpacingMark),
+    ('\u{1ba2}', '\u{1ba5}', GraphemeCategory::Extend),
+    ('\u{1ba6}', '\u{1ba7}', GraphemeCategory::SpacingMark),
+    ('\u{1ba8}', '\u{1ba9}', GraphemeCategory::Extend),
+    ('\u{1baa}', '\u{1baa}', GraphemeCategory::SpacingMark),
+    ('\u{1bab}', '\u{1bad}', GraphemeCategory::Extend),
+    ('\u{1be6}', '\u{1be6}', GraphemeCategory::Extend),
+    ('\u{1be7}', '\u{1be7}', GraphemeCategory::SpacingMark),
+    ('\u{1be8}', '\u{1be9}', GraphemeCategory::Extend),
+    ('\u{1bea}', '\u{1bec}', GraphemeCategory::SpacingMark),
+    ('\u{1bed}', '\u{1bed}', GraphemeCategory::Extend),
+    ('\u{1bee}', '\u{1bee}', GraphemeCategory::SpacingMark),
+    ('\u{1bef}', '\u{1bf1}', GraphemeCategory::Extend),
+    ('\u{1bf2}', '\u{1bf3}', GraphemeCategory::SpacingMark),
+    ('\u{1c24}', '\u{1c2b}', GraphemeCategory::SpacingMark),
+    ('\u{1c2c}', '\u{1c33}', GraphemeCategory::Extend),
+    ('\u{1c34}', '\u{1c35}', GraphemeCategory::SpacingMark),
+    ('\u{1c36}', '\u{1c37}', GraphemeCategory::Extend),
+    ('\u{1cd0}', '\u{1cd2}', GraphemeCategory::Extend),
+    ('\u{1cd4}', '\u{1ce0}', GraphemeCategory::Extend),
+    ('\u{1ce1}', '\u{1ce1}', GraphemeCategory::SpacingMark),
+    ('\u{1ce2}', '\u{1ce8}', GraphemeCategory::Extend),
+    ('\u{1ced}', '\u{1ced}', GraphemeCategory::Extend),
+    ('\u{1cf4}', '\u{1cf4}', GraphemeCategory::Extend),
+    ('\u{1cf7}', '\u{1cf7}', GraphemeCategory::SpacingMark),
+    ('\u{1cf8}', '\u{1cf9}', GraphemeCategory::Extend),
+    ('\u{1dc0}', '\u{1dff}', GraphemeCategory::Extend),
+    ('\u{200b}', '\u{200b}', GraphemeCategory::Control),
+    ('\u{200c}', '\u{200c}', GraphemeCategory::Extend),
+    ('\u{200d}', '\u{200d}', GraphemeCategory::Zwj),
+    ('\u{200e}', '\u{200f}', GraphemeCategory::Control),
+    ('\u{2028}', '\u{202e}', GraphemeCategory::Control),
+    ('\u{203c}', '\u{203c}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2049}', '\u{2049}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2060}', '\u{206f}', GraphemeCategory::Control),
+    ('\u{20d0}', '\u{20f0}', GraphemeCategory::Extend),
+    ('\u{2122}', '\u{2122}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2139}', '\u{2139}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2194}', '\u{2199}', GraphemeCategory::ExtendedPictographic),
+    ('\u{21a9}', '\u{21aa}', GraphemeCategory::ExtendedPictographic),
+    ('\u{231a}', '\u{231b}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2328}', '\u{2328}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2388}', '\u{2388}', GraphemeCategory::ExtendedPictographic),
+    ('\u{23cf}', '\u{23cf}', GraphemeCategory::ExtendedPictographic),
+    ('\u{23e9}', '\u{23f3}', GraphemeCategory::ExtendedPictographic),
+    ('\u{23f8}', '\u{23fa}', GraphemeCategory::ExtendedPictographic),
+    ('\u{24c2}', '\u{24c2}', GraphemeCategory::ExtendedPictographic),
+    ('\u{25aa}', '\u{25ab}', GraphemeCategory::ExtendedPictographic),
+    ('\u{25b6}', '\u{25b6}', GraphemeCategory::ExtendedPictographic),
+    ('\u{25c0}', '\u{25c0}', GraphemeCategory::ExtendedPictographic),
+    ('\u{25fb}', '\u{25fe}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2600}', '\u{2605}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2607}', '\u{2612}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2614}', '\u{2685}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2690}', '\u{2705}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2708}', '\u{2712}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2714}', '\u{2714}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2716}', '\u{2716}', GraphemeCategory::ExtendedPictographic),
+    ('\u{271d}', '\u{271d}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2721}', '\u{2721}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2728}', '\u{2728}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2733}', '\u{2734}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2744}', '\u{2744}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2747}', '\u{2747}', GraphemeCategory::ExtendedPictographic),
+    ('\u{274c}', '\u{274c}', GraphemeCategory::ExtendedPictographic),
+    ('\u{274e}', '\u{274e}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2753}', '\u{2755}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2757}', '\u{2757}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2763}', '\u{2767}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2795}', '\u{2797}', GraphemeCategory::ExtendedPictographic),
+    ('\u{27a1}', '\u{27a1}', GraphemeCategory::ExtendedPictographic),
+    ('\u{27b0}', '\u{27b0}', GraphemeCategory::ExtendedPictographic),
+    ('\u{27bf}', '\u{27bf}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2934}', '\u{2935}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2b05}', '\u{2b07}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2b1b}', '\u{2b1c}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2b50}', '\u{2b50}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2b55}', '\u{2b55}', GraphemeCategory::ExtendedPictographic),
+    ('\u{2cef}', '\u{2cf1}', GraphemeCategory::Extend),
+    ('\u{2d7f}', '\u{2d7f}', GraphemeCategory::Extend),
+    ('\u{2de0}', '\u{2dff}', GraphemeCategory::Extend),
+    ('\u{302a}', '\u{302f}', GraphemeCategory::Extend),
+    ('\u{3030}', '\u{3030}', GraphemeCategory::ExtendedPictographic),
+    ('\u{303d}', '\u{303d}', GraphemeCategory::ExtendedPictographic),
+    ('\u{3099}', '\u{309a}', GraphemeCategory::Extend),
+    ('\u{3297}', '\u{3297}', GraphemeCategory::ExtendedPictographic),
+    ('\u{3299}', '\u{3299}', GraphemeCategory::ExtendedPictographic),
+    ('\u{a66f}', '\u{a672}', GraphemeCategory::Extend),
+    ('\u{a674}', '\u{a67d}', GraphemeCategory::Extend),
+    ('\u{a69e}', '\u{a69f}', GraphemeCategory::Extend),
+    ('\u{a6f0}', '\u{a6f1}', GraphemeCategory::Extend),
+    ('\u{a802}', '\u{a802}', GraphemeCategory::Extend),
+    ('\u{a806}', '\u{a806}', GraphemeCategory::Extend),
+    ('\u{a80b}', '\u{a80b}', GraphemeCategory::Extend),
+    ('\u{a823}', '\u{a824}', GraphemeCategory::SpacingMark),
+    ('\u{a825}', '\u{a826}', GraphemeCategory::Extend),
+    ('\u{a827}', '\u{a827}', GraphemeCategory::SpacingMark),
+    ('\u{a82c}', '\u{a82c}', GraphemeCategory::Extend),
+    ('\u{a880}', '\u{a881}', GraphemeCategory::SpacingMark),
+    ('\u{a8b4}', '\u{a8c3}', GraphemeCategory::SpacingMark),
+    ('\u{a8c4}', '\u{a8c5}', GraphemeCategory::Extend),
+    ('\u{a8e0}', '\u{a8f1}', GraphemeCategory::Extend),
+    ('\u{a8ff}', '\u{a8ff}', GraphemeCategory::Extend),
+    ('\u{a926}', '\u{a92d}', GraphemeCategory::Extend),
+    ('\u{a947}', '\u{a951}', GraphemeCategory::Extend),
+    ('\u{a952}', '\u{a953}', GraphemeCategory::SpacingMark),
+    ('\u{a960}', '\u{a97c}', GraphemeCategory::L),
+    ('\u{a980}', '\u{a982}', GraphemeCategory::Extend),
+    ('\u{a983}', '\u{a983}', GraphemeCategory::SpacingMark),
+    ('\u{a9b3}', '\u{a9b3}', GraphemeCategory::Extend),
+    ('\u{a9b4}', '\u{a9b5}', GraphemeCategory::SpacingMark),
+    ('\u{a9b6}', '\u{a9b9}', GraphemeCategory::Extend),
+    ('\u{a9ba}', '\u{a9bb}', GraphemeCategory::SpacingMark),
+    ('\u{a9bc}', '\u{a9bd}', GraphemeCategory::Extend),
+    ('\u{a9be}', '\u{a9c0}', GraphemeCategory::SpacingMark),
+    ('\u{a9e5}', '\u{a9e5}', GraphemeCategory::Extend),
+    ('\u{aa29}', '\u{aa2e}', GraphemeCategory::Extend),
+    ('\u{aa2f}', '\u{aa30}', GraphemeCategory::SpacingMark),
+    ('\u{aa31}', '\u{aa32}', GraphemeCategory::Extend),
+    ('\u{aa33}', '\u{aa34}', GraphemeCategory::SpacingMark),
+    ('\u{aa35}', '\u{aa36}', GraphemeCategory::Extend),
+    ('\u{aa43}', '\u{aa43}', GraphemeCategory::Extend),
+    ('\u{aa4c}', '\u{aa4c}', GraphemeCategory::Extend),
+    ('\u{aa4d}', '\u{aa4d}', GraphemeCategory::SpacingMark),
+    ('\u{aa7c}', '\u{aa7c}', GraphemeCategory::Extend),
+    ('\u{aab0}', '\u{aab0}', GraphemeCategory::Extend),
+    ('\u{aab2}', '\u{aab4}', GraphemeCategory::Extend),
+    ('\u{aab7}', '\u{aab8}', GraphemeCategory::Extend),
+    ('\u{aabe}', '\u{aabf}', GraphemeCategory::Extend),
+    ('\u{aac1}', '\u{aac1}', GraphemeCategory::Extend),
+    ('\u{aaeb}', '\u{aaeb}', GraphemeCategory::SpacingMark),
+    ('\u{aaec}', '\u{aaed}', GraphemeCategory::Extend),
+    ('\u{aaee}', '\u{aaef}', GraphemeCategory::SpacingMark),
+    ('\u{aaf5}', '\u{aaf5}', GraphemeCategory::SpacingMark),
+    ('\u{aaf6}', '\u{aaf6}', GraphemeCategory::Extend),
+    ('\u{abe3}', '\u{abe4}', GraphemeCategory::SpacingMark),
+    ('\u{abe5}', '\u{abe5}', GraphemeCategory::Extend),
+    ('\u{abe6}', '\u{abe7}', GraphemeCategory::SpacingMark),
+    ('\u{abe8}', '\u{abe8}', GraphemeCategory::Extend),
+    ('\u{abe9}', '\u{abea}', GraphemeCategory::SpacingMark),
+    ('\u{abec}', '\u{abec}', GraphemeCategory::SpacingMark),
+    ('\u{abed}', '\u{abed}', GraphemeCategory::Extend),
+    ('\u{d7b0}', '\u{d7c6}', GraphemeCategory::V),
+    ('\u{d7cb}', '\u{d7fb}', GraphemeCategory::T),
+    ('\u{fb1e}', '\u{fb1e}', GraphemeCategory::Extend),
+    ('\u{fe00}', '\u{fe0f}', GraphemeCategory::Extend),
+    ('\u{fe20}', '\u{fe2f}', GraphemeCategory::Extend),
+    ('\u{feff}', '\u{feff}', GraphemeCategory::Control),
+    ('\u{ff9e}', '\u{ff9f}', GraphemeCategory::Extend),
+    ('\u{fff0}', '\u{fffb}', GraphemeCategory::Control),
+    ('\u{101fd}', '\u{101fd}', GraphemeCategory::Extend),
+    ('\u{102e0}', '\u{102e0}', GraphemeCategory::Extend),
+    ('\u{10376}', '\u{1037a}', GraphemeCategory::Extend),
+    ('\u{10a01}', '\u{10a03}', GraphemeCategory::Extend),
+    ('\u{10a05}', '\u{10a06}', GraphemeCategory::Extend),
+    ('\u{10a0c}', '\u{10a0f}', GraphemeCategory::Extend),
+    ('\u{10a38}', '\u{10a3a}', GraphemeCategory::Extend),
+    ('\u{10a3f}', '\u{10a3f}', GraphemeCategory::Extend),
+    ('\u{10ae5}', '\u{10ae6}', GraphemeCategory::Extend),
+    ('\u{10d24}', '\u{10d27}', GraphemeCategory::Extend),
+    ('\u{10eab}', '\u{10eac}', GraphemeCategory::Extend),
+    ('\u{10f46}', '\u{10f50}', GraphemeCategory::Extend),
+    ('\u{10f82}', '\u{10f85}', GraphemeCategory::Extend),
+    ('\u{11000}', '\u{11000}', GraphemeCategory::SpacingMark),
+    ('\u{11001}', '\u{11001}', GraphemeCategory::Extend),
+    ('\u{11002}', '\u{11002}', GraphemeCategory::SpacingMark),
+    ('\u{11038}', '\u{11046}', GraphemeCategory::Extend),
+    ('\u{11070}', '\u{11070}', GraphemeCategory::Extend),
+    ('\u{11073}', '\u{11074}', GraphemeCategory::Extend),
+    ('\u{1107f}', '\u{11081}', GraphemeCategory::Extend),
+    ('\u{11082}', '\u{11082}', GraphemeCategory::SpacingMark),
+    ('\u{110b0}', '\u{110b2}', GraphemeCategory::SpacingMark),
+    ('\u{110b3}', '\u{110b6}', GraphemeCategory::Extend),
+    ('\u{110b7}', '\u{110b8}', GraphemeCategory::SpacingMark),
+    ('\u{110b9}', '\u{110ba}', GraphemeCategory::Extend),
+    ('\u{110bd}', '\u{110bd}', GraphemeCategory::Prepend),
+    ('\u{110c2}', '\u{110c2}', GraphemeCategory::Extend),
+    ('\u{110cd}', '\u{110cd}', GraphemeCategory::Prepend),
+    ('\u{11100}', '\u{11102}', GraphemeCategory::Extend),
+    ('\u{11127}', '\u{1112b}', GraphemeCategory::Extend),
+    ('\u{1112c}', '\u{1112c}', GraphemeCategory::SpacingMark),
+    ('\u{1112d}', '\u{11134}', GraphemeCategory::Extend),
+    ('\u{11145}', '\u{11146}', GraphemeCategory::SpacingMark),
+    ('\u{11173}', '\u{11173}', GraphemeCategory::Extend),
+    ('\u{11180}', '\u{11181}', GraphemeCategory::Extend),
+    ('\u{11182}', '\u{11182}', GraphemeCategory::SpacingMark),
+    ('\u{111b3}', '\u{111b5}', GraphemeCategory::SpacingMark),
+    ('\u{111b6}', '\u{111be}', GraphemeCategory::Extend),
+    ('\u{111bf}', '\u{111c0}', GraphemeCategory::SpacingMark),
+    ('\u{111c2}', '\u{111c3}', GraphemeCategory::Prepend),
+    ('\u{111c9}', '\u{111cc}', GraphemeCategory::Extend),
+    ('\u{111ce}', '\u{111ce}', GraphemeCategory::SpacingMark),
+    ('\u{111cf}', '\u{111cf}', GraphemeCategory::Extend),
+    ('\u{1122c}', '\u{1122e}', GraphemeCategory::SpacingMark),
+    ('\u{1122f}', '\u{11231}', GraphemeCategory::Extend),
+    ('\u{11232}', '\u{11233}', GraphemeCategory::SpacingMark),
+    ('\u{11234}', '\u{11234}', GraphemeCategory::Extend),
+    ('\u{11235}', '\u{11235}', GraphemeCategory::SpacingMark),
+    ('\u{11236}', '\u{11237}', GraphemeCategory::Extend),
+    ('\u{1123e}', '\u{1123e}', GraphemeCategory::Extend),
+    ('\u{112df}', '\u{112df}', GraphemeCategory::Extend),
+    ('\u{112e0}', '\u{112e2}', GraphemeCategory::SpacingMark),
+    ('\u{112e3}', '\u{112ea}', GraphemeCategory::Extend),
+    ('\u{11300}', '\u{11301}', GraphemeCategory::Extend),
+    ('\u{11302}', '\u{11303}', GraphemeCategory::SpacingMark),
+    ('\u{1133b}', '\u{1133c}', GraphemeCategory::Extend),
+    ('\u{1133e}', '\u{1133e}', GraphemeCategory::Extend),
+    ('\u{1133f}', '\u{1133f}', GraphemeCategory::SpacingMark),
+    ('\u{11340}', '\u{11340}', GraphemeCategory::Extend),
+    ('\u{11341}', '\u{11344}', GraphemeCategory::SpacingMark),
+    ('\u{11347}', '\u{11348}', GraphemeCategory::SpacingMark),
+    ('\u{1134b}', '\u{1134d}', GraphemeCategory::SpacingMark),
+    ('\u{11357}', '\u{11357}', GraphemeCategory::Extend),
+    ('\u{11362}', '\u{11363}', GraphemeCategory::SpacingMark),
+    ('\u{11366}', '\u{1136c}', GraphemeCategory::Extend),
+    ('\u{11370}', '\u{11374}', GraphemeCategory::Extend),
+    ('\u{11435}', '\u{11437}', GraphemeCategory::SpacingMark),
+    ('\u{11438}', '\u{1143f}', GraphemeCategory::Extend),
+    ('\u{11440}', '\u{11441}', GraphemeCategory::SpacingMark),
+    ('\u{11442}', '\u{11444}', GraphemeCategory::Extend),
+    ('\u{11445}', '\u{11445}', GraphemeCategory::SpacingMark),
+    ('\u{11446}', '\u{11446}', GraphemeCategory::Extend),
+    ('\u{1145e}', '\u{1145e}', GraphemeCategory::Extend),
+    ('\u{114b0}', '\u{114b0}', GraphemeCategory::Extend),
+    ('\u{114b1}', '\u{114b2}', GraphemeCategory::SpacingMark),
+    ('\u{114b3}', '\u{114b8}', GraphemeCategory::Extend),
+    ('\u{114b9}', '\u{114b9}', GraphemeCategory::SpacingMark),
+    ('\u{114ba}', '\u{114ba}', GraphemeCategory::Extend),
+    ('\u{114bb}', '\u{114bc}', GraphemeCategory::SpacingMark),
+    ('\u{114bd}', '\u{114bd}', GraphemeCategory::Extend),
+    ('\u{114be}', '\u{114be}', GraphemeCategory::SpacingMark),
+    ('\u{114bf}', '\u{114c0}', GraphemeCategory::Extend),
+    ('\u{114c1}', '\u{114c1}', GraphemeCategory::SpacingMark),
+    ('\u{114c2}', '\u{114c3}', GraphemeCategory::Extend),
+    ('\u{115af}', '\u{115af}', GraphemeCategory::Extend),
+    ('\u{115b0}', '\u{115b1}', GraphemeCategory::SpacingMark),
+    ('\u{115b2}', '\u{115b5}', GraphemeCategory::Extend),
+    ('\u{115b8}', '\u{115bb}', GraphemeCategory::SpacingMark),
+    ('\u{115bc}', '\u{115bd}', GraphemeCategory::Extend),
+    ('\u{115be}', '\u{115be}', GraphemeCategory::SpacingMark),
+    ('\u{115bf}', '\u{115c0}', GraphemeCategory::Extend),
+    ('\u{115dc}', '\u{115dd}', GraphemeCategory::Extend),
+    ('\u{11630}', '\u{11632}', GraphemeCategory::SpacingMark),
+    ('\u{11633}', '\u{1163a}', GraphemeCategory::Extend),
+    ('\u{1163b}', '\u{1163c}', GraphemeCategory::SpacingMark),
+    ('\u{1163d}', '\u{1163d}', GraphemeCategory::Extend),
+    ('\u{1163e}', '\u{1163e}', GraphemeCategory::SpacingMark),
+    ('\u{1163f}', '\u{11640}', GraphemeCategory::Extend),
+    ('\u{116ab}', '\u{116ab}', GraphemeCategory::Extend),
+    ('\u{116ac}', '\u{116ac}', GraphemeCategory::SpacingMark),
+    ('\u{116ad}', '\u{116ad}', GraphemeCategory::Extend),
+    ('\u{116ae}', '\u{116af}', GraphemeCategory::SpacingMark),
+    ('\u{116b0}', '\u{116b5}', GraphemeCategory::Extend),
+    ('\u{116b6}', '\u{116b6}', GraphemeCategory::SpacingMark),
+    ('\u{116b7}', '\u{116b7}', GraphemeCategory::Extend),
+    ('\u{1171d}', '\u{1171f}', GraphemeCategory::Extend),
+    ('\u{11722}', '\u{11725}', GraphemeCategory::Extend),
+    ('\u{11726}', '\u{11726}', GraphemeCategory::SpacingMark),
+    ('\u{11727}', '\u{1172b}', GraphemeCategory::Extend),
+    ('\u{1182c}', '\u{1182e}', GraphemeCategory::SpacingMark),
+    ('\u{1182f}', '\u{11837}', GraphemeCategory::Extend),
+    ('\u{11838}', '\u{11838}', GraphemeCategory::SpacingMark),
+    ('\u{11839}', '\u{1183a}', GraphemeCategory::Extend),
+    ('\u{11930}', '\u{11930}', GraphemeCategory::Extend),
+    ('\u{11931}', '\u{11935}', GraphemeCategory::SpacingMark),
+    ('\u{11937}', '\u{11938}', GraphemeCategory::SpacingMark),
+    ('\u{1193b}', '\u{1193c}', GraphemeCategory::Extend),
+    ('\u{1193d}', '\u{1193d}', GraphemeCategory::SpacingMark),
+    ('\u{1193e}', '\u{1193e}', GraphemeCategory::Extend),
+    ('\u{1193f}', '\u{1193f}', GraphemeCategory::Prepend),
+    ('\u{11940}', '\u{11940}', GraphemeCategory::SpacingMark),
+    ('\u{11941}', '\u{11941}', GraphemeCategory::Prepend),
+    ('\u{11942}', '\u{11942}', GraphemeCategory::SpacingMark),
+    ('\u{11943}', '\u{11943}', GraphemeCategory::Extend),
+    ('\u{119d1}', '\u{119d3}', GraphemeCategory::SpacingMark),
+    ('\u{119d4}', '\u{119d7}', GraphemeCategory::Extend),
+    ('\u{119da}', '\u{119db}', GraphemeCategory::Extend),
+    ('\u{119dc}', '\u{119df}', GraphemeCategory::SpacingMark),
+    ('\u{119e0}', '\u{119e0}', GraphemeCategory::Extend),
+    ('\u{119e4}', '\u{119e4}', GraphemeCategory::SpacingMark),
+    ('\u{11a01}', '\u{11a0a}', GraphemeCategory::Extend),
+    ('\u{11a33}', '\u{11a38}', GraphemeCategory::Extend),
+    ('\u{11a39}', '\u{11a39}', GraphemeCategory::SpacingMark),
+    ('\u{11a3a}', '\u{11a3a}', GraphemeCategory::Prepend),
+    ('\u{11a3b}', '\u{11a3e}', GraphemeCategory::Extend),
+    ('\u{11a47}', '\u{11a47}', GraphemeCategory::Extend),
+    ('\u{11a51}', '\u{11a56}', GraphemeCategory::Extend),
+    ('\u{11a57}', '\u{11a58}', GraphemeCategory::SpacingMark),
+    ('\u{11a59}', '\u{11a5b}', GraphemeCategory::Extend),
+    ('\u{11a84}', '\u{11a89}', GraphemeCategory::Prepend),
+    ('\u{11a8a}', '\u{11a96}', GraphemeCategory::Extend),
+    ('\u{11a97}', '\u{11a97}', GraphemeCategory::SpacingMark),
+    ('\u{11a98}', '\u{11a99}', GraphemeCategory::Extend),
+    ('\u{11c2f}', '\u{11c2f}', GraphemeCategory::SpacingMark),
+    ('\u{11c30}', '\u{11c36}', GraphemeCategory::Extend),
+    ('\u{11c38}', '\u{11c3d}', GraphemeCategory::Extend),
+    ('\u{11c3e}', '\u{11c3e}', GraphemeCategory::SpacingMark),
+    ('\u{11c3f}', '\u{11c3f}', GraphemeCategory::Extend),
+    ('\u{11c92}', '\u{11ca7}', GraphemeCategory::Extend),
+    ('\u{11ca9}', '\u{11ca9}', GraphemeCategory::SpacingMark),
+    ('\u{11caa}', '\u{11cb0}', GraphemeCategory::Extend),
+    ('\u{11cb1}', '\u{11cb1}', GraphemeCategory::SpacingMark),
+    ('\u{11cb2}', '\u{11cb3}', GraphemeCategory::Extend),
+    ('\u{11cb4}', '\u{11cb4}', GraphemeCategory::SpacingMark),
+    ('\u{11cb5}', '\u{11cb6}', GraphemeCategory::Extend),
+    ('\u{11d31}', '\u{11d36}', GraphemeCategory::Extend),
+    ('\u{11d3a}', '\u{11d3a}', GraphemeCategory::Extend),
+    ('\u{11d3c}', '\u{11d3d}', GraphemeCategory::Extend),
+    ('\u{11d3f}', '\u{11d45}', GraphemeCategory::Extend),
+    ('\u{11d46}', '\u{11d46}', GraphemeCategory::Prepend),
+    ('\u{11d47}', '\u{11d47}', GraphemeCategory::Extend),
+    ('\u{11d8a}', '\u{11d8e}', GraphemeCategory::SpacingMark),
+    ('\u{11d90}', '\u{11d91}', GraphemeCategory::Extend),
+    ('\u{11d93}', '\u{11d94}', GraphemeCategory::SpacingMark),
+    ('\u{11d95}', '\u{11d95}', GraphemeCategory::Extend),
+    ('\u{11d96}', '\u{11d96}', GraphemeCategory::SpacingMark),
+    ('\u{11d97}', '\u{11d97}', GraphemeCategory::Extend),
+    ('\u{11ef3}', '\u{11ef4}', GraphemeCategory::Extend),
+    ('\u{11ef5}', '\u{11ef6}', GraphemeCategory::SpacingMark),
+    ('\u{13430}', '\u{13438}', GraphemeCategory::Control),
+    ('\u{16af0}', '\u{16af4}', GraphemeCategory::Extend),
+    ('\u{16b30}', '\u{16b36}', GraphemeCategory::Extend),
+    ('\u{16f4f}', '\u{16f4f}', GraphemeCategory::Extend),
+    ('\u{16f51}', '\u{16f87}', GraphemeCategory::SpacingMark),
+    ('\u{16f8f}', '\u{16f92}', GraphemeCategory::Extend),
+    ('\u{16fe4}', '\u{16fe4}', GraphemeCategory::Extend),
+    ('\u{16ff0}', '\u{16ff1}', GraphemeCategory::SpacingMark),
+    ('\u{1bc9d}', '\u{1bc9e}', GraphemeCategory::Extend),
+    ('\u{1bca0}', '\u{1bca3}', GraphemeCategory::Control),
+    ('\u{1cf00}', '\u{1cf2d}', GraphemeCategory::Extend),
+    ('\u{1cf30}', '\u{1cf46}', GraphemeCategory::Extend),
+    ('\u{1d165}', '\u{1d165}', GraphemeCategory::Extend),
+    ('\u{1d166}', '\u{1d166}', GraphemeCategory::SpacingMark),
+    ('\u{1d167}', '\u{1d169}', GraphemeCategory::Extend),
+    ('\u{1d16d}', '\u{1d16d}', GraphemeCategory::SpacingMark),
+    ('\u{1d16e}', '\u{1d172}', GraphemeCategory::Extend),
+    ('\u{1d173}', '\u{1d17a}', GraphemeCategory::Control),
+    ('\u{1d17b}', '\u{1d182}', GraphemeCategory::Extend),
+    ('\u{1d185}', '\u{1d18b}', GraphemeCategory::Extend),
+    ('\u{1d1aa}', '\u{1d1ad}', GraphemeCategory::Extend),
+    ('\u{1d242}', '\u{1d244}', GraphemeCategory::Extend),
+    ('\u{1da00}', '\u{1da36}', GraphemeCategory::Extend),
+    ('\u{1da3b}', '\u{1da6c}', GraphemeCategory::Extend),
+    ('\u{1da75}', '\u{1da75}', GraphemeCategory::Extend),
+    ('\u{1da84}', '\u{1da84}', GraphemeCategory::Extend),
+    ('\u{1da9b}', '\u{1da9f}', GraphemeCategory::Extend),
+    ('\u{1daa1}', '\u{1daaf}', GraphemeCategory::Extend),
+    ('\u{1e000}', '\u{1e006}', GraphemeCategory::Extend),
+    ('\u{1e008}', '\u{1e018}', GraphemeCategory::Extend),
+    ('\u{1e01b}', '\u{1e021}', GraphemeCategory::Extend),
+    ('\u{1e023}', '\u{1e024}', GraphemeCategory::Extend),
+    ('\u{1e026}', '\u{1e02a}', GraphemeCategory::Extend),
+    ('\u{1e130}', '\u{1e136}', GraphemeCategory::Extend),
+    ('\u{1e2ae}', '\u{1e2ae}', GraphemeCategory::Extend),
+    ('\u{1e2ec}', '\u{1e2ef}', GraphemeCategory::Extend),
+    ('\u{1e8d0}', '\u{1e8d6}', GraphemeCategory::Extend),
+    ('\u{1e944}', '\u{1e94a}', GraphemeCategory::Extend),
+    ('\u{1f000}', '\u{1f0ff}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f10d}', '\u{1f10f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f12f}', '\u{1f12f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f16c}', '\u{1f171}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f17e}', '\u{1f17f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f18e}', '\u{1f18e}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f191}', '\u{1f19a}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f1ad}', '\u{1f1e5}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f1e6}', '\u{1f1ff}', GraphemeCategory::RegionalIndicator),
+    ('\u{1f201}', '\u{1f20f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f21a}', '\u{1f21a}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f22f}', '\u{1f22f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f232}', '\u{1f23a}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f23c}', '\u{1f23f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f249}', '\u{1f3fa}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f3fb}', '\u{1f3ff}', GraphemeCategory::Extend),
+    ('\u{1f400}', '\u{1f53d}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f546}', '\u{1f64f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f680}', '\u{1f6ff}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f774}', '\u{1f77f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f7d5}', '\u{1f7ff}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f80c}', '\u{1f80f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f848}', '\u{1f84f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f85a}', '\u{1f85f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f888}', '\u{1f88f}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f8ae}', '\u{1f8ff}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f90c}', '\u{1f93a}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f93c}', '\u{1f945}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1f947}', '\u{1faff}', GraphemeCategory::ExtendedPictographic),
+    ('\u{1fc00}', '\u{1fffd}', GraphemeCategory::ExtendedPictographic),
+    ('\u{e0000}', '\u{e001f}', GraphemeCategory::Control),
+    ('\u{e0020}', '\u{e007f}', GraphemeCategory::Extend),
+    ('\u{e0080}', '\u{e00ff}', GraphemeCategory::Control),
+    ('\u{e0100}', '\u{e01ef}', GraphemeCategory::Extend),
+    ('\u{e01f0}', '\u{e0fff}', GraphemeCategory::Control),
+];
+
+/// The ranges of chars with an East_Asian_Width of Wide or Fullwidth,
+/// sorted by their first char.
+#[rustfmt::skip]
+pub(crate) const WIDE: &[(char, char)] = &[
+    ('\u{1100}', '\u{115f}'),
+    ('\u{231a}', '\u{231b}'),
+    ('\u{2329}', '\u{232a}'),
+    ('\u{23e9}', '\u{23ec}'),
+    ('\u{23f0}', '\u{23f0}'),
+    ('\u{23f3}', '\u{23f3}'),
+    ('\u{25fd}', '\u{25fe}'),
+    ('\u{2614}', '\u{2615}'),
+    ('\u{2648}', '\u{2653}'),
+    ('\u{267f}', '\u{267f}'),
+    ('\u{2693}', '\u{2693}'),
+    ('\u{26a1}', '\u{26a1}'),
+    ('\u{26aa}', '\u{26ab}'),
+    ('\u{26bd}', '\u{26be}'),
+    ('\u{26c4}', '\u{26c5}'),
+    ('\u{26ce}', '\u{26ce}'),
+    ('\u{26d4}', '\u{26d4}'),
+    ('\u{26ea}', '\u{26ea}'),
+    ('\u{26f2}', '\u{26f3}'),
+    ('\u{26f5}', '\u{26f5}'),
+    ('\u{26fa}', '\u{26fa}'),
+    ('\u{26fd}', '\u{26fd}'),
+    ('\u{2705}', '\u{2705}'),
+    ('\u{270a}', '\u{270b}'),
+    ('\u{2728}', '\u{2728}'),
+    ('\u{274c}', '\u{274c}'),
+    ('\u{274e}', '\u{274e}'),
+    ('\u{2753}', '\u{2755}'),
+    ('\u{2757}', '\u{2757}'),
+    ('\u{2795}', '\u{2797}'),
+    ('\u{27b0}', '\u{27b0}'),
+    ('\u{27bf}', '\u{27bf}'),
+    ('\u{2b1b}', '\u{2b1c}'),
+    ('\u{2b50}', '\u{2b50}'),
+    ('\u{2b55}', '\u{2b55}'),
+    ('\u{2e80}', '\u{2e99}'),
+    ('\u{2e9b}', '\u{2ef3}'),
+    ('\u{2f00}', '\u{2fd5}'),
+    ('\u{2ff0}', '\u{2ffb}'),
+    ('\u{3000}', '\u{303e}'),
+    ('\u{3041}', '\u{3096}'),
+    ('\u{3099}', '\u{30ff}'),
+    ('\u{3105}', '\u{312f}'),
+    ('\u{3131}', '\u{318e}'),
+    ('\u{3190}', '\u{31e3}'),
+    ('\u{31f0}', '\u{321e}'),
+    ('\u{3220}', '\u{3247}'),
+    ('\u{3250}', '\u{4dbf}'),
+    ('\u{4e00}', '\u{a48c}'),
+    ('\u{a490}', '\u{a4c6}'),
+    ('\u{a960}', '\u{a97c}'),
+    ('\u{ac00}', '\u{d7a3}'),
+    ('\u{f900}', '\u{faff}'),
+    ('\u{fe10}', '\u{fe19}'),
+    ('\u{fe30}', '\u{fe52}'),
+    ('\u{fe54}', '\u{fe66}'),
+    ('\u{fe68}', '\u{fe6b}'),
+    ('\u{ff01}', '\u{ff60}'),
+    ('\u{ffe0}', '\u{ffe6}'),
+    ('\u{16fe0}', '\u{16fe4}'),
+    ('\u{16ff0}', '\u{16ff1}'),
+    ('\u{17000}', '\u{187f7}'),
+    ('\u{18800}', '\u{18cd5}'),
+    ('\u{18d00}', '\u{18d08}'),
+    ('\u{1aff0}', '\u{1aff3}'),
+    ('\u{1aff5}', '\u{1affb}'),
+    ('\u{1affd}', '\u{1affe}'),
+    ('\u{1b000}', '\u{1b122}'),
+    ('\u{1b150}', '\u{1b152}'),
+    ('\u{1b164}', '\u{1b167}'),
+    ('\u{1b170}', '\u{1b2fb}'),
+    ('\u{1f004}', '\u{1f004}'),
+    ('\u{1f0cf}', '\u{1f0cf}'),
+    ('\u{1f18e}', '\u{1f18e}'),
+    ('\u{1f191}', '\u{1f19a}'),
+    ('\u{1f200}', '\u{1f202}'),
+    ('\u{1f210}', '\u{1f23b}'),
+    ('\u{1f240}', '\u{1f248}'),
+    ('\u{1f250}', '\u{1f251}'),
+    ('\u{1f260}', '\u{1f265}'),
+    ('\u{1f300}', '\u{1f320}'),
+    ('\u{1f32d}', '\u{1f335}'),
+    ('\u{1f337}', '\u{1f37c}'),
+    ('\u{1f37e}', '\u{1f393}'),
+    ('\u{1f3a0}', '\u{1f3ca}'),
+    ('\u{1f3cf}', '\u{1f3d3}'),
+    ('\u{1f3e0}', '\u{1f3f0}'),
+    ('\u{1f3f4}', '\u{1f3f4}'),
+    ('\u{1f3f8}', '\u{1f43e}'),
+    ('\u{1f440}', '\u{1f440}'),
+    ('\u{1f442}', '\u{1f4fc}'),
+    ('\u{1f4ff}', '\u{1f53d}'),
+    ('\u{1f54b}', '\u{1f54e}'),
+    ('\u{1f550}', '\u{1f567}'),
+    ('\u{1f57a}', '\u{1f57a}'),
+    ('\u{1f595}', '\u{1f596}'),
+    ('\u{1f5a4}', '\u{1f5a4}'),
+    ('\u{1f5fb}', '\u{1f64f}'),
+    ('\u{1f680}', '\u{1f6c5}'),
+    ('\u{1f6cc}', '\u{1f6cc}'),
+    ('\u{1f6d0}', '\u{1f6d2}'),
+    ('\u{1f6d5}', '\u{1f6d7}'),
+    ('\u{1f6dd}', '\u{1f6df}'),
+    ('\u{1f6eb}', '\u{1f6ec}'),
+    ('\u{1f6f4}', '\u{1f6fc}'),
+    ('\u{1f7e0}', '\u{1f7eb}'),
+    ('\u{1f7f0}', '\u{1f7f0}'),
+    ('\u{1f90c}', '\u{1f93a}'),
+    ('\u{1f93c}', '\u{1f945}'),
+    ('\u{1f947}', '\u{1f9ff}'),
+    ('\u{1fa70}', '\u{1fa74}'),
+    ('\u{1fa78}', '\u{1fa7c}'),
+    ('\u{1fa80}', '\u{1fa86}'),
+    ('\u{1fa90}', '\u{1faac}'),
+    ('\u{1fab0}', '\u{1faba}'),
+    ('\u{1fac0}', '\u{1fac5}'),
+    ('\u{1fad0}', '\u{1fad9}'),
+    ('\u{1fae0}', '\u{1fae7}'),
+    ('\u{1faf0}', '\u{1faf6}'),
+    ('\u{20000}', '\u{2fffd}'),
+    ('\u{30000}', '\u{3fffd}'),
+];
+
+/// The ranges of chars that occupy no cell, e.g. combining marks and format
+/// chars, sorted by their first char.
+#[rustfmt::skip]
+pub(crate) const ZERO_WIDTH: &[(char, char)] = &[
+    ('\u{300}', '\u{36f}'),
+    ('\u{483}', '\u{489}'),
+    ('\u{591}', '\u{5bd}'),
+    ('\u{5bf}', '\u{5bf}'),
+    ('\u{5c1}', '\u{5c2}'),
+    ('\u{5c4}', '\u{5c5}'),
+    ('\u{5c7}', '\u{5c7}'),
+    ('\u{610}', '\u{61a}'),
+    ('\u{61c}', '\u{61c}'),
+    ('\u{64b}', '\u{65f}'),
+    ('\u{670}', '\u{670}'),
+    ('\u{6d6}', '\u{6dc}'),
+    ('\u{6df}', '\u{6e4}'),
+    ('\u{6e7}', '\u{6e8}'),
+    ('\u{6ea}', '\u{6ed}'),
+    ('\u{711}', '\u{711}'),
+    ('\u{730}', '\u{74a}'),
+    ('\u{7a6}', '\u{7b0}'),
+    ('\u{7eb}', '\u{7f3}'),
+    ('\u{7fd}', '\u{7fd}'),
+    ('\u{816}', '\u{819}'),
+    ('\u{81b}', '\u{823}'),
+    ('\u{825}', '\u{827}'),
+    ('\u{829}', '\u{82d}'),
+    ('\u{859}', '\u{85b}'),
+    ('\u{898}', '\u{89f}'),
+    ('\u{8ca}', '\u{8e1}'),
+    ('\u{8e3}', '\u{902}'),
+    ('\u{93a}', '\u{93a}'),
+    ('\u{93c}', '\u{93c}'),
+    ('\u{941}', '\u{948}'),
+    ('\u{94d}', '\u{94d}'),
+    ('\u{951}', '\u{957}'),
+    ('\u{962}', '\u{963}'),
+    ('\u{981}', '\u{981}'),
+    ('\u{9bc}', '\u{9bc}'),
+    ('\u{9c1}', '\u{9c4}'),
+    ('\u{9cd}', '\u{9cd}'),
+    ('\u{9e2}', '\u{9e3}'),
+    ('\u{9fe}', '\u{9fe}'),
+    ('\u{a01}', '\u{a02}'),
+    ('\u{a3c}', '\u{a3c}'),
+    ('\u{a41}', '\u{a42}'),
+    ('\u{a47}', '\u{a48}'),
+    ('\u{a4b}', '\u{a4d}'),
+    ('\u{a51}', '\u{a51}'),
+    ('\u{a70}', '\u{a71}'),
+    ('\u{a75}', '\u{a75}'),
+    ('\u{a81}', '\u{a82}'),
+    ('\u{abc}', '\u{abc}'),
+    ('\u{ac1}', '\u{ac5}'),
+    ('\u{ac7}', '\u{ac8}'),
+    ('\u{acd}', '\u{acd}'),
+    ('\u{ae2}', '\u{ae3}'),
+    ('\u{afa}', '\u{aff}'),
+    ('\u{b01}', '\u{b01}'),
+    ('\u{b3c}', '\u{b3c}'),
+    ('\u{b3f}', '\u{b3f}'),
+    ('\u{b41}', '\u{b44}'),
+    ('\u{b4d}', '\u{b4d}'),
+    ('\u{b55}', '\u{b56}'),
+    ('\u{b62}', '\u{b63}'),
+    ('\u{b82}', '\u{b82}'),
+    ('\u{bc0}', '\u{bc0}'),
+    ('\u{bcd}', '\u{bcd}'),
+    ('\u{c00}', '\u{c00}'),
+    ('\u{c04}', '\u{c04}'),
+    ('\u{c3c}', '\u{c3c}'),
+    ('\u{c3e}', '\u{c40}'),
+    ('\u{c46}', '\u{c48}'),
+    ('\u{c4a}', '\u{c4d}'),
+    ('\u{c55}', '\u{c56}'),
+    ('\u{c62}', '\u{c63}'),
+    ('\u{c81}', '\u{c81}'),
+    ('\u{cbc}', '\u{cbc}'),
+    ('\u{cbf}', '\u{cbf}'),
+    ('\u{cc6}', '\u{cc6}'),
+    ('\u{ccc}', '\u{ccd}'),
+    ('\u{ce2}', '\u{ce3}'),
+    ('\u{d00}', '\u{d01}'),
+    ('\u{d3b}', '\u{d3c}'),
+    ('\u{d41}', '\u{d44}'),
+    ('\u{d4d}', '\u{d4d}'),
+    ('\u{d62}', '\u{d63}'),
+    ('\u{d81}', '\u{d81}'),
+    ('\u{dca}', '\u{dca}'),
+    ('\u{dd2}', '\u{dd4}'),
+    ('\u{dd6}', '\u{dd6}'),
+    ('\u{e31}', '\u{e31}'),
+    ('\u{e34}', '\u{e3a}'),
+    ('\u{e47}', '\u{e4e}'),
+    ('\u{eb1}', '\u{eb1}'),
+    ('\u{eb4}', '\u{ebc}'),
+    ('\u{ec8}', '\u{ecd}'),
+    ('\u{f18}', '\u{f19}'),
+    ('\u{f35}', '\u{f35}'),
+    ('\u{f37}', '\u{f37}'),
+    ('\u{f39}', '\u{f39}'),
+    ('\u{f71}', '\u{f7e}'),
+    ('\u{f80}', '\u{f84}'),
+    ('\u{f86}', '\u{f87}'),
+    ('\u{f8d}', '\u{f97}'),
+    ('\u{f99}', '\u{fbc}'),
+    ('\u{fc6}', '\u{fc6}'),
+    ('\u{102d}', '\u{1030}'),
+    ('\u{1032}', '\u{1037}'),
+    ('\u{1039}', '\u{103a}'),
+    ('\u{103d}', '\u{103e}'),
+    ('\u{1058}', '\u{1059}'),
+    ('\u{105e}', '\u{1060}'),
+    ('\u{1071}', '\u{1074}'),
+    ('\u{1082}', '\u{1082}'),
+    ('\u{1085}', '\u{1086}'),
+    ('\u{108d}', '\u{108d}'),
+    ('\u{109d}', '\u{109d}'),
+    ('\u{1160}', '\u{11ff}'),
+    ('\u{135d}', '\u{135f}'),
+    ('\u{1712}', '\u{1714}'),
+    ('\u{1732}', '\u{1733}'),
+    ('\u{1752}', '\u{1753}'),
+    ('\u{1772}', '\u{1773}'),
+    ('\u{17b4}', '\u{17b5}'),
+    ('\u{17b7}', '\u{17bd}'),
+    ('\u{17c6}', '\u{17c6}'),
+    ('\u{17c9}', '\u{17d3}'),
+    ('\u{17dd}', '\u{17dd}'),
+    ('\u{180b}', '\u{180f}'),
+    ('\u{1885}', '\u{1886}'),
+    ('\u{18a9}', '\u{18a9}'),
+    ('\u{1920}', '\u{1922}'),
+    ('\u{1927}', '\u{1928}'),
+    ('\u{1932}', '\u{1932}'),
+    ('\u{1939}', '\u{193b}'),
+    ('\u{1a17}', '\u{1a18}'),
+    ('\u{1a1b}', '\u{1a1b}'),
+    ('\u{1a56}', '\u{1a56}'),
+    ('\u{1a58}', '\u{1a5e}'),
+    ('\u{1a60}', '\u{1a60}'),
+    ('\u{1a62}', '\u{1a62}'),
+    ('\u{1a65}', '\u{1a6c}'),
+    ('\u{1a73}', '\u{1a7c}'),
+    ('\u{1a7f}', '\u{1a7f}'),
+    ('\u{1ab0}', '\u{1ace}'),
+    ('\u{1b00}', '\u{1b03}'),
+    ('\u{1b34}', '\u{1b34}'),
+    ('\u{1b36}', '\u{1b3a}'),
+    ('\u{1b3c}', '\u{1b3c}'),
+    ('\u{1b42}', '\u{1b42}'),
+    ('\u{1b6b}', '\u{1b73}'),
+    ('\u{1b80}', '\u{1b81}'),
+    ('\u{1ba2}', '\u{1ba5}'),
+    ('\u{1ba8}', '\u{1ba9}'),
+    ('\u{1bab}', '\u{1bad}'),
+    ('\u{1be6}', '\u{1be6}'),
+    ('\u{1be8}', '\u{1be9}'),
+    ('\u{1bed}', '\u{1bed}'),
+    ('\u{1bef}', '\u{1bf1}'),
+    ('\u{1c2c}', '\u{1c33}'),
+    ('\u{1c36}', '\u{1c37}'),
+    ('\u{1cd0}', '\u{1cd2}'),
+    ('\u{1cd4}', '\u{1ce0}'),
+    ('\u{1ce2}', '\u{1ce8}'),
+    ('\u{1ced}', '\u{1ced}'),
+    ('\u{1cf4}', '\u{1cf4}'),
+    ('\u{1cf8}', '\u{1cf9}'),
+    ('\u{1dc0}', '\u{1dff}'),
+    ('\u{200b}', '\u{200f}'),
+    ('\u{202a}', '\u{202e}'),
+    ('\u{2060}', '\u{2064}'),
+    ('\u{2066}', '\u{206f}'),
+    ('\u{20d0}', '\u{20f0}'),
+    ('\u{2cef}', '\u{2cf1}'),
+    ('\u{2d7f}', '\u{2d7f}'),
+    ('\u{2de0}', '\u{2dff}'),
+    ('\u{302a}', '\u{302d}'),
+    ('\u{3099}', '\u{309a}'),
+    ('\u{a66f}', '\u{a672}'),
+    ('\u{a674}', '\u{a67d}'),
+    ('\u{a69e}', '\u{a69f}'),
+    ('\u{a6f0}', '\u{a6f1}'),
+    ('\u{a802}', '\u{a802}'),
+    ('\u{a806}', '\u{a806}'),
+    ('\u{a80b}', '\u{a80b}'),
+    ('\u{a825}', '\u{a826}'),
+    ('\u{a82c}', '\u{a82c}'),
+    ('\u{a8c4}', '\u{a8c5}'),
+    ('\u{a8e0}', '\u{a8f1}'),
+    ('\u{a8ff}', '\u{a8ff}'),
+    ('\u{a926}', '\u{a92d}'),
+    ('\u{a947}', '\u{a951}'),
+    ('\u{a980}', '\u{a982}'),
+    ('\u{a9b3}', '\u{a9b3}'),
+    ('\u{a9b6}', '\u{a9b9}'),
+    ('\u{a9bc}', '\u{a9bd}'),
+    ('\u{a9e5}', '\u{a9e5}'),
+    ('\u{aa29}', '\u{aa2e}'),
+    ('\u{aa31}', '\u{aa32}'),
+    ('\u{aa35}', '\u{aa36}'),
+    ('\u{aa43}', '\u{aa43}'),
+    ('\u{aa4c}', '\u{aa4c}'),
+    ('\u{aa7c}', '\u{aa7c}'),
+    ('\u{aab0}', '\u{aab0}'),
+    ('\u{aab2}', '\u{aab4}'),
+    ('\u{aab7}', '\u{aab8}'),
+    ('\u{aabe}', '\u{aabf}'),
+    ('\u{aac1}', '\u{aac1}'),
+    ('\u{aaec}', '\u{aaed}'),
+    ('\u{aaf6}', '\u{aaf6}'),
+    ('\u{abe5}', '\u{abe5}'),
+    ('\u{abe8}', '\u{abe8}'),
+    ('\u{abed}', '\u{abed}'),
+    ('\u{d7b0}', '\u{d7ff}'),
+    ('\u{fb1e}', '\u{fb1e}'),
+    ('\u{fe00}', '\u{fe0f}'),
+    ('\u{fe20}', '\u{fe2f}'),
+    ('\u{feff}', '\u{feff}'),
+    ('\u{fff9}', '\u{fffb}'),
+    ('\u{101fd}', '\u{101fd}'),
+    ('\u{102e0}', '\u{102e0}'),
+    ('\u{10376}', '\u{1037a}'),
+    ('\u{10a01}', '\u{10a03}'),
+    ('\u{10a05}', '\u{10a06}'),
+    ('\u{10a0c}', '\u{10a0f}'),
+    ('\u{10a38}', '\u{10a3a}'),
+    ('\u{10a3f}', '\u{10a3f}'),
+    ('\u{10ae5}', '\u{10ae6}'),
+    ('\u{10d24}', '\u{10d27}'),
+    ('\u{10eab}', '\u{10eac}'),
+    ('\u{10f46}', '\u{10f50}'),
+    ('\u{10f82}', '\u{10f85}'),
+    ('\u{11001}', '\u{11001}'),
+    ('\u{11038}', '\u{11046}'),
+    ('\u{11070}', '\u{11070}'),
+    ('\u{11073}', '\u{11074}'),
+    ('\u{1107f}', '\u{11081}'),
+    ('\u{110b3}', '\u{110b6}'),
+    ('\u{110b9}', '\u{110ba}'),
+    ('\u{110c2}', '\u{110c2}'),
+    ('\u{11100}', '\u{11102}'),
+    ('\u{11127}', '\u{1112b}'),
+    ('\u{1112d}', '\u{11134}'),
+    ('\u{11173}', '\u{11173}'),
+    ('\u{11180}', '\u{11181}'),
+    ('\u{111b6}', '\u{111be}'),
+    ('\u{111c9}', '\u{111cc}'),
+    ('\u{111cf}', '\u{111cf}'),
+    ('\u{1122f}', '\u{11231}'),
+    ('\u{11234}', '\u{11234}'),
+    ('\u{11236}', '\u{11237}'),
+    ('\u{1123e}', '\u{1123e}'),
+    ('\u{112df}', '\u{112df}'),
+    ('\u{112e3}', '\u{112ea}'),
+    ('\u{11300}', '\u{11301}'),
+    ('\u{1133b}', '\u{1133c}'),
+    ('\u{11340}', '\u{11340}'),
+    ('\u{11366}', '\u{1136c}'),
+    ('\u{11370}', '\u{11374}'),
+    ('\u{11438}', '\u{1143f}'),
+    ('\u{11442}', '\u{11444}'),
+    ('\u{11446}', '\u{11446}'),
+    ('\u{1145e}', '\u{1145e}'),
+    ('\u{114b3}', '\u{114b8}'),
+    ('\u{114ba}', '\u{114ba}'),
+    ('\u{114bf}', '\u{114c0}'),
+    ('\u{114c2}', '\u{114c3}'),
+    ('\u{115b2}', '\u{115b5}'),
+    ('\u{115bc}', '\u{115bd}'),
+    ('\u{115bf}', '\u{115c0}'),
+    ('\u{115dc}', '\u{115dd}'),
+    ('\u{11633}', '\u{1163a}'),
+    ('\u{1163d}', '\u{1163d}'),
+    ('\u{1163f}', '\u{11640}'),
+    ('\u{116ab}', '\u{116ab}'),
+    ('\u{116ad}', '\u{116ad}'),
+    ('\u{116b0}', '\u{116b5}'),
+    ('\u{116b7}', '\u{116b7}'),
+    ('\u{1171d}', '\u{1171f}'),
+    ('\u{11722}', '\u{11725}'),
+    ('\u{11727}', '\u{1172b}'),
+    ('\u{1182f}', '\u{11837}'),
+    ('\u{11839}', '\u{1183a}'),
+    ('\u{1193b}', '\u{1193c}'),
+    ('\u{1193e}', '\u{1193e}'),
+    ('\u{11943}', '\u{11943}'),
+    ('\u{119d4}', '\u{119d7}'),
+    ('\u{119da}', '\u{119db}'),
+    ('\u{119e0}', '\u{119e0}'),
+    ('\u{11a01}', '\u{11a0a}'),
+    ('\u{11a33}', '\u{11a38}'),
+    ('\u{11a3b}', '\u{11a3e}'),
+    ('\u{11a47}', '\u{11a47}'),
+    ('\u{11a51}', '\u{11a56}'),
+    ('\u{11a59}', '\u{11a5b}'),
+    ('\u{11a8a}', '\u{11a96}'),
+    ('\u{11a98}', '\u{11a99}'),
+    ('\u{11c30}', '\u{11c36}'),
+    ('\u{11c38}', '\u{11c3d}'),
+    ('\u{11c3f}', '\u{11c3f}'),
+    ('\u{11c92}', '\u{11ca7}'),
+    ('\u{11caa}', '\u{11cb0}'),
+    ('\u{11cb2}', '\u{11cb3}'),
+    ('\u{11cb5}', '\u{11cb6}'),
+    ('\u{11d31}', '\u{11d36}'),
+    ('\u{11d3a}', '\u{11d3a}'),
+    ('\u{11d3c}', '\u{11d3d}'),
+    ('\u{11d3f}', '\u{11d45}'),
+    ('\u{11d47}', '\u{11d47}'),
+    ('\u{11d90}', '\u{11d91}'),
+    ('\u{11d95}', '\u{11d95}'),
+    ('\u{11d97}', '\u{11d97}'),
+    ('\u{11ef3}', '\u{11ef4}'),
+    ('\u{13430}', '\u{13438}'),
+    ('\u{16af0}', '\u{16af4}'),
+    ('\u{16b30}', '\u{16b36}'),
+    ('\u{16f4f}', '\u{16f4f}'),
+    ('\u{16f8f}', '\u{16f92}'),
+    ('\u{16fe4}', '\u{16fe4}'),
+    ('\u{1bc9d}', '\u{1bc9e}'),
+    ('\u{1bca0}', '\u{1bca3}'),
+    ('\u{1cf00}', '\u{1cf2d}'),
+    ('\u{1cf30}', '\u{1cf46}'),
+    ('\u{1d167}', '\u{1d169}'),
+    ('\u{1d173}', '\u{1d182}'),
+    ('\u{1d185}', '\u{1d18b}'),
+    ('\u{1d1aa}', '\u{1d1ad}'),
+    ('\u{1d242}', '\u{1d244}'),
+    ('\u{1da00}', '\u{1da36}'),
+    ('\u{1da3b}', '\u{1da6c}'),
+    ('\u{1da75}', '\u{1da75}'),
+    ('\u{1da84}', '\u{1da84}'),
+    ('\u{1da9b}', '\u{1da9f}'),
+    ('\u{1daa1}', '\u{1daaf}'),
+    ('\u{1e000}', '\u{1e006}'),
+    ('\u{1e008}', '\u{1e018}'),
+    ('\u{1e01b}', '\u{1e021}'),
+    ('\u{1e023}', '\u{1e024}'),
+    ('\u{1e026}', '\u{1e02a}'),
+    ('\u{1e130}', '\u{1e136}'),
+    ('\u{1e2ae}', '\u{1e2ae}'),
+    ('\u{1e2ec}', '\u{1e2ef}'),
+    ('\u{1e8d0}', '\u{1e8d6}'),
+    ('\u{1e944}', '\u{1e94a}'),
+    ('\u{e0001}', '\u{e0001}'),
+    ('\u{e0020}', '\u{e007f}'),
+    ('\u{e0100}', '\u{e01ef}'),
+];
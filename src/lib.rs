@@ -10,6 +10,7 @@
 //! ## Generic Parameters
 //!
 //! - `T`: The data type of elements stored within the jagged array.
+//! - `S`: The storage backend of the rows, see [`JaggedStorage`]. Defaults to `Vec<Vec<T>>`.
 //!
 //! ## Examples
 //!
@@ -35,11 +36,29 @@
 //! - Convenient creation and manipulation of rows and elements.
 //! - Iteration and searching utilities for enhanced data processing.
 //!
+//! ## Cargo Features
+//!
+//! - `regex`: Regex search over `Jagged<char>` using the [`regex`](https://docs.rs/regex) crate.
+//!
 //! _For more details, refer to the documentation of individual types and methods._
 #![allow(clippy::module_name_repetitions)]
+pub mod error;
+pub mod history;
 pub mod index;
 pub mod jagged;
+pub mod storage;
 pub mod traits;
+pub use error::JaggedError;
 pub use index::Index2;
+pub use jagged::changes::ChangeEvent;
+pub use jagged::cursors::Cursors;
+pub use jagged::line_ending::LineEnding;
+pub use jagged::lsp::{Encoding, Position, Range, TextEdit};
+pub use jagged::marks::{Gravity, MarkId};
+pub use jagged::motions::{BigWordClassifier, CharClass, CharClassifier, DefaultClassifier};
+pub use jagged::row_find::{Direction, RowFind};
+pub use jagged::search::{Case, Pattern, SearchOptions};
+pub use jagged::selection::{Selection, SelectionKind};
 pub use jagged::Jagged;
+pub use storage::JaggedStorage;
 pub use traits::JaggedIndex;
@@ -0,0 +1,333 @@
+//! # Storage Module
+//!
+//! The `storage` module defines the [`JaggedStorage`] trait, which abstracts the
+//! container that holds the rows of a [`Jagged`](crate::Jagged) array.
+//!
+//! The default backend is `Vec<Vec<T>>`. Alternative backends can be used to trade
+//! off the cost of row insertions and removals against the cost of row lookups.
+//! [`Rope`] is a balanced tree backend for very large buffers and [`GapBuffer`]
+//! favours repeated edits around a single position.
+use std::ops::{Bound, Deref, RangeBounds};
+
+mod gap_buffer;
+mod rope;
+pub use gap_buffer::{GapBuffer, GapBufferIter, GapRows, GapRowsIter, RowEditor};
+pub use rope::{Rope, RopeElement, RopeIter, RopeRow};
+
+/// The backend that stores the rows of a jagged array.
+///
+/// Rows are accessed as `Vec<T>`. The storage decides how the rows are organized
+/// and may keep them in another form, e.g. [`Rope`] decodes its rows on access.
+///
+/// # Examples
+///
+/// ```
+/// use edtui_jagged::{Jagged, JaggedStorage};
+///
+/// let storage: Vec<Vec<i32>> = JaggedStorage::from_rows(vec![vec![1, 2], vec![3]]);
+/// let jagged = Jagged::from_storage(storage);
+/// assert_eq!(jagged.len(), 2);
+/// ```
+pub trait JaggedStorage<T>: Default {
+    /// A row yielded by [`JaggedStorage::iter_rows`]. A storage that keeps its
+    /// rows in another form may yield a row that is decoded for the iteration only.
+    type Row<'a>: Deref<Target = Vec<T>>
+    where
+        Self: 'a,
+        T: 'a;
+
+    /// An iterator over the rows of the storage.
+    type RowIter<'a>: Iterator<Item = Self::Row<'a>>
+    where
+        Self: 'a,
+        T: 'a;
+
+    /// Creates the storage from a vector of rows.
+    fn from_rows(rows: Vec<Vec<T>>) -> Self;
+
+    /// Consumes the storage and returns its rows.
+    fn into_rows(self) -> Vec<Vec<T>>;
+
+    /// Returns the number of rows.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the storage contains no rows.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to a row, or `None` if out of bounds.
+    fn row(&self, index: usize) -> Option<&Vec<T>>;
+
+    /// Returns a mutable reference to a row, or `None` if out of bounds.
+    fn row_mut(&mut self, index: usize) -> Option<&mut Vec<T>>;
+
+    /// Returns the number of elements of a row, or `None` if out of bounds.
+    fn row_len(&self, row: usize) -> Option<usize> {
+        self.row(row).map(Vec::len)
+    }
+
+    /// Folds the elements of a row into an accumulator without keeping a copy of
+    /// the row, or returns `None` if out of bounds.
+    fn fold_row<B, F>(&self, row: usize, init: B, f: F) -> Option<B>
+    where
+        F: FnMut(B, &T) -> B,
+    {
+        self.row(row).map(|row| row.iter().fold(init, f))
+    }
+
+    /// Returns a reference to the element at `col` of `row`, or `None` if out of
+    /// bounds.
+    fn element(&self, row: usize, col: usize) -> Option<&T> {
+        self.row(row)?.get(col)
+    }
+
+    /// Returns a mutable reference to the element at `col` of `row`, or `None` if
+    /// out of bounds.
+    fn element_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        self.row_mut(row)?.get_mut(col)
+    }
+
+    /// Inserts an element at `col` of `row`, shifting all elements after it.
+    ///
+    /// A storage can override the element methods to edit its rows in place,
+    /// e.g. [`GapRows`] moves the edited row into a gap buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` is out of bounds or if `col > len`.
+    fn insert_element(&mut self, row: usize, col: usize, value: T) {
+        self.row_mut(row)
+            .expect("row index out of bounds")
+            .insert(col, value);
+    }
+
+    /// Removes and returns the element at `col` of `row`, shifting all elements
+    /// after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` or `col` is out of bounds.
+    fn remove_element(&mut self, row: usize, col: usize) -> T {
+        self.row_mut(row)
+            .expect("row index out of bounds")
+            .remove(col)
+    }
+
+    /// Appends a row to the back.
+    fn push_row(&mut self, row: Vec<T>);
+
+    /// Inserts a row at `index`, shifting all rows after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    fn insert_row(&mut self, index: usize, row: Vec<T>);
+
+    /// Removes and returns the row at `index`, shifting all rows after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn remove_row(&mut self, index: usize) -> Vec<T>;
+
+    /// Shortens the storage, keeping the first `len` rows.
+    fn truncate_rows(&mut self, len: usize) {
+        if len < self.len() {
+            let _ = self.split_off_rows(len);
+        }
+    }
+
+    /// Splits the storage into two at the given row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    #[must_use]
+    fn split_off_rows(&mut self, at: usize) -> Self;
+
+    /// Moves all rows of `other` into `self`, leaving `other` empty.
+    fn append_rows(&mut self, other: &mut Self);
+
+    /// Removes the rows in `range` and returns them as a new storage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or if start is greater than end.
+    #[must_use]
+    fn drain_rows<R>(&mut self, range: R) -> Self
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = row_range(&range, self.len());
+        let mut tail = self.split_off_rows(end);
+        let drained = self.split_off_rows(start);
+        self.append_rows(&mut tail);
+        drained
+    }
+
+    /// Removes all rows.
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Returns an iterator over the rows.
+    fn iter_rows(&self) -> Self::RowIter<'_>;
+}
+
+/// Converts a range of rows into `(start, end)` with an exclusive end.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds or if start is greater than end.
+pub(crate) fn row_range<R>(range: &R, len: usize) -> (usize, usize)
+where
+    R: RangeBounds<usize>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "row range starts at {start} but ends at {end}"
+    );
+    assert!(
+        end <= len,
+        "row range end {end} out of bounds of length {len}"
+    );
+    (start, end)
+}
+
+impl<T> JaggedStorage<T> for Vec<Vec<T>> {
+    type Row<'a>
+        = &'a Vec<T>
+    where
+        T: 'a;
+
+    type RowIter<'a>
+        = std::slice::Iter<'a, Vec<T>>
+    where
+        T: 'a;
+
+    fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        rows
+    }
+
+    fn into_rows(self) -> Vec<Vec<T>> {
+        self
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn row(&self, index: usize) -> Option<&Vec<T>> {
+        self.get(index)
+    }
+
+    fn row_mut(&mut self, index: usize) -> Option<&mut Vec<T>> {
+        self.get_mut(index)
+    }
+
+    fn push_row(&mut self, row: Vec<T>) {
+        self.push(row);
+    }
+
+    fn insert_row(&mut self, index: usize, row: Vec<T>) {
+        self.insert(index, row);
+    }
+
+    fn remove_row(&mut self, index: usize) -> Vec<T> {
+        self.remove(index)
+    }
+
+    fn truncate_rows(&mut self, len: usize) {
+        self.truncate(len);
+    }
+
+    fn split_off_rows(&mut self, at: usize) -> Self {
+        self.split_off(at)
+    }
+
+    fn append_rows(&mut self, other: &mut Self) {
+        self.append(other);
+    }
+
+    fn drain_rows<R>(&mut self, range: R) -> Self
+    where
+        R: RangeBounds<usize>,
+    {
+        self.drain(range).collect()
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self);
+    }
+
+    fn iter_rows(&self) -> Self::RowIter<'_> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drain_rows_default_impl() {
+        #[derive(Default, Debug, PartialEq)]
+        struct Rows(Vec<Vec<i32>>);
+
+        impl JaggedStorage<i32> for Rows {
+            type Row<'a> = &'a Vec<i32>;
+            type RowIter<'a> = std::slice::Iter<'a, Vec<i32>>;
+
+            fn from_rows(rows: Vec<Vec<i32>>) -> Self {
+                Self(rows)
+            }
+            fn into_rows(self) -> Vec<Vec<i32>> {
+                self.0
+            }
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+            fn row(&self, index: usize) -> Option<&Vec<i32>> {
+                self.0.get(index)
+            }
+            fn row_mut(&mut self, index: usize) -> Option<&mut Vec<i32>> {
+                self.0.get_mut(index)
+            }
+            fn push_row(&mut self, row: Vec<i32>) {
+                self.0.push(row);
+            }
+            fn insert_row(&mut self, index: usize, row: Vec<i32>) {
+                self.0.insert(index, row);
+            }
+            fn remove_row(&mut self, index: usize) -> Vec<i32> {
+                self.0.remove(index)
+            }
+            fn split_off_rows(&mut self, at: usize) -> Self {
+                Self(self.0.split_off(at))
+            }
+            fn append_rows(&mut self, other: &mut Self) {
+                self.0.append(&mut other.0);
+            }
+            fn iter_rows(&self) -> Self::RowIter<'_> {
+                self.0.iter()
+            }
+        }
+
+        let mut rows = Rows::from_rows(vec![vec![1], vec![2], vec![3], vec![4]]);
+        let drained = rows.drain_rows(1..=2);
+
+        assert_eq!(drained, Rows(vec![vec![2], vec![3]]));
+        assert_eq!(rows, Rows(vec![vec![1], vec![4]]));
+    }
+}
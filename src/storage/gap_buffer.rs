@@ -0,0 +1,775 @@
+//! A gap buffer backend for [`JaggedStorage`].
+use std::{
+    fmt::Debug,
+    iter::{Chain, Enumerate, Rev},
+    slice::Iter,
+    sync::OnceLock,
+};
+
+use super::JaggedStorage;
+use crate::{Index2, Jagged};
+
+/// A sequence with a movable gap.
+///
+/// Inserting and removing at the gap is `O(1)`, moving the gap is linear in the
+/// distance it is moved. This makes repeated edits around a single cursor
+/// position amortized `O(1)`.
+///
+/// Elements before the gap are stored in order, elements behind the gap are
+/// stored in reverse order, so the gap itself never has to be initialized.
+///
+/// [`GapRows`] stores the rows of a [`Jagged`] in gap buffers.
+///
+/// # Examples
+///
+/// ```
+/// use edtui_jagged::storage::GapBuffer;
+///
+/// let mut buffer = GapBuffer::from(vec!['a', 'd']);
+/// buffer.insert(1, 'b');
+/// buffer.insert(2, 'c');
+/// assert_eq!(buffer.into_vec(), vec!['a', 'b', 'c', 'd']);
+/// ```
+#[derive(Clone)]
+pub struct GapBuffer<T> {
+    front: Vec<T>,
+    back: Vec<T>,
+}
+
+/// An iterator over the elements of a [`GapBuffer`].
+pub type GapBufferIter<'a, T> = Chain<Iter<'a, T>, Rev<Iter<'a, T>>>;
+
+impl<T> GapBuffer<T> {
+    /// Instantiates an empty [`GapBuffer`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            front: Vec::new(),
+            back: Vec::new(),
+        }
+    }
+
+    /// Returns the number of elements.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    /// Returns `true` if the buffer contains no elements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the position of the gap.
+    #[must_use]
+    pub fn gap(&self) -> usize {
+        self.front.len()
+    }
+
+    /// Moves the gap to `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn move_gap(&mut self, index: usize) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "gap index {index} out of bounds of length {len}"
+        );
+        if index < self.front.len() {
+            self.back.extend(self.front.drain(index..).rev());
+        } else {
+            let start = self.back.len() - (index - self.front.len());
+            self.front.extend(self.back.drain(start..).rev());
+        }
+    }
+
+    /// Returns a reference to the element at `index`.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.front.len() {
+            return self.front.get(index);
+        }
+        let index = index - self.front.len();
+        self.back
+            .len()
+            .checked_sub(index + 1)
+            .map(|i| &self.back[i])
+    }
+
+    /// Returns a mutable reference to the element at `index`.
+    #[must_use]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.front.len() {
+            return self.front.get_mut(index);
+        }
+        let index = index - self.front.len();
+        self.back
+            .len()
+            .checked_sub(index + 1)
+            .map(|i| &mut self.back[i])
+    }
+
+    /// Appends an element to the back, moving the gap there.
+    pub fn push(&mut self, value: T) {
+        self.move_gap(self.len());
+        self.front.push(value);
+    }
+
+    /// Inserts an element at `index`, moving the gap there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.move_gap(index);
+        self.front.push(value);
+    }
+
+    /// Removes and returns the element at `index`, moving the gap there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+        assert!(
+            index < len,
+            "removal index {index} out of bounds of length {len}"
+        );
+        self.move_gap(index);
+        self.back.pop().expect("index is in bounds")
+    }
+
+    /// Splits the buffer into two at `at`, moving the gap there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    #[must_use]
+    pub fn split_off(&mut self, at: usize) -> Self {
+        self.move_gap(at);
+        Self {
+            front: Vec::new(),
+            back: std::mem::take(&mut self.back),
+        }
+    }
+
+    /// Moves all elements of `other` to the back of `self`, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        self.move_gap(self.len());
+        self.front.append(&mut other.front);
+        self.back = std::mem::take(&mut other.back);
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        self.front.clear();
+        self.back.clear();
+    }
+
+    /// Returns an iterator over the elements.
+    pub fn iter(&self) -> GapBufferIter<'_, T> {
+        self.front.iter().chain(self.back.iter().rev())
+    }
+
+    /// Moves the gap to the end and returns the elements as a contiguous slice.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        self.move_gap(self.len());
+        &mut self.front
+    }
+
+    /// Consumes the buffer and returns its elements as a vector.
+    #[must_use]
+    pub fn into_vec(mut self) -> Vec<T> {
+        self.move_gap(self.len());
+        self.front
+    }
+}
+
+impl<T> Default for GapBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<Vec<T>> for GapBuffer<T> {
+    fn from(value: Vec<T>) -> Self {
+        Self {
+            front: value,
+            back: Vec::new(),
+        }
+    }
+}
+
+impl<T: Debug> Debug for GapBuffer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for GapBuffer<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for GapBuffer<T> {}
+
+/// A storage backend that keeps the rows in a gap buffer and the row that was
+/// edited last in a gap buffer of its own.
+///
+/// Inserting and removing rows around one row, and elements around one column
+/// with [`Jagged::insert`] and [`Jagged::remove`], is amortized `O(1)`. The gap
+/// of the edited row persists across edits, until another row is edited or the
+/// edited row is borrowed mutably or removed.
+///
+/// Rows are still read as contiguous `Vec<T>`. Reading the edited row as a
+/// whole, e.g. with [`Jagged::get`] and a [`RowIndex`](crate::index::RowIndex)
+/// or with [`Jagged::iter_row`], copies it once after every edit, unless its gap
+/// is at its end. Single elements are read in place.
+///
+/// # Examples
+///
+/// ```
+/// use edtui_jagged::{storage::GapRows, Index2, Jagged};
+///
+/// let mut data: Jagged<char, GapRows<char>> = "hello world".parse().unwrap();
+/// for (i, ch) in ", dear".chars().enumerate() {
+///     data.insert(Index2::new(0, 5 + i), ch);
+/// }
+/// assert_eq!(data.to_string(), "hello, dear world");
+/// ```
+#[derive(Clone)]
+pub struct GapRows<T> {
+    rows: GapBuffer<Vec<T>>,
+    /// The row that was edited last. Its elements are moved out of `rows`.
+    open: Option<OpenRow<T>>,
+}
+
+#[derive(Clone)]
+struct OpenRow<T> {
+    index: usize,
+    buffer: GapBuffer<T>,
+    /// A contiguous copy of the row, made when the row is read as a whole.
+    contiguous: OnceLock<Vec<T>>,
+}
+
+impl<T: Clone> OpenRow<T> {
+    /// Returns the row as a contiguous vector.
+    fn row(&self) -> &Vec<T> {
+        if self.buffer.back.is_empty() {
+            return &self.buffer.front;
+        }
+        self.contiguous
+            .get_or_init(|| self.buffer.iter().cloned().collect())
+    }
+}
+
+impl<T> GapRows<T> {
+    /// Instantiates an empty [`GapRows`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            rows: GapBuffer::new(),
+            open: None,
+        }
+    }
+
+    /// Returns the edited row if it is `row`.
+    fn open_row(&self, row: usize) -> Option<&OpenRow<T>> {
+        self.open.as_ref().filter(|open| open.index == row)
+    }
+
+    /// Moves the elements of the edited row back into `rows`.
+    fn close(&mut self) {
+        if let Some(open) = self.open.take() {
+            if let Some(row) = self.rows.get_mut(open.index) {
+                *row = open.buffer.into_vec();
+            }
+        }
+    }
+
+    /// Closes the edited row if it is `row`.
+    fn close_row(&mut self, row: usize) {
+        if self.open_row(row).is_some() {
+            self.close();
+        }
+    }
+
+    /// Returns the gap buffer of `row` for an edit, moving the row into it if
+    /// another row was edited last.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` is out of bounds.
+    fn edit(&mut self, row: usize) -> &mut GapBuffer<T> {
+        if self.open_row(row).is_none() {
+            self.close();
+            let elements = self.rows.get_mut(row).expect("row index out of bounds");
+            self.open = Some(OpenRow {
+                index: row,
+                buffer: GapBuffer::from(std::mem::take(elements)),
+                contiguous: OnceLock::new(),
+            });
+        }
+        let open = self.open.as_mut().expect("the row is edited");
+        open.contiguous.take();
+        &mut open.buffer
+    }
+}
+
+impl<T> Default for GapRows<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone + Debug> Debug for GapRows<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter_rows()).finish()
+    }
+}
+
+impl<T: Clone + PartialEq> PartialEq for GapRows<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter_rows().eq(other.iter_rows())
+    }
+}
+
+impl<T: Clone + Eq> Eq for GapRows<T> {}
+
+impl<T: Clone> JaggedStorage<T> for GapRows<T> {
+    type Row<'a>
+        = &'a Vec<T>
+    where
+        T: 'a;
+
+    type RowIter<'a>
+        = GapRowsIter<'a, T>
+    where
+        T: 'a;
+
+    fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        Self {
+            rows: GapBuffer::from(rows),
+            open: None,
+        }
+    }
+
+    fn into_rows(mut self) -> Vec<Vec<T>> {
+        self.close();
+        self.rows.into_vec()
+    }
+
+    fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn row(&self, index: usize) -> Option<&Vec<T>> {
+        match self.open_row(index) {
+            Some(open) => Some(open.row()),
+            None => self.rows.get(index),
+        }
+    }
+
+    fn row_mut(&mut self, index: usize) -> Option<&mut Vec<T>> {
+        self.close_row(index);
+        self.rows.get_mut(index)
+    }
+
+    fn row_len(&self, row: usize) -> Option<usize> {
+        match self.open_row(row) {
+            Some(open) => Some(open.buffer.len()),
+            None => self.rows.get(row).map(Vec::len),
+        }
+    }
+
+    fn fold_row<B, F>(&self, row: usize, init: B, f: F) -> Option<B>
+    where
+        F: FnMut(B, &T) -> B,
+    {
+        match self.open_row(row) {
+            Some(open) => Some(open.buffer.iter().fold(init, f)),
+            None => self.rows.get(row).map(|row| row.iter().fold(init, f)),
+        }
+    }
+
+    fn element(&self, row: usize, col: usize) -> Option<&T> {
+        match self.open_row(row) {
+            Some(open) => open.buffer.get(col),
+            None => self.rows.get(row)?.get(col),
+        }
+    }
+
+    fn element_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if self.open_row(row).is_some() {
+            return self.edit(row).get_mut(col);
+        }
+        self.rows.get_mut(row)?.get_mut(col)
+    }
+
+    fn insert_element(&mut self, row: usize, col: usize, value: T) {
+        self.edit(row).insert(col, value);
+    }
+
+    fn remove_element(&mut self, row: usize, col: usize) -> T {
+        self.edit(row).remove(col)
+    }
+
+    fn push_row(&mut self, row: Vec<T>) {
+        self.rows.push(row);
+    }
+
+    fn insert_row(&mut self, index: usize, row: Vec<T>) {
+        self.rows.insert(index, row);
+        if let Some(open) = self.open.as_mut().filter(|open| open.index >= index) {
+            open.index += 1;
+        }
+    }
+
+    fn remove_row(&mut self, index: usize) -> Vec<T> {
+        self.close_row(index);
+        let row = self.rows.remove(index);
+        if let Some(open) = self.open.as_mut().filter(|open| open.index > index) {
+            open.index -= 1;
+        }
+        row
+    }
+
+    fn split_off_rows(&mut self, at: usize) -> Self {
+        if self.open.as_ref().is_some_and(|open| open.index >= at) {
+            self.close();
+        }
+        Self {
+            rows: self.rows.split_off(at),
+            open: None,
+        }
+    }
+
+    fn append_rows(&mut self, other: &mut Self) {
+        other.close();
+        self.rows.append(&mut other.rows);
+    }
+
+    fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn iter_rows(&self) -> Self::RowIter<'_> {
+        GapRowsIter {
+            rows: self.rows.iter().enumerate(),
+            open: self.open.as_ref(),
+        }
+    }
+}
+
+/// An iterator over the rows of a [`GapRows`].
+pub struct GapRowsIter<'a, T> {
+    rows: Enumerate<GapBufferIter<'a, Vec<T>>>,
+    open: Option<&'a OpenRow<T>>,
+}
+
+impl<'a, T: Clone> Iterator for GapRowsIter<'a, T> {
+    type Item = &'a Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, row) = self.rows.next()?;
+        match self.open {
+            Some(open) if open.index == index => Some(open.row()),
+            _ => Some(row),
+        }
+    }
+}
+
+/// Batches the edits of a single row of a [`Jagged`] into a single edit.
+///
+/// The editor edits the row through the element methods of the storage, so the
+/// edits of a [`GapRows`] storage go into the gap of the row. It tracks the span
+/// of the row that is edited and reports only that span to marks and change
+/// tracking when it is dropped.
+///
+/// Created by [`Jagged::edit_row`].
+pub struct RowEditor<'a, T, S: JaggedStorage<T> = Vec<Vec<T>>> {
+    data: &'a mut Jagged<T, S>,
+    row_index: usize,
+    dirty: Option<DirtySpan>,
+}
+
+/// The edited span of a row, `start..old_end` of the original row was replaced
+/// by `start..new_end` of the edited row.
+#[derive(Debug, Clone, Copy)]
+struct DirtySpan {
+    start: usize,
+    old_end: usize,
+    new_end: usize,
+}
+
+impl<T, S: JaggedStorage<T>> RowEditor<'_, T, S> {
+    /// Returns the number of elements of the row.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.data.row_len(self.row_index).unwrap_or(0)
+    }
+
+    /// Returns `true` if the row contains no elements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the element at `col`.
+    #[must_use]
+    pub fn get(&self, col: usize) -> Option<&T> {
+        self.data.data.element(self.row_index, col)
+    }
+
+    /// Inserts an element at `col`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `col > len`.
+    pub fn insert(&mut self, col: usize, value: T) {
+        self.data.data.insert_element(self.row_index, col, value);
+        self.mark_dirty(col, 0, 1);
+    }
+
+    /// Appends an element to the row.
+    pub fn push(&mut self, value: T) {
+        let col = self.len();
+        self.insert(col, value);
+    }
+
+    /// Removes and returns the element at `col`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `col` is out of bounds.
+    pub fn remove(&mut self, col: usize) -> T {
+        let value = self.data.data.remove_element(self.row_index, col);
+        self.mark_dirty(col, 1, 0);
+        value
+    }
+
+    /// Returns a mutable reference to the element at `col`. The element is
+    /// treated as replaced.
+    #[must_use]
+    pub fn get_mut(&mut self, col: usize) -> Option<&mut T> {
+        if col < self.len() {
+            self.mark_dirty(col, 1, 1);
+        }
+        self.data.data.element_mut(self.row_index, col)
+    }
+
+    /// Extends the dirty span by an edit that replaced `removed` elements at `col`
+    /// of the edited row by `inserted` elements.
+    fn mark_dirty(&mut self, col: usize, removed: usize, inserted: usize) {
+        let end = col + removed;
+        let span = match self.dirty {
+            None => DirtySpan {
+                start: col,
+                old_end: end,
+                new_end: col + inserted,
+            },
+            Some(span) => {
+                // Columns behind the span are shifted by the edits within it.
+                let end = end.max(span.new_end);
+                DirtySpan {
+                    start: span.start.min(col),
+                    old_end: end + span.old_end - span.new_end,
+                    new_end: end + inserted - removed,
+                }
+            }
+        };
+        self.dirty = Some(span);
+    }
+}
+
+impl<T, S: JaggedStorage<T>> Drop for RowEditor<'_, T, S> {
+    fn drop(&mut self) {
+        if let Some(span) = self.dirty {
+            let row = self.row_index;
+            self.data.edited(
+                Index2::new(row, span.start),
+                Index2::new(row, span.old_end),
+                Index2::new(row, span.new_end),
+            );
+        }
+    }
+}
+
+impl<T, S: JaggedStorage<T>> Jagged<T, S> {
+    /// Returns a [`RowEditor`] that batches edits of a row into a single edit.
+    /// Returns None if the row is out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use edtui_jagged::Jagged;
+    ///
+    /// let mut data = Jagged::from("hello world");
+    /// if let Some(mut row) = data.edit_row(0) {
+    ///     for (i, ch) in ",".chars().enumerate() {
+    ///         row.insert(5 + i, ch);
+    ///     }
+    /// }
+    /// assert_eq!(data, Jagged::from("hello, world"));
+    /// ```
+    #[must_use]
+    pub fn edit_row(&mut self, row_index: usize) -> Option<RowEditor<'_, T, S>> {
+        if row_index >= self.len() {
+            return None;
+        }
+        Some(RowEditor {
+            data: self,
+            row_index,
+            dirty: None,
+        })
+    }
+}
+
+impl<T: Clone> From<Jagged<T>> for Jagged<T, GapRows<T>> {
+    /// Moves the rows into a [`GapRows`], keeping marks, recorded changes and
+    /// line endings.
+    fn from(value: Jagged<T>) -> Self {
+        value.convert_storage()
+    }
+}
+
+impl<T: Clone> From<Jagged<T, GapRows<T>>> for Jagged<T> {
+    /// Moves the rows out of a [`GapRows`], keeping marks, recorded changes and
+    /// line endings.
+    fn from(value: Jagged<T, GapRows<T>>) -> Self {
+        value.convert_storage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{index::RowIndex, Gravity};
+
+    #[test]
+    fn test_gap_buffer() {
+        let mut buffer = GapBuffer::from(vec![1, 2, 3, 4]);
+        buffer.insert(2, 9);
+        assert_eq!(buffer.gap(), 3);
+        assert_eq!(buffer.get(3), Some(&3));
+        assert_eq!(buffer.get(5), None);
+
+        assert_eq!(buffer.remove(0), 1);
+        buffer.push(5);
+        assert_eq!(
+            buffer.iter().copied().collect::<Vec<_>>(),
+            vec![2, 9, 3, 4, 5]
+        );
+
+        let mut tail = buffer.split_off(2);
+        assert_eq!(tail, GapBuffer::from(vec![3, 4, 5]));
+        tail.insert(0, 0);
+        buffer.append(&mut tail);
+        assert_eq!(buffer.make_contiguous(), &[2, 9, 0, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_gap_buffer_storage() {
+        let mut data: Jagged<char, GapRows<char>> = Jagged::from("first\n\nsecond\nthird").into();
+
+        let drained = data.extract(Index2::new(0, 2)..Index2::new(2, 2));
+        assert_eq!(drained.to_string(), "rst\n\nse");
+        assert_eq!(data.to_string(), "ficond\nthird");
+
+        data.insert(RowIndex::new(1), vec!['x']);
+        assert_eq!(data.to_string(), "ficond\nx\nthird");
+    }
+
+    #[test]
+    fn test_gap_rows_keep_the_gap() {
+        let mut data: Jagged<char, GapRows<char>> = "abcdef\nxyz".parse().unwrap();
+        data.insert(Index2::new(0, 3), '1');
+        data.insert(Index2::new(0, 4), '2');
+        assert_eq!(data.remove(Index2::new(0, 2)), 'c');
+        let open = data.data.open.as_ref().unwrap();
+        assert_eq!((open.index, open.buffer.gap()), (0, 2));
+
+        // Elements are read in place, rows are copied once.
+        assert_eq!(data.get(Index2::new(0, 3)), Some(&'2'));
+        assert_eq!(data.len_col(0), Some(7));
+        assert!(data.data.open.as_ref().unwrap().contiguous.get().is_none());
+        assert_eq!(
+            data.get(RowIndex::new(0)),
+            Some(&"ab12def".chars().collect())
+        );
+        assert_eq!(data.to_string(), "ab12def\nxyz");
+
+        // Inserting a row in front of the edited row keeps its gap.
+        data.insert(RowIndex::new(0), vec!['>']);
+        data.insert(Index2::new(1, 2), '3');
+        assert_eq!(data.data.open.as_ref().unwrap().index, 1);
+        assert_eq!(data.to_string(), ">\nab312def\nxyz");
+
+        // Editing another row moves the elements back into their row.
+        data.insert(Index2::new(2, 0), 'w');
+        assert_eq!(data.data.open.as_ref().unwrap().index, 2);
+        assert_eq!(data.data.rows.get(1), Some(&"ab312def".chars().collect()));
+        assert_eq!(data.to_string(), ">\nab312def\nwxyz");
+    }
+
+    #[test]
+    fn test_edit_row() {
+        let mut data = Jagged::from("ac\nb");
+        {
+            let mut row = data.edit_row(0).unwrap();
+            row.insert(1, 'b');
+            assert_eq!(row.remove(2), 'c');
+        }
+        assert_eq!(data, Jagged::from("ab\nb"));
+        assert!(data.edit_row(2).is_none());
+    }
+
+    #[test]
+    fn test_edit_row_reports_dirty_span() {
+        let mut data = Jagged::from("abcdef\nx");
+        let before = data.add_mark(Index2::new(0, 1), Gravity::Left);
+        let after = data.add_mark(Index2::new(0, 5), Gravity::Left);
+        data.track_changes(true);
+        {
+            let mut row = data.edit_row(0).unwrap();
+            row.insert(3, '1');
+            row.insert(4, '2');
+            assert_eq!(row.remove(2), 'c');
+            *row.get_mut(4).unwrap() = 'D';
+        }
+        assert_eq!(data.to_string(), "ab12Def\nx");
+        assert_eq!(data.mark(before), Some(Index2::new(0, 1)));
+        assert_eq!(data.mark(after), Some(Index2::new(0, 6)));
+
+        let changes: Vec<_> = data.drain_changes().collect();
+        assert_eq!(changes.len(), 1);
+        let range_removed = (Index2::new(0, 2), Index2::new(0, 4));
+        assert_eq!(changes[0].range_removed, range_removed);
+        assert_eq!(changes[0].new_end, Index2::new(0, 5));
+        assert_eq!(changes[0].inserted_len, 3);
+
+        // An editor without edits reports nothing.
+        drop(data.edit_row(1));
+        assert_eq!(data.drain_changes().count(), 0);
+    }
+
+    #[test]
+    fn test_conversion_keeps_marks_and_line_endings() {
+        let mut data = Jagged::from("a\r\nb\rc");
+        let mark = data.add_mark(Index2::new(1, 1), Gravity::Left);
+
+        let mut buffer: Jagged<char, GapRows<char>> = data.into();
+        buffer.insert(Index2::new(1, 0), 'x');
+        assert_eq!(buffer.mark(mark), Some(Index2::new(1, 2)));
+        assert_eq!(buffer.to_string(), "a\r\nxb\rc");
+
+        let data: Jagged<char> = buffer.into();
+        assert_eq!(data.mark(mark), Some(Index2::new(1, 2)));
+        assert_eq!(data.to_string(), "a\r\nxb\rc");
+    }
+}
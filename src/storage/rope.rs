@@ -0,0 +1,652 @@
+//! A balanced tree backend for [`JaggedStorage`].
+use std::{
+    fmt::Debug,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        OnceLock,
+    },
+};
+
+use super::JaggedStorage;
+use crate::Jagged;
+
+/// The number of decoded rows a [`Rope`] caches at most, the cache is dropped by
+/// the next mutable access once it grows larger.
+const CACHED_ROWS: usize = 1024;
+
+/// An element of a [`Rope`], which decides how the rows are stored.
+///
+/// Rows of chars are stored as UTF-8. Rows of other elements are stored as they
+/// are, implement the trait without overriding a method for them.
+///
+/// # Examples
+///
+/// ```
+/// use edtui_jagged::{storage::{Rope, RopeElement}, Jagged};
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct Cell(u8);
+///
+/// impl RopeElement for Cell {}
+///
+/// let data: Jagged<Cell, Rope<Cell>> = Jagged::new(vec![vec![Cell(1)]]).into();
+/// assert_eq!(data.len(), 1);
+/// ```
+pub trait RopeElement: Sized {
+    /// Encodes a row, or returns None to store the row as it is.
+    #[must_use]
+    fn encode(row: &[Self]) -> Option<Box<[u8]>> {
+        let _ = row;
+        None
+    }
+
+    /// Decodes a row that was encoded by [`RopeElement::encode`].
+    #[must_use]
+    fn decode(bytes: &[u8]) -> Vec<Self> {
+        let _ = bytes;
+        unreachable!("rows that are stored as they are are never decoded")
+    }
+}
+
+impl RopeElement for char {
+    fn encode(row: &[Self]) -> Option<Box<[u8]>> {
+        Some(
+            row.iter()
+                .collect::<String>()
+                .into_bytes()
+                .into_boxed_slice(),
+        )
+    }
+
+    fn decode(bytes: &[u8]) -> Vec<Self> {
+        std::str::from_utf8(bytes)
+            .expect("rows of chars are encoded as UTF-8")
+            .chars()
+            .collect()
+    }
+}
+
+macro_rules! impl_rope_element {
+    ($($ty:ty),*) => {
+        $(impl RopeElement for $ty {})*
+    };
+}
+
+impl_rope_element!(
+    bool, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, String
+);
+
+/// A balanced binary tree of rows.
+///
+/// Inserting, removing, splitting and merging rows at an arbitrary position is
+/// `O(log n)` in the number of rows, whereas `Vec<Vec<T>>` has to shift all rows
+/// behind the edit. In turn, a single row lookup is `O(log n)` instead of `O(1)`.
+///
+/// Rows are stored as encoded by [`RopeElement`], so a `Rope<char>` stores its
+/// rows as UTF-8 instead of four bytes per char. Iterating the rows, e.g. to
+/// convert them into a string or to search them, decodes every row for the
+/// iteration only. A row that is borrowed, e.g. by [`Jagged::get`], is decoded
+/// and cached, since the borrow must outlive the call. Once more than
+/// 1024 rows are cached, the next mutable access drops the cache. A
+/// row that is borrowed mutably is encoded again by the next edit of another row.
+///
+/// Use it as the storage of a [`Jagged`] when editing very large buffers. Parse
+/// the text into it directly, so that no `Vec<Vec<char>>` of the whole buffer is
+/// allocated.
+///
+/// # Examples
+///
+/// ```
+/// use edtui_jagged::{storage::Rope, Index2, Jagged};
+///
+/// let mut lines: Jagged<char, Rope<char>> = "hello\nworld".parse().unwrap();
+/// let tail = lines.split_off(Index2::new(0, 2));
+/// assert_eq!(lines.to_string(), "he");
+/// assert_eq!(tail.to_string(), "llo\nworld");
+/// ```
+pub struct Rope<T> {
+    root: Link<T>,
+    /// The row that was borrowed mutably last and is not encoded.
+    dirty: Option<usize>,
+    /// The number of encoded rows whose decoded row is cached.
+    cached: AtomicUsize,
+}
+
+type Link<T> = Option<Box<Node<T>>>;
+
+#[derive(Clone)]
+struct Node<T> {
+    /// The encoded row, None if the row is stored as it is.
+    encoded: Option<Box<[u8]>>,
+    /// The row if it is not encoded, otherwise the cached decoded row.
+    decoded: OnceLock<Vec<T>>,
+    priority: u64,
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T> Node<T> {
+    fn update(&mut self) {
+        self.size = 1 + size(self.left.as_deref()) + size(self.right.as_deref());
+    }
+}
+
+impl<T: RopeElement> Node<T> {
+    fn new(row: Vec<T>) -> Box<Self> {
+        let encoded = T::encode(&row);
+        let decoded = OnceLock::new();
+        if encoded.is_none() {
+            let _ = decoded.set(row);
+        }
+        Box::new(Self {
+            encoded,
+            decoded,
+            priority: next_priority(),
+            size: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    /// Returns the row, decoding it if it is not cached.
+    fn row(&self, cached: &AtomicUsize) -> &Vec<T> {
+        if let Some(row) = self.decoded.get() {
+            return row;
+        }
+        self.decoded.get_or_init(|| {
+            cached.fetch_add(1, Ordering::Relaxed);
+            T::decode(self.encoded.as_deref().unwrap_or_default())
+        })
+    }
+
+    /// Returns the row without caching it, if it is not cached yet.
+    fn read(&self) -> RopeRow<'_, T> {
+        match self.decoded.get() {
+            Some(row) => RopeRow(RowRef::Borrowed(row)),
+            None => RopeRow(RowRef::Decoded(T::decode(
+                self.encoded.as_deref().unwrap_or_default(),
+            ))),
+        }
+    }
+
+    /// Returns the row for mutation. The row stays decoded until [`Node::encode`].
+    fn row_mut(&mut self) -> &mut Vec<T> {
+        if let Some(encoded) = self.encoded.take() {
+            if self.decoded.get().is_none() {
+                let _ = self.decoded.set(T::decode(&encoded));
+            }
+        }
+        self.decoded.get_mut().expect("the row is decoded")
+    }
+
+    /// Encodes a row that was borrowed mutably.
+    fn encode(&mut self) {
+        if self.encoded.is_none() {
+            self.encoded = self.decoded.get().and_then(|row| T::encode(row));
+            if self.encoded.is_some() {
+                self.decoded.take();
+            }
+        }
+    }
+
+    fn into_row(self) -> Vec<T> {
+        let encoded = self.encoded;
+        self.decoded
+            .into_inner()
+            .unwrap_or_else(|| T::decode(encoded.as_deref().unwrap_or_default()))
+    }
+}
+
+/// Drops the cached decoded rows of the tree.
+fn drop_cache<T>(node: Option<&mut Node<T>>) {
+    if let Some(node) = node {
+        if node.encoded.is_some() {
+            node.decoded.take();
+        }
+        drop_cache(node.left.as_deref_mut());
+        drop_cache(node.right.as_deref_mut());
+    }
+}
+
+/// Returns a pseudo random priority that keeps the tree balanced.
+fn next_priority() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    // splitmix64 finalizer
+    let mut z = COUNTER
+        .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed)
+        .wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn size<T>(node: Option<&Node<T>>) -> usize {
+    node.map_or(0, |node| node.size)
+}
+
+/// Merges two trees, all rows of `left` come before the rows of `right`.
+fn merge<T>(left: Link<T>, right: Link<T>) -> Link<T> {
+    match (left, right) {
+        (None, node) | (node, None) => node,
+        (Some(mut left), Some(mut right)) => {
+            if left.priority > right.priority {
+                left.right = merge(left.right.take(), Some(right));
+                left.update();
+                Some(left)
+            } else {
+                right.left = merge(Some(left), right.left.take());
+                right.update();
+                Some(right)
+            }
+        }
+    }
+}
+
+/// Splits a tree into the first `at` rows and the remaining rows.
+fn split<T>(node: Link<T>, at: usize) -> (Link<T>, Link<T>) {
+    let Some(mut node) = node else {
+        return (None, None);
+    };
+    let left_size = size(node.left.as_deref());
+    if at <= left_size {
+        let (left, right) = split(node.left.take(), at);
+        node.left = right;
+        node.update();
+        (left, Some(node))
+    } else {
+        let (left, right) = split(node.right.take(), at - left_size - 1);
+        node.right = left;
+        node.update();
+        (Some(node), right)
+    }
+}
+
+impl<T> Rope<T> {
+    /// Instantiates an empty [`Rope`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            dirty: None,
+            cached: AtomicUsize::new(0),
+        }
+    }
+
+    fn node(&self, mut index: usize) -> Option<&Node<T>> {
+        let mut node = self.root.as_deref()?;
+        loop {
+            let left_size = size(node.left.as_deref());
+            if index < left_size {
+                node = node.left.as_deref()?;
+            } else if index == left_size {
+                return Some(node);
+            } else {
+                index -= left_size + 1;
+                node = node.right.as_deref()?;
+            }
+        }
+    }
+
+    fn node_mut(&mut self, mut index: usize) -> Option<&mut Node<T>> {
+        let mut node = self.root.as_deref_mut()?;
+        loop {
+            let left_size = size(node.left.as_deref());
+            if index < left_size {
+                node = node.left.as_deref_mut()?;
+            } else if index == left_size {
+                return Some(node);
+            } else {
+                index -= left_size + 1;
+                node = node.right.as_deref_mut()?;
+            }
+        }
+    }
+
+    fn split_at(&mut self, at: usize) -> Self {
+        let (left, right) = split(self.root.take(), at);
+        self.root = left;
+        Self {
+            root: right,
+            ..Self::new()
+        }
+    }
+}
+
+impl<T: RopeElement> Rope<T> {
+    /// Encodes the row that was borrowed mutably and drops the cache of decoded
+    /// rows if it grew larger than [`CACHED_ROWS`]. Called before every edit.
+    fn flush(&mut self) {
+        if let Some(row) = self.dirty.take() {
+            if let Some(node) = self.node_mut(row) {
+                node.encode();
+            }
+        }
+        let cached = self.cached.get_mut();
+        if *cached > CACHED_ROWS {
+            *cached = 0;
+            drop_cache(self.root.as_deref_mut());
+        }
+    }
+}
+
+impl<T> Default for Rope<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for Rope<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            dirty: self.dirty,
+            cached: AtomicUsize::new(self.cached.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl<T: RopeElement + Debug> Debug for Rope<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter_rows()).finish()
+    }
+}
+
+impl<T: RopeElement + PartialEq> PartialEq for Rope<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter_rows()
+                .zip(other.iter_rows())
+                .all(|(row, other)| *row == *other)
+    }
+}
+
+impl<T: RopeElement + Eq> Eq for Rope<T> {}
+
+impl<T: RopeElement> JaggedStorage<T> for Rope<T> {
+    type Row<'a>
+        = RopeRow<'a, T>
+    where
+        T: 'a;
+
+    type RowIter<'a>
+        = RopeIter<'a, T>
+    where
+        T: 'a;
+
+    fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let mut rope = Self::new();
+        for row in rows {
+            rope.push_row(row);
+        }
+        rope
+    }
+
+    fn into_rows(self) -> Vec<Vec<T>> {
+        fn collect<T: RopeElement>(node: Link<T>, rows: &mut Vec<Vec<T>>) {
+            if let Some(node) = node {
+                let mut node = *node;
+                let (left, right) = (node.left.take(), node.right.take());
+                collect(left, rows);
+                rows.push(node.into_row());
+                collect(right, rows);
+            }
+        }
+        let mut rows = Vec::with_capacity(self.len());
+        collect(self.root, &mut rows);
+        rows
+    }
+
+    fn len(&self) -> usize {
+        size(self.root.as_deref())
+    }
+
+    fn row(&self, index: usize) -> Option<&Vec<T>> {
+        self.node(index).map(|node| node.row(&self.cached))
+    }
+
+    fn fold_row<B, F>(&self, row: usize, init: B, f: F) -> Option<B>
+    where
+        F: FnMut(B, &T) -> B,
+    {
+        self.node(row).map(|node| node.read().iter().fold(init, f))
+    }
+
+    fn row_mut(&mut self, index: usize) -> Option<&mut Vec<T>> {
+        if index >= self.len() {
+            return None;
+        }
+        if self.dirty != Some(index) {
+            self.flush();
+            self.dirty = Some(index);
+        }
+        self.node_mut(index).map(Node::row_mut)
+    }
+
+    fn push_row(&mut self, row: Vec<T>) {
+        self.flush();
+        self.root = merge(self.root.take(), Some(Node::new(row)));
+    }
+
+    fn insert_row(&mut self, index: usize, row: Vec<T>) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index {index} out of bounds of length {len}"
+        );
+        self.flush();
+        let (left, right) = split(self.root.take(), index);
+        self.root = merge(merge(left, Some(Node::new(row))), right);
+    }
+
+    fn remove_row(&mut self, index: usize) -> Vec<T> {
+        let len = self.len();
+        assert!(
+            index < len,
+            "removal index {index} out of bounds of length {len}"
+        );
+        self.flush();
+        let (left, right) = split(self.root.take(), index);
+        let (removed, right) = split(right, 1);
+        self.root = merge(left, right);
+        removed.map(|node| node.into_row()).unwrap_or_default()
+    }
+
+    fn split_off_rows(&mut self, at: usize) -> Self {
+        let len = self.len();
+        assert!(at <= len, "split index {at} out of bounds of length {len}");
+        self.flush();
+        self.split_at(at)
+    }
+
+    fn append_rows(&mut self, other: &mut Self) {
+        self.flush();
+        other.flush();
+        self.root = merge(self.root.take(), other.root.take());
+    }
+
+    fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn iter_rows(&self) -> Self::RowIter<'_> {
+        let mut iter = RopeIter { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+}
+
+/// An in-order iterator over the rows of a [`Rope`].
+pub struct RopeIter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T> RopeIter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a Node<T>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T: RopeElement> Iterator for RopeIter<'a, T> {
+    type Item = RopeRow<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        Some(node.read())
+    }
+}
+
+/// A row yielded by [`RopeIter`]. A row that is not cached is decoded for the
+/// iteration only and dropped with the `RopeRow`.
+pub struct RopeRow<'a, T>(RowRef<'a, T>);
+
+enum RowRef<'a, T> {
+    Borrowed(&'a Vec<T>),
+    Decoded(Vec<T>),
+}
+
+impl<T> Deref for RopeRow<'_, T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        match &self.0 {
+            RowRef::Borrowed(row) => row,
+            RowRef::Decoded(row) => row,
+        }
+    }
+}
+
+impl<T: Debug> Debug for RopeRow<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: RopeElement> From<Jagged<T>> for Jagged<T, Rope<T>> {
+    /// Moves the rows into a [`Rope`], keeping marks, recorded changes and line
+    /// endings.
+    fn from(value: Jagged<T>) -> Self {
+        value.convert_storage()
+    }
+}
+
+impl<T: RopeElement> From<Jagged<T, Rope<T>>> for Jagged<T> {
+    /// Moves the rows out of a [`Rope`], keeping marks, recorded changes and line
+    /// endings.
+    fn from(value: Jagged<T, Rope<T>>) -> Self {
+        value.convert_storage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{index::RowIndex, Gravity, Index2};
+
+    fn rope(text: &str) -> Jagged<char, Rope<char>> {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_row_operations() {
+        let mut rows = Rope::from_rows((0..100).map(|i| vec![i]).collect());
+        assert_eq!(rows.len(), 100);
+        assert_eq!(rows.row(42), Some(&vec![42]));
+
+        rows.insert_row(10, vec![-1]);
+        assert_eq!(rows.row(10), Some(&vec![-1]));
+        assert_eq!(rows.row(11), Some(&vec![10]));
+
+        assert_eq!(rows.remove_row(10), vec![-1]);
+        let tail = rows.split_off_rows(50);
+        assert_eq!(rows.len(), 50);
+        assert_eq!(tail.row(0), Some(&vec![50]));
+
+        let drained = rows.drain_rows(1..49);
+        assert_eq!(drained.len(), 48);
+        assert_eq!(rows.into_rows(), vec![vec![0], vec![49]]);
+    }
+
+    #[test]
+    fn test_extract() {
+        let mut data = rope("first\n\nsecond\nthird");
+        let drained = data.extract(Index2::new(0, 2)..Index2::new(2, 2));
+
+        assert_eq!(drained, rope("rst\n\nse"));
+        assert_eq!(data, rope("ficond\nthird"));
+    }
+
+    #[test]
+    fn test_split_off_and_merge() {
+        let mut data = rope("Hello\nWorld");
+        let mut tail = data.split_off(Index2::new(0, 3));
+        assert_eq!(data.copy_range(..), rope("Hel"));
+        assert_eq!(tail, rope("lo\nWorld"));
+
+        data.merge(&mut tail);
+        assert_eq!(data, rope("Hello\nWorld"));
+        assert_eq!(data.get(Index2::new(1, 0)), Some(&'W'));
+    }
+
+    #[test]
+    fn test_rows_are_stored_as_utf8() {
+        let mut data = rope("héllo\nworld");
+        let encoded = |data: &Jagged<char, Rope<char>>, row| {
+            let node = data.data.node(row).unwrap();
+            node.encoded.as_deref().map(<[u8]>::to_vec)
+        };
+        assert_eq!(encoded(&data, 0), Some("héllo".as_bytes().to_vec()));
+
+        // A row that is borrowed mutably is encoded again by an edit of another row.
+        data.insert(Index2::new(0, 0), 'x');
+        assert_eq!(encoded(&data, 0), None);
+        data.insert(Index2::new(1, 0), 'y');
+        assert_eq!(encoded(&data, 0), Some("xhéllo".as_bytes().to_vec()));
+        assert!(data.data.node(0).unwrap().decoded.get().is_none());
+        assert_eq!(data.to_string(), "xhéllo\nyworld");
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let text = "row\n".repeat(2 * CACHED_ROWS);
+        let mut data = rope(&text);
+        let cached = |data: &Jagged<char, Rope<char>>| data.data.cached.load(Ordering::Relaxed);
+
+        // Iterating the rows does not cache them.
+        assert_eq!(data.to_string(), text);
+        assert_eq!(data.iter_row().count(), 2 * CACHED_ROWS + 1);
+        assert_eq!(cached(&data), 0);
+        assert!(data.data.node(0).unwrap().decoded.get().is_none());
+
+        // Borrowed rows are cached up to the next mutable access.
+        for row in 0..data.len() {
+            assert!(data.get(RowIndex::new(row)).is_some());
+        }
+        assert_eq!(cached(&data), 2 * CACHED_ROWS + 1);
+        data.push('!');
+        assert!(cached(&data) <= CACHED_ROWS);
+        assert!(data.data.node(0).unwrap().decoded.get().is_none());
+        assert_eq!(data.get(Index2::new(0, 0)), Some(&'r'));
+    }
+
+    #[test]
+    fn test_conversion_keeps_marks_and_line_endings() {
+        let mut data = Jagged::from("a\r\nb");
+        let mark = data.add_mark(Index2::new(1, 1), Gravity::Left);
+
+        let mut rope: Jagged<char, Rope<char>> = data.into();
+        rope.insert(Index2::new(1, 0), 'x');
+        assert_eq!(rope.mark(mark), Some(Index2::new(1, 2)));
+        assert_eq!(rope.to_string(), "a\r\nxb");
+
+        let data: Jagged<char> = rope.into();
+        assert_eq!(data.mark(mark), Some(Index2::new(1, 2)));
+        assert_eq!(data.to_string(), "a\r\nxb");
+    }
+}
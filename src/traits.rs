@@ -2,69 +2,171 @@
 //!
 //! The `traits` module defines traits used by the `edtui_jagged` library for
 //! specific functionalities.
-use crate::{index::RowIndex, Index2, Jagged};
+use std::ops::Range;
+
+use crate::{index::RowIndex, Index2, Jagged, JaggedError, JaggedStorage};
 
 /// A helper trait used for indexing operations of a jagged array.
-pub trait JaggedIndex<T> {
+///
+/// `S` is the storage backend of the array. It defaults to `Vec<Vec<T>>`, so
+/// implementations for the default storage keep their signatures.
+pub trait JaggedIndex<T, S = Vec<Vec<T>>> {
     type Output: Sized;
 
-    fn get(self, array: &Jagged<T>) -> Option<&Self::Output>;
-    fn get_mut(self, array: &mut Jagged<T>) -> Option<&mut Self::Output>;
+    fn get(self, array: &Jagged<T, S>) -> Option<&Self::Output>;
+    fn get_mut(self, array: &mut Jagged<T, S>) -> Option<&mut Self::Output>;
+
+    /// Returns the rows that a mutable borrow at this index may change.
+    ///
+    /// [`Jagged::get_mut`] invalidates the caches of these rows. The default
+    /// invalidates the caches of every row.
+    fn rows(&self) -> Range<usize> {
+        0..usize::MAX
+    }
+
+    /// Checks whether a slice can be inserted at this index.
+    ///
+    /// The default accepts every index, so the fallible methods of [`Jagged`]
+    /// panic like their infallible variants for an index that is out of bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index is out of bounds for an insertion.
+    fn check_insert(&self, _array: &Jagged<T, S>) -> Result<(), JaggedError> {
+        Ok(())
+    }
 }
 
-pub trait JaggedRemove<T> {
+/// A helper trait used for remove operations of a jagged array.
+///
+/// `S` is the storage backend of the array, see [`JaggedIndex`].
+pub trait JaggedRemove<T, S = Vec<Vec<T>>> {
     type Output: Sized;
-    fn remove(self, array: &mut Jagged<T>) -> Self::Output;
+    fn remove(self, array: &mut Jagged<T, S>) -> Self::Output;
+
+    /// Returns the start and the (exclusive) end of the elements that are removed at
+    /// this index, where a line break counts as one element.
+    ///
+    /// The range may be larger than the removed elements. The default covers the
+    /// whole array.
+    fn removed_range(&self, array: &Jagged<T, S>) -> (Index2, Index2)
+    where
+        S: JaggedStorage<T>,
+    {
+        (Index2::default(), array.end_index())
+    }
+
+    /// Checks whether the element at this index can be removed.
+    ///
+    /// The default accepts every index, see [`JaggedIndex::check_insert`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index is out of bounds.
+    fn check_remove(&self, _array: &Jagged<T, S>) -> Result<(), JaggedError> {
+        Ok(())
+    }
 }
 
-impl<T> JaggedIndex<T> for Index2 {
+impl<T, S: JaggedStorage<T>> JaggedIndex<T, S> for Index2 {
     type Output = T;
 
-    fn get(self, array: &Jagged<T>) -> Option<&Self::Output> {
-        array.data.get(self.row).and_then(|line| line.get(self.col))
+    fn get(self, array: &Jagged<T, S>) -> Option<&Self::Output> {
+        array.data.element(self.row, self.col)
     }
 
-    fn get_mut(self, array: &mut Jagged<T>) -> Option<&mut Self::Output> {
-        array
-            .data
-            .get_mut(self.row)
-            .and_then(|line| line.get_mut(self.col))
+    fn get_mut(self, array: &mut Jagged<T, S>) -> Option<&mut Self::Output> {
+        array.data.element_mut(self.row, self.col)
+    }
+
+    fn rows(&self) -> Range<usize> {
+        self.row..self.row + 1
+    }
+
+    fn check_insert(&self, array: &Jagged<T, S>) -> Result<(), JaggedError> {
+        array.check_position(*self)
     }
 }
 
-impl<T> JaggedRemove<T> for Index2 {
+impl Index2 {
+    /// Inserts `value` at this index of `array` and records the edit.
+    fn insert_element<T, S: JaggedStorage<T>>(self, array: &mut Jagged<T, S>, value: T) {
+        array.data.insert_element(self.row, self.col, value);
+        array.edited(self, self, Index2::new(self.row, self.col + 1));
+    }
+}
+
+impl<T, S: JaggedStorage<T>> JaggedRemove<T, S> for Index2 {
     type Output = T;
 
-    fn remove(self, array: &mut Jagged<T>) -> Self::Output {
-        array.data[self.row].remove(self.col)
+    fn remove(self, array: &mut Jagged<T, S>) -> Self::Output {
+        let removed = array.data.remove_element(self.row, self.col);
+        array.edited(self, Index2::new(self.row, self.col + 1), self);
+        removed
+    }
+
+    fn removed_range(&self, _array: &Jagged<T, S>) -> (Index2, Index2) {
+        (*self, Index2::new(self.row, self.col + 1))
+    }
+
+    fn check_remove(&self, array: &Jagged<T, S>) -> Result<(), JaggedError> {
+        let len = array.check_row(self.row)?;
+        if self.col >= len {
+            return Err(JaggedError::ColOutOfBounds { index: *self, len });
+        }
+        Ok(())
     }
 }
 
-impl<T> JaggedIndex<T> for RowIndex {
+impl<T, S: JaggedStorage<T>> JaggedIndex<T, S> for RowIndex {
     type Output = Vec<T>;
 
-    fn get(self, array: &Jagged<T>) -> Option<&Self::Output> {
-        array.data.get(self.0)
+    fn get(self, array: &Jagged<T, S>) -> Option<&Self::Output> {
+        array.data.row(self.0)
+    }
+
+    fn get_mut(self, array: &mut Jagged<T, S>) -> Option<&mut Self::Output> {
+        array.data.row_mut(self.0)
+    }
+
+    fn rows(&self) -> Range<usize> {
+        self.0..self.0 + 1
     }
 
-    fn get_mut(self, array: &mut Jagged<T>) -> Option<&mut Self::Output> {
-        array.data.get_mut(self.0)
+    fn check_insert(&self, array: &Jagged<T, S>) -> Result<(), JaggedError> {
+        if self.0 > array.len() {
+            return Err(JaggedError::RowOutOfBounds {
+                row: self.0,
+                len: array.len(),
+            });
+        }
+        Ok(())
     }
 }
 
-impl<T> JaggedRemove<T> for RowIndex {
+impl<T, S: JaggedStorage<T>> JaggedRemove<T, S> for RowIndex {
     type Output = Vec<T>;
 
-    fn remove(self, array: &mut Jagged<T>) -> Self::Output {
-        array.data.remove(self.0)
+    fn remove(self, array: &mut Jagged<T, S>) -> Self::Output {
+        array.splice_rows(self.0, 1, S::default()).remove_row(0)
+    }
+
+    fn removed_range(&self, array: &Jagged<T, S>) -> (Index2, Index2) {
+        array.rows_range(self.0, 1)
+    }
+
+    fn check_remove(&self, array: &Jagged<T, S>) -> Result<(), JaggedError> {
+        array.check_row(self.0).map(|_| ())
     }
 }
 
 /// A helper trait used for data operations of a jagged array.
-pub trait JaggedSlice<T> {
-    type Index: JaggedIndex<T>;
-    fn push_into(self, array: &mut Jagged<T>);
-    fn insert_into(self, index: Self::Index, array: &mut Jagged<T>);
+///
+/// `S` is the storage backend of the array, see [`JaggedIndex`].
+pub trait JaggedSlice<T, S = Vec<Vec<T>>> {
+    type Index: JaggedIndex<T, S>;
+    fn push_into(self, array: &mut Jagged<T, S>);
+    fn insert_into(self, index: Self::Index, array: &mut Jagged<T, S>);
 }
 
 /// An index representing a specific row in a jagged array.
@@ -87,46 +189,47 @@ impl<T> RowSlice<T> {
     }
 }
 
-impl<T> JaggedSlice<T> for T {
+impl<T, S: JaggedStorage<T>> JaggedSlice<T, S> for T {
     type Index = Index2;
 
-    fn push_into(self, array: &mut Jagged<T>) {
-        if let Some(row) = array.get_mut(RowIndex::new(array.len().saturating_sub(1))) {
-            row.push(self);
+    fn push_into(self, array: &mut Jagged<T, S>) {
+        let row = array.len().saturating_sub(1);
+        if let Some(col) = array.len_col(row) {
+            Index2::new(row, col).insert_element(array, self);
         }
     }
 
-    fn insert_into(self, index: Self::Index, array: &mut Jagged<T>) {
-        if let Some(line) = array.get_mut(RowIndex::new(index.row)) {
-            line.insert(index.col, self);
+    fn insert_into(self, index: Self::Index, array: &mut Jagged<T, S>) {
+        if index.row < array.len() {
+            index.insert_element(array, self);
         }
     }
 }
 
-impl<T> JaggedSlice<T> for RowSlice<T>
+impl<T, S: JaggedStorage<T>> JaggedSlice<T, S> for RowSlice<T>
 // where
 //     T: std::fmt::Debug + Clone,
 {
     type Index = RowIndex;
 
-    fn push_into(self, array: &mut Jagged<T>) {
-        array.data.push(self.data);
+    fn push_into(self, array: &mut Jagged<T, S>) {
+        self.data.push_into(array);
     }
 
-    fn insert_into(self, index: Self::Index, array: &mut Jagged<T>) {
-        array.data.insert(index.0, self.data);
+    fn insert_into(self, index: Self::Index, array: &mut Jagged<T, S>) {
+        self.data.insert_into(index, array);
     }
 }
 
-impl<T> JaggedSlice<T> for Vec<T> {
+impl<T, S: JaggedStorage<T>> JaggedSlice<T, S> for Vec<T> {
     type Index = RowIndex;
 
-    fn push_into(self, array: &mut Jagged<T>) {
-        array.data.push(self);
+    fn push_into(self, array: &mut Jagged<T, S>) {
+        let _ = array.splice_rows(array.len(), 0, S::from_rows(vec![self]));
     }
 
-    fn insert_into(self, index: Self::Index, array: &mut Jagged<T>) {
-        array.data.insert(index.0, self);
+    fn insert_into(self, index: Self::Index, array: &mut Jagged<T, S>) {
+        let _ = array.splice_rows(index.0, 0, S::from_rows(vec![self]));
     }
 }
 
@@ -155,4 +258,28 @@ mod tests {
 
         assert_eq!(index.get(&data), Some(&vec!['W', 'o', 'r', 'l', 'd']));
     }
+
+    #[test]
+    fn test_index_for_default_storage() {
+        // An index that is only implemented for the default storage.
+        struct LastRow;
+
+        impl JaggedIndex<char> for LastRow {
+            type Output = Vec<char>;
+
+            fn get(self, array: &Jagged<char>) -> Option<&Self::Output> {
+                array.get(RowIndex::new(array.len().checked_sub(1)?))
+            }
+
+            fn get_mut(self, array: &mut Jagged<char>) -> Option<&mut Self::Output> {
+                array.get_mut(RowIndex::new(array.len().checked_sub(1)?))
+            }
+        }
+
+        let mut data = test_data();
+        data.get_mut(LastRow).unwrap().push('!');
+        assert_eq!(data.get(LastRow), Some(&"World!".chars().collect()));
+        assert_eq!(LastRow.rows(), 0..usize::MAX);
+        assert_eq!(LastRow.check_insert(&data), Ok(()));
+    }
 }